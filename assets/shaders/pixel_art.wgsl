@@ -1,41 +1,24 @@
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+// Struct generata da `PostProcessSettings` in pp.rs (shader_params!)
+#import xirai::post_process::PostProcessSettings
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
 
-struct PostProcessSettings {
-    // gruppo 0 (16 bytes)
-    edge_denoise: f32,
-    edge_intensity: f32,
-    color_levels: f32,
-    cel_levels: f32,
-
-    // gruppo 1 (16 bytes)
-    contrast: f32,
-    saturation: f32,
-    scanline_intensity: f32,
-    _pad1: f32,
-
-    // gruppo 2 (16 bytes)
-    window_size: vec2<f32>,
-    _pad2: vec2<f32>,
-
-    // gruppo 3 (16 bytes)
-    dithering_strength: f32,
-    edge_threshold: f32,
-    color_snap_strength: f32,
-    _pad3: f32,
-
-    // palette
-    palette: array<vec4<f32>, 32>,
-
-    // ultimo gruppo:
-    // x = color_count
-    // y = palette_enabled (1 = ON, 0 = OFF)
-    color_count_and_pad: vec4<u32>,
-};
-
 @group(0) @binding(2) var<uniform> settings: PostProcessSettings;
+@group(0) @binding(3) var blue_noise_texture: texture_2d<f32>;
+
+const DISTANCE_LUMINANCE: u32 = 0u;
+const DISTANCE_RGB: u32 = 1u;
+const DISTANCE_WEIGHTED_RGB: u32 = 2u;
+const DISTANCE_CIELAB: u32 = 3u;
+const DISTANCE_OKLAB: u32 = 4u;
+
+const DITHER_NONE: u32 = 0u;
+const DITHER_BAYER2: u32 = 1u;
+const DITHER_BAYER4: u32 = 2u;
+const DITHER_BAYER8: u32 = 3u;
+const DITHER_BLUE_NOISE: u32 = 4u;
 
 
 // Trova il colore più vicino nella palette
@@ -58,16 +41,63 @@ fn nearest_color(gray_value: f32) -> vec3<f32> {
 }
 
 
+fn cbrt(x: f32) -> f32 {
+    return pow(max(x, 0.0), 1.0 / 3.0);
+}
+
+// CIELAB (D65) da RGB lineare
+fn rgb_to_cielab(c: vec3<f32>) -> vec3<f32> {
+    let xyz = vec3<f32>(
+        dot(c, vec3<f32>(0.4124564, 0.3575761, 0.1804375)),
+        dot(c, vec3<f32>(0.2126729, 0.7151522, 0.0721750)),
+        dot(c, vec3<f32>(0.0193339, 0.1191920, 0.9503041)),
+    ) / vec3<f32>(0.95047, 1.0, 1.08883);
+    let linear_part = xyz * 7.787 + vec3<f32>(16.0 / 116.0);
+    let f = select(linear_part, vec3<f32>(cbrt(xyz.x), cbrt(xyz.y), cbrt(xyz.z)), xyz > vec3<f32>(0.008856));
+    return vec3<f32>(116.0 * f.y - 16.0, 500.0 * (f.x - f.y), 200.0 * (f.y - f.z));
+}
+
+// OKLab da RGB lineare
+fn rgb_to_oklab(c: vec3<f32>) -> vec3<f32> {
+    let l = cbrt(dot(c, vec3<f32>(0.4122214708, 0.5363325363, 0.0514459929)));
+    let m = cbrt(dot(c, vec3<f32>(0.2119034982, 0.6806995451, 0.1073969566)));
+    let s = cbrt(dot(c, vec3<f32>(0.0883024619, 0.2817188376, 0.6299787005)));
+    return vec3<f32>(
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    );
+}
+
+// Porta il colore nello spazio in cui si misura la distanza
+fn to_distance_space(c: vec3<f32>) -> vec3<f32> {
+    switch settings.color_distance {
+        case DISTANCE_CIELAB: { return rgb_to_cielab(c); }
+        case DISTANCE_OKLAB: { return rgb_to_oklab(c); }
+        default: { return c; }
+    }
+}
+
+fn color_distance(a: vec3<f32>, b: vec3<f32>) -> f32 {
+    let diff = a - b;
+    if settings.color_distance == DISTANCE_WEIGHTED_RGB {
+        // "redmean": pesa il verde di più e sposta il peso fra rosso e blu
+        let r = (a.r + b.r) * 0.5;
+        return dot(diff * diff, vec3<f32>(2.0 + r, 4.0, 3.0 - r));
+    }
+    return dot(diff, diff);
+}
+
 fn nearest_color_rgb(color: vec3<f32>) -> vec3<f32> {
     var min_dist: f32 = 1.0e8;
     var best: vec3<f32> = color;
+    let target_color = to_distance_space(color);
 
     let count = i32(settings.color_count_and_pad.x);
 
     for (var i: i32 = 0; i < count && i < 32; i = i + 1) {
         let palette_color = settings.palette[i].rgb;
-        let diff = color - palette_color;
-        let d = dot(diff, diff);
+        let d = color_distance(target_color, to_distance_space(palette_color));
         if (d < min_dist) {
             min_dist = d;
             best = palette_color;
@@ -77,22 +107,34 @@ fn nearest_color_rgb(color: vec3<f32>) -> vec3<f32> {
 }
 
 
-// Dithering
-fn ordered_dither(uv: vec2<f32>, value: f32) -> f32 {
-    let bayer = array<f32, 16>(
-        0.0/16.0,  8.0/16.0,  2.0/16.0, 10.0/16.0,
-        12.0/16.0, 4.0/16.0, 14.0/16.0,  6.0/16.0,
-        3.0/16.0, 11.0/16.0,  1.0/16.0,  9.0/16.0,
-        15.0/16.0, 7.0/16.0, 13.0/16.0,  5.0/16.0
-    );
+// Dithering: soglia in [0, 1) per il pixel
+fn bayer_threshold(pixel: vec2<u32>, bits: u32) -> f32 {
+    // Matrice di Bayer ricorsiva: i bit bassi delle coordinate sono i più significativi
+    var value: u32 = 0u;
+    for (var i: u32 = 0u; i < bits; i = i + 1u) {
+        let x = (pixel.x >> i) & 1u;
+        let y = (pixel.y >> i) & 1u;
+        value = value | ((((x ^ y) << 1u) | y) << (2u * (bits - 1u - i)));
+    }
+    return f32(value) / f32(1u << (2u * bits));
+}
 
-    let pixel_pos = uv * settings.window_size;
-    let x = i32(pixel_pos.x) % 4;
-    let y = i32(pixel_pos.y) % 4;
-    let index = y * 4 + x;
+fn dither_threshold(uv: vec2<f32>) -> f32 {
+    let pixel = vec2<u32>(uv * settings.window_size);
+    switch settings.dither_mode {
+        case DITHER_BAYER2: { return bayer_threshold(pixel, 1u); }
+        case DITHER_BAYER4: { return bayer_threshold(pixel, 2u); }
+        case DITHER_BAYER8: { return bayer_threshold(pixel, 3u); }
+        case DITHER_BLUE_NOISE: {
+            let size = textureDimensions(blue_noise_texture);
+            return textureLoad(blue_noise_texture, pixel % size, 0).r;
+        }
+        default: { return 0.5; }
+    }
+}
 
-    let dither_value = (bayer[index] - 0.5) * settings.dithering_strength;
-    return clamp(value + dither_value, 0.0, 1.0);
+fn dither_offset(uv: vec2<f32>) -> f32 {
+    return (dither_threshold(uv) - 0.5) * settings.dithering_strength;
 }
 
 
@@ -152,13 +194,20 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     }
 
     // 5. Dithering
-    lum = ordered_dither(uv, lum);
+    let dither = dither_offset(uv);
+    let base_lum = dot(saturated_color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let shaded_color = saturated_color * (lum / max(base_lum, 0.0001));
+    lum = clamp(lum + dither, 0.0, 1.0);
 
     // 6. Quantizzazione / palette
     var final_color: vec3<f32>;
     if settings.color_count_and_pad.y == 1u {
         // Palette attiva
-        final_color = nearest_color(lum);
+        if settings.color_distance == DISTANCE_LUMINANCE {
+            final_color = nearest_color(lum);
+        } else {
+            final_color = nearest_color_rgb(clamp(shaded_color + vec3<f32>(dither), vec3<f32>(0.0), vec3<f32>(1.0)));
+        }
     } else {
         // Palette disattivata: mantieni il colore originale corretto
         final_color = saturated_color;
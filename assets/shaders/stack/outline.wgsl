@@ -0,0 +1,35 @@
+// Passata dello stack: scurisce i bordi trovati con Sobel sulla luminanza.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import xirai::stack::StackView
+#import xirai::stack::outline::OutlineParams
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> view: StackView;
+@group(0) @binding(3) var<uniform> params: OutlineParams;
+
+fn luminance(uv: vec2<f32>) -> f32 {
+    return dot(textureSample(screen_texture, texture_sampler, uv).rgb, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let threshold = params.threshold;
+    let step = max(params.thickness, 1.0) / view.resolution;
+
+    let tl = luminance(in.uv + vec2<f32>(-step.x, -step.y));
+    let tm = luminance(in.uv + vec2<f32>(0.0, -step.y));
+    let tr = luminance(in.uv + vec2<f32>(step.x, -step.y));
+    let ml = luminance(in.uv + vec2<f32>(-step.x, 0.0));
+    let mr = luminance(in.uv + vec2<f32>(step.x, 0.0));
+    let bl = luminance(in.uv + vec2<f32>(-step.x, step.y));
+    let bm = luminance(in.uv + vec2<f32>(0.0, step.y));
+    let br = luminance(in.uv + vec2<f32>(step.x, step.y));
+
+    let sobel_x = -tl + tr - 2.0 * ml + 2.0 * mr - bl + br;
+    let sobel_y = -tl - 2.0 * tm - tr + bl + 2.0 * bm + br;
+    let edge = smoothstep(threshold, threshold * 1.5 + 0.001, length(vec2<f32>(sobel_x, sobel_y)));
+
+    let color = textureSample(screen_texture, texture_sampler, in.uv).rgb;
+    return vec4<f32>(mix(color, vec3<f32>(0.0), edge * params.strength), 1.0);
+}
@@ -0,0 +1,85 @@
+// Passata dello stack: toni pastello (HSV), livelli morbidi e bordi Sobel sfumati.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import xirai::stack::StackView
+#import xirai::stack::pastel::PastelParams
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> view: StackView;
+@group(0) @binding(3) var<uniform> params: PastelParams;
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+fn luminance_at(uv: vec2<f32>) -> f32 {
+    return luminance(textureSample(screen_texture, texture_sampler, uv).rgb);
+}
+
+fn rgb_to_hsv(rgb: vec3<f32>) -> vec3<f32> {
+    let c_max = max(max(rgb.r, rgb.g), rgb.b);
+    let delta = c_max - min(min(rgb.r, rgb.g), rgb.b);
+    var hue = 0.0;
+    if (delta > 0.0001) {
+        if (c_max == rgb.r) {
+            hue = (rgb.g - rgb.b) / delta;
+        } else if (c_max == rgb.g) {
+            hue = (rgb.b - rgb.r) / delta + 2.0;
+        } else {
+            hue = (rgb.r - rgb.g) / delta + 4.0;
+        }
+    }
+    hue = fract(hue / 6.0);
+    let saturation = select(0.0, delta / c_max, c_max > 0.0001);
+    return vec3<f32>(hue, saturation, c_max);
+}
+
+fn hsv_to_rgb(hsv: vec3<f32>) -> vec3<f32> {
+    let k = vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0);
+    let p = abs(fract(hsv.xxx + k) * 6.0 - 3.0);
+    return hsv.z * mix(vec3<f32>(1.0), clamp(p - 1.0, vec3<f32>(0.0), vec3<f32>(1.0)), hsv.y);
+}
+
+// Livelli con transizione morbida a metà gradino invece di un taglio netto.
+fn soft_levels(color: vec3<f32>, levels: f32) -> vec3<f32> {
+    let scaled = color * levels;
+    let t = smoothstep(vec3<f32>(0.4), vec3<f32>(0.6), fract(scaled));
+    return (floor(scaled) + t) / levels;
+}
+
+fn sobel(uv: vec2<f32>) -> f32 {
+    let texel = 1.0 / view.resolution;
+    let tl = luminance_at(uv + vec2<f32>(-texel.x, -texel.y));
+    let tm = luminance_at(uv + vec2<f32>(0.0, -texel.y));
+    let tr = luminance_at(uv + vec2<f32>(texel.x, -texel.y));
+    let ml = luminance_at(uv + vec2<f32>(-texel.x, 0.0));
+    let mr = luminance_at(uv + vec2<f32>(texel.x, 0.0));
+    let bl = luminance_at(uv + vec2<f32>(-texel.x, texel.y));
+    let bm = luminance_at(uv + vec2<f32>(0.0, texel.y));
+    let br = luminance_at(uv + vec2<f32>(texel.x, texel.y));
+    let sobel_x = -tl + tr - 2.0 * ml + 2.0 * mr - bl + br;
+    let sobel_y = -tl - 2.0 * tm - tr + bl + 2.0 * bm + br;
+    return length(vec2<f32>(sobel_x, sobel_y));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv).rgb;
+    let threshold = params.outline_threshold;
+    let softness = max(params.edge_softness, 0.01);
+
+    let hsv = rgb_to_hsv(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)));
+    var pastel = hsv_to_rgb(vec3<f32>(hsv.x, hsv.y * params.saturation, min(hsv.z * params.brightness, 1.0)));
+    pastel = mix(vec3<f32>(0.5), pastel, params.contrast);
+    if (params.levels >= 2.0) {
+        pastel = soft_levels(pastel, params.levels);
+    }
+
+    let edge = sobel(in.uv);
+    if (edge > threshold) {
+        let normalized = (edge - threshold) / max(1.0 - threshold, 0.001);
+        let amount = min(smoothstep(0.0, 1.0, normalized * softness) * params.outline_strength, 1.0);
+        pastel = mix(pastel, vec3<f32>(0.15, 0.1, 0.2), amount);
+    }
+    return vec4<f32>(mix(color, pastel, params.intensity), 1.0);
+}
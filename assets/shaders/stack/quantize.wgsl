@@ -0,0 +1,18 @@
+// Passata dello stack: riduce ogni canale a `levels` livelli, a colori o in scala di grigi.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import xirai::stack::StackView
+#import xirai::stack::quantize::QuantizeParams
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> view: StackView;
+@group(0) @binding(3) var<uniform> params: QuantizeParams;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let source = clamp(textureSample(screen_texture, texture_sampler, in.uv).rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    let levels = max(params.levels, 2.0) - 1.0;
+    let gray = dot(source, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let color = mix(source, vec3<f32>(gray), clamp(params.grayscale, 0.0, 1.0));
+    return vec4<f32>(round(color * levels) / levels, 1.0);
+}
@@ -0,0 +1,34 @@
+// Passata dello stack: bordo colorato dove la luminanza cambia entro `thickness` pixel.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import xirai::stack::StackView
+#import xirai::stack::simple_outline::SimpleOutlineParams
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> view: StackView;
+@group(0) @binding(3) var<uniform> params: SimpleOutlineParams;
+
+fn luminance(uv: vec2<f32>) -> f32 {
+    return dot(textureSample(screen_texture, texture_sampler, uv).rgb, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let step = max(params.thickness, 0.0) / view.resolution;
+
+    let center = luminance(in.uv);
+    var max_diff = 0.0;
+    for (var x = -1; x <= 1; x++) {
+        for (var y = -1; y <= 1; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * step;
+            max_diff = max(max_diff, abs(center - luminance(in.uv + offset)));
+        }
+    }
+
+    let color = textureSample(screen_texture, texture_sampler, in.uv).rgb;
+    if (max_diff <= params.threshold) {
+        return vec4<f32>(color, 1.0);
+    }
+    let alpha = min(max_diff * 5.0, 1.0) * params.intensity;
+    return vec4<f32>(mix(color, params.color, alpha), 1.0);
+}
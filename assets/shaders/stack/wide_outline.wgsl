@@ -0,0 +1,43 @@
+// Passata dello stack: contorno spesso con bordo sfumato, al posto del jump flood a più passate.
+// Cerca il salto di luminanza più vicino in un disco di raggio `thickness` pixel.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import xirai::stack::StackView
+#import xirai::stack::wide_outline::WideOutlineParams
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> view: StackView;
+@group(0) @binding(3) var<uniform> params: WideOutlineParams;
+
+const MAX_RADIUS: i32 = 8;
+
+fn luminance(uv: vec2<f32>) -> f32 {
+    return dot(textureSample(screen_texture, texture_sampler, uv).rgb, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let thickness = clamp(params.thickness, 1.0, f32(MAX_RADIUS));
+    let softness = max(params.softness, 0.001);
+    let texel = 1.0 / view.resolution;
+    let radius = i32(ceil(thickness));
+
+    let center = luminance(in.uv);
+    var nearest = thickness + 1.0;
+    for (var x = -radius; x <= radius; x++) {
+        for (var y = -radius; y <= radius; y++) {
+            let dist = length(vec2<f32>(f32(x), f32(y)));
+            if (dist > thickness || dist >= nearest) {
+                continue;
+            }
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            if (abs(center - luminance(in.uv + offset)) > params.threshold) {
+                nearest = dist;
+            }
+        }
+    }
+
+    let color = textureSample(screen_texture, texture_sampler, in.uv).rgb;
+    let coverage = 1.0 - smoothstep(thickness - softness, thickness, nearest);
+    return vec4<f32>(mix(color, params.color, coverage), 1.0);
+}
@@ -0,0 +1,353 @@
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Messo sull'entità dell'`AnimationPlayer` mentre il browser dei clip ne ha il controllo:
+/// la macchina a stati smette di toccare i pesi.
+#[derive(Component)]
+pub struct ClipPreview;
+
+/// Stati della macchina a stati delle animazioni.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum AnimState {
+    Idle,
+    Walk,
+    Run,
+    Jump,
+    Attack,
+}
+
+impl AnimState {
+    pub const ALL: [AnimState; 5] = [
+        AnimState::Idle,
+        AnimState::Walk,
+        AnimState::Run,
+        AnimState::Jump,
+        AnimState::Attack,
+    ];
+
+    /// Parola cercata nei nomi dei clip quando lo stato non ha un clip assegnato.
+    fn keyword(self) -> &'static str {
+        match self {
+            AnimState::Idle => "idle",
+            AnimState::Walk => "walk",
+            AnimState::Run => "run",
+            AnimState::Jump => "jump",
+            AnimState::Attack => "attack",
+        }
+    }
+
+    /// One-shot states play once from the start every time they are entered.
+    fn one_shot(self) -> bool {
+        matches!(self, AnimState::Jump | AnimState::Attack)
+    }
+}
+
+/// Parametri che guidano le transizioni, scritti dal gameplay.
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct AnimationParams {
+    /// Velocità orizzontale in m/s.
+    pub speed: f32,
+    pub grounded: bool,
+    /// Trigger: consumato al frame successivo, che lo stato Attack parta o no.
+    pub attack: bool,
+}
+
+/// Controller delle animazioni di un personaggio: carica i clip dal GLTF, li mette sotto
+/// un nodo blend dell'`AnimationGraph` e fa cross-fade dei pesi quando cambia stato.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct AnimationController {
+    pub gltf: Handle<Gltf>,
+    /// Nome del clip per stato; gli stati mancanti vengono cercati per parola chiave.
+    pub clip_names: HashMap<AnimState, String>,
+    pub params: AnimationParams,
+    pub walk_threshold: f32,
+    pub run_threshold: f32,
+    /// Durata del cross-fade in secondi.
+    pub fade_duration: f32,
+    pub current: AnimState,
+    /// Tempo passato nello stato corrente.
+    pub state_time: f32,
+    nodes: HashMap<AnimState, AnimationNodeIndex>,
+    durations: HashMap<AnimState, f32>,
+    weights: HashMap<AnimationNodeIndex, f32>,
+    player: Option<Entity>,
+}
+
+impl AnimationController {
+    pub fn new(gltf: Handle<Gltf>) -> Self {
+        Self {
+            gltf,
+            clip_names: HashMap::new(),
+            params: AnimationParams {
+                grounded: true,
+                ..default()
+            },
+            walk_threshold: 0.2,
+            run_threshold: 3.0,
+            fade_duration: 0.2,
+            current: AnimState::Idle,
+            state_time: 0.0,
+            nodes: HashMap::new(),
+            durations: HashMap::new(),
+            weights: HashMap::new(),
+            player: None,
+        }
+    }
+
+    /// Forces the graph to be rebuilt on the next frame (e.g. after a preview replaced it).
+    pub fn rebuild(&mut self) {
+        self.player = None;
+    }
+
+    /// Stato voluto dai parametri correnti.
+    fn desired_state(&self) -> AnimState {
+        let attack_length = self.durations.get(&AnimState::Attack).copied().unwrap_or(0.6);
+        if self.current == AnimState::Attack && self.state_time < attack_length {
+            return AnimState::Attack;
+        }
+        if self.params.attack && self.nodes.contains_key(&AnimState::Attack) {
+            return AnimState::Attack;
+        }
+        if !self.params.grounded {
+            return AnimState::Jump;
+        }
+        if self.params.speed >= self.run_threshold {
+            AnimState::Run
+        } else if self.params.speed >= self.walk_threshold {
+            AnimState::Walk
+        } else {
+            AnimState::Idle
+        }
+    }
+
+    /// Stato da suonare: quello voluto se ha un clip, altrimenti il primo stato che ne ha uno
+    /// (Idle se c'è), così un GLTF senza idle non resta in bind pose.
+    fn playable_state(&self, desired: AnimState) -> AnimState {
+        if self.nodes.contains_key(&desired) {
+            return desired;
+        }
+        AnimState::ALL
+            .into_iter()
+            .find(|state| self.nodes.contains_key(state))
+            .unwrap_or(desired)
+    }
+}
+
+/// Trova il clip per uno stato: prima per nome esatto, poi per parola chiave nel nome.
+fn find_clip(gltf: &Gltf, name: Option<&String>, state: AnimState) -> Option<Handle<AnimationClip>> {
+    if let Some(name) = name {
+        if let Some(clip) = gltf.named_animations.get(name.as_str()) {
+            return Some(clip.clone());
+        }
+        warn!("Clip '{}' non trovato per lo stato {:?}", name, state);
+    }
+    gltf.named_animations
+        .iter()
+        .find(|(clip_name, _)| clip_name.to_lowercase().contains(state.keyword()))
+        .map(|(_, clip)| clip.clone())
+}
+
+/// Costruisce il grafo appena il GLTF è caricato e la scena ha spawnato l'`AnimationPlayer`.
+fn build_animation_graphs(
+    mut commands: Commands,
+    mut controllers: Query<(Entity, &mut AnimationController)>,
+    children: Query<&Children>,
+    mut players: Query<&mut AnimationPlayer, Without<ClipPreview>>,
+    gltfs: Res<Assets<Gltf>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+) {
+    for (entity, mut controller) in controllers.iter_mut() {
+        if controller.player.is_some() {
+            continue;
+        }
+        let Some(gltf) = gltfs.get(&controller.gltf) else {
+            continue;
+        };
+        let Some(player_entity) = children
+            .iter_descendants(entity)
+            .find(|&e| players.contains(e))
+        else {
+            continue;
+        };
+
+        let mut graph = AnimationGraph::new();
+        let blend = graph.add_blend(1.0, graph.root);
+        let mut nodes = HashMap::new();
+        let mut durations = HashMap::new();
+        for state in AnimState::ALL {
+            let Some(clip) = find_clip(gltf, controller.clip_names.get(&state), state) else {
+                continue;
+            };
+            if let Some(duration) = clips.get(&clip).map(|c| c.duration()) {
+                durations.insert(state, duration);
+            }
+            nodes.insert(state, graph.add_clip(clip, 1.0, blend));
+        }
+
+        // Senza un clip per nessuno stato si usa il primo clip del file.
+        if nodes.is_empty() {
+            let Some(clip) = gltf.animations.first() else {
+                warn!("Nessuna animazione nel GLTF del personaggio");
+                controller.player = Some(player_entity);
+                continue;
+            };
+            nodes.insert(AnimState::Idle, graph.add_clip(clip.clone(), 1.0, blend));
+        }
+
+        let Ok(mut player) = players.get_mut(player_entity) else {
+            continue;
+        };
+        player.stop_all();
+        controller.nodes = nodes;
+        controller.current = controller.playable_state(controller.current);
+        let nodes = &controller.nodes;
+        let mut weights = HashMap::new();
+        for (&state, &node) in nodes {
+            let weight = if state == controller.current { 1.0 } else { 0.0 };
+            let animation = player.play(node);
+            animation.set_weight(weight);
+            if !state.one_shot() {
+                animation.repeat();
+            }
+            weights.insert(node, weight);
+        }
+
+        commands
+            .entity(player_entity)
+            .insert(AnimationGraphHandle(graphs.add(graph)));
+        controller.durations = durations;
+        controller.weights = weights;
+        controller.player = Some(player_entity);
+    }
+}
+
+/// Applica le transizioni e porta i pesi verso lo stato corrente (cross-fade).
+fn update_animation_controllers(
+    time: Res<Time>,
+    mut controllers: Query<&mut AnimationController>,
+    mut players: Query<&mut AnimationPlayer, Without<ClipPreview>>,
+) {
+    let dt = time.delta_secs();
+    for mut controller in controllers.iter_mut() {
+        let Some(mut player) = controller.player.and_then(|e| players.get_mut(e).ok()) else {
+            continue;
+        };
+        let controller = &mut *controller;
+        controller.state_time += dt;
+
+        let desired = controller.playable_state(controller.desired_state());
+        // Il trigger vale un solo frame, anche quando non può far partire Attack.
+        controller.params.attack = false;
+        if desired != controller.current {
+            controller.current = desired;
+            controller.state_time = 0.0;
+            if let Some(node) = controller.nodes.get(&desired) {
+                if desired.one_shot() {
+                    player.play(*node).replay();
+                }
+            }
+        }
+
+        let target = controller.nodes.get(&controller.current).copied();
+        let step = if controller.fade_duration > 0.0 {
+            dt / controller.fade_duration
+        } else {
+            1.0
+        };
+        for (node, weight) in controller.weights.iter_mut() {
+            let goal = if Some(*node) == target { 1.0 } else { 0.0 };
+            *weight = if *weight < goal {
+                (*weight + step).min(goal)
+            } else {
+                (*weight - step).max(goal)
+            };
+            if let Some(animation) = player.animation_mut(*node) {
+                animation.set_weight(*weight);
+            }
+        }
+    }
+}
+
+pub struct AnimationControllerPlugin;
+impl Plugin for AnimationControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AnimationController>().add_systems(
+            Update,
+            (build_animation_graphs, update_animation_controllers).chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller(states: &[AnimState]) -> AnimationController {
+        let mut controller = AnimationController::new(Handle::default());
+        for (i, &state) in states.iter().enumerate() {
+            controller.nodes.insert(state, AnimationNodeIndex::new(i + 1));
+        }
+        controller
+    }
+
+    #[test]
+    fn speed_thresholds() {
+        let mut controller = controller(&AnimState::ALL);
+        for (speed, state) in [
+            (0.0, AnimState::Idle),
+            (0.19, AnimState::Idle),
+            (0.2, AnimState::Walk),
+            (2.99, AnimState::Walk),
+            (3.0, AnimState::Run),
+            (8.0, AnimState::Run),
+        ] {
+            controller.params.speed = speed;
+            assert_eq!(controller.desired_state(), state, "speed {}", speed);
+        }
+    }
+
+    #[test]
+    fn airborne_is_jump() {
+        let mut controller = controller(&AnimState::ALL);
+        controller.params.grounded = false;
+        controller.params.speed = 5.0;
+        assert_eq!(controller.desired_state(), AnimState::Jump);
+    }
+
+    #[test]
+    fn attack_locks_until_the_clip_ends() {
+        let mut controller = controller(&AnimState::ALL);
+        controller.durations.insert(AnimState::Attack, 1.0);
+        controller.params.attack = true;
+        assert_eq!(controller.desired_state(), AnimState::Attack);
+
+        controller.current = AnimState::Attack;
+        controller.params.attack = false;
+        controller.params.grounded = false;
+        controller.state_time = 0.9;
+        assert_eq!(controller.desired_state(), AnimState::Attack);
+        controller.state_time = 1.0;
+        assert_eq!(controller.desired_state(), AnimState::Jump);
+    }
+
+    #[test]
+    fn attack_needs_a_clip() {
+        let mut controller = controller(&[AnimState::Idle, AnimState::Walk]);
+        controller.params.attack = true;
+        assert_eq!(controller.desired_state(), AnimState::Idle);
+    }
+
+    #[test]
+    fn missing_clips_fall_back_to_an_existing_state() {
+        let controller = controller(&[AnimState::Walk, AnimState::Run]);
+        assert_eq!(controller.playable_state(AnimState::Run), AnimState::Run);
+        assert_eq!(controller.playable_state(AnimState::Idle), AnimState::Walk);
+        assert_eq!(controller.playable_state(AnimState::Jump), AnimState::Walk);
+
+        let with_idle = self::controller(&[AnimState::Run, AnimState::Idle]);
+        assert_eq!(with_idle.playable_state(AnimState::Jump), AnimState::Idle);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::animation::{AnimationController, ClipPreview};
+use crate::transform::{scene_root, Selected};
+use bevy::animation::RepeatAnimation;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+
+/// Stato del pannello "Animation Clips": entità ispezionata, clip del suo GLTF e anteprima.
+#[derive(Resource)]
+struct ClipBrowserState {
+    /// Radice della scena dell'entità selezionata.
+    root: Option<Entity>,
+    player: Option<Entity>,
+    gltf: Option<Handle<Gltf>>,
+    clips: Vec<(String, Handle<AnimationClip>)>,
+    preview: Option<PreviewGraph>,
+    clip_a: usize,
+    clip_b: Option<usize>,
+    /// 0 = solo A, 1 = solo B.
+    blend: f32,
+    speed: f32,
+    looping: bool,
+    paused: bool,
+}
+
+impl Default for ClipBrowserState {
+    fn default() -> Self {
+        Self {
+            root: None,
+            player: None,
+            gltf: None,
+            clips: Vec::new(),
+            preview: None,
+            clip_a: 0,
+            clip_b: None,
+            blend: 0.0,
+            speed: 1.0,
+            looping: true,
+            paused: false,
+        }
+    }
+}
+
+/// Grafo di anteprima con tutti i clip sotto un nodo blend, e il grafo originale da ripristinare.
+struct PreviewGraph {
+    nodes: Vec<AnimationNodeIndex>,
+    original: Option<Handle<AnimationGraph>>,
+    /// Animazioni attive prima dell'anteprima, per i modelli senza macchina a stati.
+    original_player: AnimationPlayer,
+}
+
+enum BrowserAction {
+    Play(usize),
+    SetB(Option<usize>),
+    Seek(f32),
+    StopPreview,
+}
+
+/// Rimette il grafo originale e restituisce il player alla macchina a stati.
+fn stop_preview(
+    commands: &mut Commands,
+    state: &mut ClipBrowserState,
+    players: &mut Query<(&mut AnimationPlayer, Option<&AnimationGraphHandle>)>,
+    controllers: &mut Query<&mut AnimationController>,
+) {
+    let (Some(preview), Some(player_entity)) = (state.preview.take(), state.player) else {
+        return;
+    };
+    let controller = state.root.and_then(|root| controllers.get_mut(root).ok());
+    if let Ok((mut player, _)) = players.get_mut(player_entity) {
+        // Con un controller ci pensa la macchina a stati; altrimenti torna ciò che suonava prima.
+        if controller.is_some() {
+            player.stop_all();
+        } else {
+            *player = preview.original_player;
+        }
+    }
+    let mut entity = commands.entity(player_entity);
+    entity.remove::<ClipPreview>();
+    match preview.original {
+        Some(original) => {
+            entity.insert(AnimationGraphHandle(original));
+        }
+        None => {
+            entity.remove::<AnimationGraphHandle>();
+        }
+    }
+    if let Some(mut controller) = controller {
+        controller.rebuild();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn animation_browser_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut state: ResMut<ClipBrowserState>,
+    selected: Query<Entity, With<Selected>>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    scene_roots: Query<&SceneRoot>,
+    mut players: Query<(&mut AnimationPlayer, Option<&AnimationGraphHandle>)>,
+    mut controllers: Query<&mut AnimationController>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+) {
+    let state = &mut *state;
+
+    // Cambio di selezione: si chiude l'anteprima sulla vecchia entità.
+    let root = selected.iter().next().map(|e| scene_root(e, &parents));
+    if root != state.root {
+        stop_preview(&mut commands, state, &mut players, &mut controllers);
+        *state = ClipBrowserState {
+            root,
+            speed: state.speed,
+            looping: state.looping,
+            ..default()
+        };
+        if let Some(root) = root {
+            state.player = std::iter::once(root)
+                .chain(children.iter_descendants(root))
+                .find(|&e| players.contains(e));
+            state.gltf = scene_roots
+                .get(root)
+                .ok()
+                .and_then(|scene| asset_server.get_path(scene.0.id()))
+                .map(|path| asset_server.load(path.without_label().into_owned()));
+        }
+    }
+
+    // I clip si leggono quando il GLTF ha finito di caricare.
+    if state.clips.is_empty() {
+        if let Some(gltf) = state.gltf.as_ref().and_then(|h| gltfs.get(h)) {
+            // Tutti i clip, in ordine di file; quelli senza nome prendono l'indice.
+            state.clips = gltf
+                .animations
+                .iter()
+                .enumerate()
+                .map(|(index, clip)| {
+                    let name = gltf
+                        .named_animations
+                        .iter()
+                        .find(|(_, named)| *named == clip)
+                        .map_or_else(|| format!("Animation {}", index), |(name, _)| name.to_string());
+                    (name, clip.clone())
+                })
+                .collect();
+        }
+    }
+
+    let duration = |index: usize, state: &ClipBrowserState| {
+        state
+            .clips
+            .get(index)
+            .and_then(|(_, clip)| clips.get(clip))
+            .map_or(0.0, |clip| clip.duration())
+    };
+    let duration_a = duration(state.clip_a, state);
+    let current_time = state
+        .preview
+        .as_ref()
+        .zip(state.player)
+        .and_then(|(preview, player)| {
+            let (player, _) = players.get(player).ok()?;
+            player.animation(preview.nodes[state.clip_a]).map(|a| a.seek_time())
+        })
+        .unwrap_or(0.0);
+
+    let mut action = None;
+    let ui = context.ui();
+    ui.window("Animation Clips")
+        .size([320.0, 420.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let Some(root) = state.root else {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "No entity selected");
+                return;
+            };
+            ui.text(format!("Entity: {:?}", root));
+            if state.player.is_none() {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "No AnimationPlayer");
+                return;
+            }
+            if state.clips.is_empty() {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "No clips (GLTF not loaded?)");
+                return;
+            }
+
+            ui.text("Clips (click: A, right click: B)");
+            for (index, (name, _)) in state.clips.iter().enumerate() {
+                let is_a = state.preview.is_some() && index == state.clip_a;
+                let is_b = state.clip_b == Some(index);
+                let label = match (is_a, is_b) {
+                    (true, _) => format!("[A] {}", name),
+                    (_, true) => format!("[B] {}", name),
+                    _ => name.clone(),
+                };
+                if ui.selectable_config(&label).selected(is_a || is_b).build() {
+                    action = Some(BrowserAction::Play(index));
+                }
+                if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+                    action = Some(BrowserAction::SetB(if is_b { None } else { Some(index) }));
+                }
+            }
+
+            if state.preview.is_none() {
+                return;
+            }
+            ui.separator();
+
+            if ui.button(if state.paused { "Play" } else { "Pause" }) {
+                state.paused = !state.paused;
+            }
+            ui.same_line();
+            if ui.button("Restart") {
+                action = Some(BrowserAction::Seek(0.0));
+            }
+            ui.same_line();
+            if ui.button("Stop preview") {
+                action = Some(BrowserAction::StopPreview);
+            }
+
+            let mut time = current_time;
+            if ui.slider(format!("Time / {:.2}s", duration_a), 0.0, duration_a.max(0.01), &mut time) {
+                action = Some(BrowserAction::Seek(time));
+            }
+            ui.slider("Speed", -2.0, 3.0, &mut state.speed);
+            ui.checkbox("Loop", &mut state.looping);
+
+            if let Some(b) = state.clip_b {
+                ui.separator();
+                ui.text(format!("Blend: {} -> {}", state.clips[state.clip_a].0, state.clips[b].0));
+                ui.slider("Blend", 0.0, 1.0, &mut state.blend);
+                if ui.button("Clear B") {
+                    action = Some(BrowserAction::SetB(None));
+                }
+            }
+        });
+
+    let Some(player_entity) = state.player else {
+        return;
+    };
+
+    match action {
+        Some(BrowserAction::Play(index)) => {
+            if state.preview.is_none() {
+                // Prima anteprima: grafo con tutti i clip, quello originale si tiene da parte.
+                let mut graph = AnimationGraph::new();
+                let blend = graph.add_blend(1.0, graph.root);
+                let nodes = state
+                    .clips
+                    .iter()
+                    .map(|(_, clip)| graph.add_clip(clip.clone(), 1.0, blend))
+                    .collect();
+                let Ok((player, handle)) = players.get(player_entity) else {
+                    return;
+                };
+                let original = handle.map(|h| h.0.clone());
+                let original_player = player.clone();
+                commands
+                    .entity(player_entity)
+                    .insert((AnimationGraphHandle(graphs.add(graph)), ClipPreview));
+                state.preview = Some(PreviewGraph {
+                    nodes,
+                    original,
+                    original_player,
+                });
+            }
+            state.clip_a = index;
+            if state.clip_b == Some(index) {
+                state.clip_b = None;
+            }
+            state.paused = false;
+            if let Ok((mut player, _)) = players.get_mut(player_entity) {
+                player.stop_all();
+            }
+        }
+        Some(BrowserAction::SetB(b)) => {
+            state.clip_b = b.filter(|&b| b != state.clip_a);
+            if let Ok((mut player, _)) = players.get_mut(player_entity) {
+                player.stop_all();
+            }
+        }
+        Some(BrowserAction::StopPreview) => {
+            stop_preview(&mut commands, state, &mut players, &mut controllers);
+            return;
+        }
+        Some(BrowserAction::Seek(_)) | None => {}
+    }
+
+    let Some(preview) = state.preview.as_ref() else {
+        return;
+    };
+    let Ok((mut player, _)) = players.get_mut(player_entity) else {
+        return;
+    };
+
+    let repeat = if state.looping {
+        RepeatAnimation::Forever
+    } else {
+        RepeatAnimation::Never
+    };
+    let duration_b = state.clip_b.map_or(0.0, |b| duration(b, state));
+    let weight_a = if state.clip_b.is_some() {
+        1.0 - state.blend
+    } else {
+        1.0
+    };
+    let tracks = [
+        Some((preview.nodes[state.clip_a], weight_a, duration_a)),
+        state.clip_b.map(|b| (preview.nodes[b], state.blend, duration_b)),
+    ];
+    for (node, weight, length) in tracks.into_iter().flatten() {
+        let animation = player.play(node);
+        animation
+            .set_weight(weight)
+            .set_speed(state.speed)
+            .set_repeat(repeat);
+        if state.paused {
+            animation.pause();
+        } else {
+            animation.resume();
+        }
+        if let Some(BrowserAction::Seek(time)) = action {
+            // B segue A in proporzione, così un blend resta a tempo.
+            let time = if duration_a > 0.0 {
+                time / duration_a * length
+            } else {
+                0.0
+            };
+            animation.seek_to(time);
+        }
+    }
+}
+
+pub struct AnimationBrowserPlugin;
+impl Plugin for AnimationBrowserPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClipBrowserState>()
+            .add_systems(Update, animation_browser_ui);
+    }
+}
@@ -139,10 +139,8 @@ fn imgui_ui(
     let sidebar_window = ui.window("Asset Browser");
     sidebar_window
         .size([400.0, 900.0], imgui::Condition::FirstUseEver)
-        .position([0.0, 0.0], imgui::Condition::FirstUseEver)
         .collapsible(true)
         .resizable(true)
-        .movable(false)
         .build(|| {
             ui.text("Choose assets");
             ui.separator();
@@ -233,7 +231,6 @@ fn alive_entities_ui(mut context: NonSendMut<ImguiContext>, query: Query<Entity,
     let ui = context.ui();
     let window = ui.window("Alive entities");
     window
-        .position([1000., 1000.0], imgui::Condition::FirstUseEver)
         .size([300.0, 300.0], imgui::Condition::FirstUseEver)
         .build(|| {
             for e in query.iter() {
@@ -242,52 +239,74 @@ fn alive_entities_ui(mut context: NonSendMut<ImguiContext>, query: Query<Entity,
         });
 }
 
+/// Modello spawnato dall'editor: è quello che finisce nel file della scena.
+#[derive(Component)]
+pub struct SpawnedAsset {
+    pub model_path: String,
+}
+
+/// Spawna un modello GLTF come oggetto della scena (outline, picking, collider).
+pub fn spawn_model(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    model_path: &str,
+    transform: Transform,
+) -> Entity {
+    let scene_handle = asset_server.load(GltfAssetLabel::Scene(0).from_asset(model_path.to_string()));
+    let asset = commands
+        .spawn((
+            SceneRoot(scene_handle),
+            transform,
+            SpawnedAsset {
+                model_path: model_path.to_string(),
+            },
+            AsyncSceneInheritOutline::default(),
+            OutlineVolume {
+                visible: true,
+                width: 2.0,
+                colour: BLACK.into(),
+            },
+            OutlineMode::FloodFlatDoubleSided,
+            Alive,
+            // Rileva il rig se il modello ne ha uno (ragdoll, IK, retargeting).
+            crate::skeleton::SkeletonMap::default(),
+            bevy::picking::Pickable{
+                should_block_lower:false,
+                is_hoverable:true
+            },
+            //crate::transform::Selected
+        ))
+        .id();
+    // Spawn collider as child, offset by half_height on Y
+    commands.entity(asset).with_children(|parent| {
+        parent.spawn((
+            Collider::cuboid(0.25, 0.8, 0.25),
+            Transform::from_xyz(0.0, 0.8, 0.0),
+        ));
+    });
+    asset
+}
+
 pub fn spawn_asset(
     mut commands: Commands,
-    buttons: Res<ButtonInput<MouseButton>>,
+    actions: Res<crate::input::ActionState>,
     query: Query<(Entity, &GameAsset)>,
     cursor: Res<crate::cursor::Cursor>,
     asset_server: Res<AssetServer>,
 ) {
-    if !buttons.just_pressed(MouseButton::Right) {
+    if !actions.just_pressed(crate::input::Action::PlaceAsset) {
         return;
     }
 
     for (_, game_asset) in query.iter() {
         if game_asset.selected {
-            let scene_handle = asset_server
-                .load(GltfAssetLabel::Scene(0).from_asset(game_asset.model_path.clone()));
-            let asset = commands
-                .spawn((
-                    SceneRoot(scene_handle),
-                    Transform::from_xyz(
-                        cursor.cursor_position.x,
-                        cursor.cursor_position.y,
-                        cursor.cursor_position.z,
-                    ),
-                    AsyncSceneInheritOutline::default(),
-                    OutlineVolume {
-                        visible: true,
-                        width: 2.0,
-                        colour: BLACK.into(),
-                    },
-                    OutlineMode::FloodFlatDoubleSided,
-                    Alive,
-                    bevy::picking::Pickable{
-                        should_block_lower:false,
-                        is_hoverable:true
-                    },
-                    //crate::transform::Selected
-                ))
-                .id();
+            let asset = spawn_model(
+                &mut commands,
+                &asset_server,
+                &game_asset.model_path,
+                Transform::from_translation(cursor.cursor_position),
+            );
             println!("From: spawn_asset {}", asset);
-            // Spawn collider as child, offset by half_height on Y
-            commands.entity(asset).with_children(|parent| {
-                parent.spawn((
-                    Collider::cuboid(0.25, 0.8, 0.25),
-                    Transform::from_xyz(0.0, 0.8, 0.0),
-                ));
-            });
             break;
         };
     }
@@ -302,6 +321,11 @@ impl Plugin for AssetsPlugin {
             })
             .add_systems(Startup, setup_game_assets)
             .add_systems(Update, imgui_ui)
-            .add_systems(Update, spawn_asset.after(crate::cursor::calc_cursor_pos));
+            .add_systems(
+                Update,
+                spawn_asset
+                    .after(crate::cursor::calc_cursor_pos)
+                    .in_set(crate::play_mode::EditSet),
+            );
     }
 }
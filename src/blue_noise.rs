@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// Lato della tessera di blue noise (si ripete sullo schermo).
+pub const SIZE: usize = 64;
+/// Raggio della gaussiana con cui si misura quanto un punto è "affollato".
+const SIGMA: f32 = 1.5;
+
+/// Energia portata da un punto a distanza (dx, dy), su una griglia che si avvolge.
+fn kernel() -> Vec<f32> {
+    let mut kernel = vec![0.0; SIZE * SIZE];
+    for dy in 0..SIZE {
+        for dx in 0..SIZE {
+            let x = dx.min(SIZE - dx) as f32;
+            let y = dy.min(SIZE - dy) as f32;
+            kernel[dy * SIZE + dx] = (-(x * x + y * y) / (2.0 * SIGMA * SIGMA)).exp();
+        }
+    }
+    kernel
+}
+
+struct Pattern {
+    kernel: Vec<f32>,
+    energy: Vec<f32>,
+    on: Vec<bool>,
+}
+
+impl Pattern {
+    fn toggle(&mut self, index: usize) {
+        self.on[index] = !self.on[index];
+        let sign = if self.on[index] { 1.0 } else { -1.0 };
+        let (px, py) = (index % SIZE, index / SIZE);
+        for (i, energy) in self.energy.iter_mut().enumerate() {
+            let dx = (i % SIZE + SIZE - px) % SIZE;
+            let dy = (i / SIZE + SIZE - py) % SIZE;
+            *energy += sign * self.kernel[dy * SIZE + dx];
+        }
+    }
+
+    /// Punto acceso più affollato.
+    fn tightest_cluster(&self) -> usize {
+        (0..self.on.len())
+            .filter(|&i| self.on[i])
+            .max_by(|&a, &b| self.energy[a].total_cmp(&self.energy[b]))
+            .unwrap_or(0)
+    }
+
+    /// Punto spento più isolato.
+    fn largest_void(&self) -> usize {
+        (0..self.on.len())
+            .filter(|&i| !self.on[i])
+            .min_by(|&a, &b| self.energy[a].total_cmp(&self.energy[b]))
+            .unwrap_or(0)
+    }
+}
+
+/// Soglie 0..255 ottenute con void-and-cluster (Ulichney): ogni pixel riceve il suo
+/// rango, così ogni livello di soglia è distribuito in modo uniforme e senza grumi.
+pub fn generate() -> Vec<u8> {
+    let count = SIZE * SIZE;
+    let mut pattern = Pattern {
+        kernel: kernel(),
+        energy: vec![0.0; count],
+        on: vec![false; count],
+    };
+
+    // Disposizione iniziale pseudo-casuale (LCG fisso: la texture è sempre la stessa).
+    let mut seed: u32 = 0x2545_f491;
+    let initial = count / 10;
+    while pattern.on.iter().filter(|on| **on).count() < initial {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let index = (seed >> 8) as usize % count;
+        if !pattern.on[index] {
+            pattern.toggle(index);
+        }
+    }
+    // Si spostano i punti dai grumi ai vuoti finché la disposizione non si stabilizza.
+    for _ in 0..count {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        let void = pattern.largest_void();
+        if void == cluster {
+            pattern.toggle(cluster);
+            break;
+        }
+        pattern.toggle(void);
+    }
+
+    let mut rank = vec![0usize; count];
+    let prototype = pattern.on.clone();
+    let prototype_energy = pattern.energy.clone();
+    // Ranghi dei punti iniziali: si tolgono dal più affollato.
+    for r in (0..initial).rev() {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        rank[cluster] = r;
+    }
+    // Gli altri si accendono riempiendo ogni volta il vuoto più grande.
+    pattern.on = prototype;
+    pattern.energy = prototype_energy;
+    for r in initial..count {
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        rank[void] = r;
+    }
+
+    rank.iter().map(|r| (r * 256 / count) as u8).collect()
+}
+
+pub fn image() -> Image {
+    Image::new(
+        Extent3d {
+            width: SIZE as u32,
+            height: SIZE as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        generate(),
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
@@ -1,130 +1,242 @@
-use bevy::color::palettes::css::*;
 use bevy::prelude::*;
-use bevy_mod_outline::*;
-use crate::ground;
-#[derive(Component)]
-pub struct Board;
 
+/// Griglia generica di tile (scacchi, mappe tattiche, ...).
+///
+/// Il transform dell'entità è l'angolo della cella (0, 0): le celle crescono
+/// lungo +X (colonne) e +Z (righe) nello spazio locale della board. I tile
+/// vengono spawnati come figli quando il componente viene aggiunto.
+#[derive(Component, Clone)]
+#[require(Transform, Visibility)]
+pub struct Board {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: f32,
+    /// Materiali ripetuti a scacchiera: la cella (x, y) usa `(x + y) % len`.
+    pub tile_materials: Vec<Handle<StandardMaterial>>,
+}
+
+impl Board {
+    /// Una board ha almeno una cella.
+    pub fn new(width: u32, height: u32) -> Self {
+        assert!(width > 0 && height > 0, "board size must be at least 1x1");
+        Self {
+            width,
+            height,
+            tile_size: 1.0,
+            tile_materials: Vec::new(),
+        }
+    }
+
+    pub fn with_tile_size(mut self, tile_size: f32) -> Self {
+        assert!(tile_size > 0.0 && tile_size.is_finite(), "tile size must be positive");
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn with_materials(mut self, materials: Vec<Handle<StandardMaterial>>) -> Self {
+        self.tile_materials = materials;
+        self
+    }
+
+    pub fn contains(&self, cell: IVec2) -> bool {
+        cell.x >= 0 && cell.y >= 0 && (cell.x as u32) < self.width && (cell.y as u32) < self.height
+    }
+
+    /// Indice lineare della cella, riga per riga (per gli scacchi: 0 = a1, 63 = h8).
+    pub fn index(&self, cell: UVec2) -> Option<usize> {
+        self.contains(cell.as_ivec2())
+            .then(|| cell.y as usize * self.width as usize + cell.x as usize)
+    }
+
+    /// Inverso di `index`: `None` fuori dalla board (anche se `width` o `height` sono 0).
+    pub fn cell(&self, index: usize) -> Option<UVec2> {
+        let width = self.width as usize;
+        (index < width * self.height as usize)
+            .then(|| UVec2::new((index % width) as u32, (index / width) as u32))
+    }
+
+    /// Centro della cella nello spazio locale della board.
+    pub fn cell_to_local(&self, cell: UVec2) -> Vec3 {
+        Vec3::new(
+            (cell.x as f32 + 0.5) * self.tile_size,
+            0.0,
+            (cell.y as f32 + 0.5) * self.tile_size,
+        )
+    }
+
+    pub fn local_to_cell(&self, local: Vec3) -> Option<UVec2> {
+        let cell = IVec2::new(
+            (local.x / self.tile_size).floor() as i32,
+            (local.z / self.tile_size).floor() as i32,
+        );
+        self.contains(cell).then(|| cell.as_uvec2())
+    }
+
+    /// Centro della cella in world space.
+    pub fn cell_to_world(&self, transform: &GlobalTransform, cell: UVec2) -> Vec3 {
+        transform.transform_point(self.cell_to_local(cell))
+    }
+
+    /// Cella sotto un punto in world space (la componente lungo la normale della board è ignorata).
+    pub fn world_to_cell(&self, transform: &GlobalTransform, world: Vec3) -> Option<UVec2> {
+        let local = transform.affine().inverse().transform_point3(world);
+        self.local_to_cell(local)
+    }
+
+    /// Celle adiacenti (4 direzioni) dentro i bordi.
+    pub fn neighbours(&self, cell: UVec2) -> impl Iterator<Item = UVec2> + '_ {
+        [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+            .into_iter()
+            .map(move |dir| cell.as_ivec2() + dir)
+            .filter(|c| self.contains(*c))
+            .map(|c| c.as_uvec2())
+    }
+}
+
+/// Tile di una board, figlio dell'entità `Board`.
 #[derive(Component)]
-pub struct Tile;
-#[derive(Component)]
+pub struct Tile {
+    pub cell: UVec2,
+}
+
+/// Ingombro in celle di qualcosa appoggiato sulla board (un tile è 1x1).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Size {
     pub w: u32,
     pub h: u32,
 }
 
-fn setup_board(
+impl Default for Size {
+    fn default() -> Self {
+        Self { w: 1, h: 1 }
+    }
+}
+
+impl Size {
+    /// Celle coperte partendo da `origin` (l'angolo con x e y minimi).
+    pub fn cells(self, origin: UVec2) -> impl Iterator<Item = UVec2> {
+        (0..self.h).flat_map(move |y| (0..self.w).map(move |x| origin + UVec2::new(x, y)))
+    }
+}
+
+fn spawn_board_tiles(
     mut commands: Commands,
+    boards: Query<(Entity, &Board), Added<Board>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let board_entity = commands
-        .spawn((
-            Board,
-            Transform::from_xyz(10.0, 0.0, 10.0),
-            GlobalTransform::default(),
-        ))
-        .id();
-
-    let tile_size = 2.0; // Dimensione di ogni tile
-    let board_width = 8; // Numero di tile in larghezza
-    let board_height = 8; // Numero di tile in altezza
-    let mut square_material = StandardMaterial {
-        base_color: Color::WHITE, // Green color
-        alpha_mode: AlphaMode::Opaque,
-        unlit: true, // Flat pixel art look
-        metallic: 0.0,
-        perceptual_roughness: 1.0,
-        //emissive: LinearRgba::rgb(1.0,1.0,1.0),
-        reflectance: 0.0,
-        ..default()
-    };
-    let mesh = Mesh::from(Plane3d::default().mesh().size(tile_size, tile_size));
-    for x in 0..board_width {
-        for y in 0..board_height {
-            println!("Creating square at ({}, {})", x, y);
-            let current_square = commands
-                .spawn((
-                    Mesh3d(meshes.add(mesh.clone())),
-                    Transform::from_xyz(x as f32 * tile_size, 0.1, y as f32 * tile_size),
-                    Size { w: 1, h: 1 },
-                    crate::outline::Outlined,
-                ))
-                .id();
-            if (x + y) % 2 == 0 {
-                square_material.base_color = BLACK.into();
-            } else {
-                square_material.base_color = WHITE.into();
+    for (board_entity, board) in boards.iter() {
+        let mesh = meshes.add(Plane3d::default().mesh().size(board.tile_size, board.tile_size));
+        let tile_materials = if board.tile_materials.is_empty() {
+            vec![materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                ..default()
+            })]
+        } else {
+            board.tile_materials.clone()
+        };
+
+        commands.entity(board_entity).with_children(|parent| {
+            for y in 0..board.height {
+                for x in 0..board.width {
+                    let cell = UVec2::new(x, y);
+                    let material = tile_materials[((x + y) as usize) % tile_materials.len()].clone();
+                    parent.spawn((
+                        Mesh3d(mesh.clone()),
+                        MeshMaterial3d(material),
+                        Transform::from_translation(board.cell_to_local(cell)),
+                        Tile { cell },
+                        Size::default(),
+                        crate::outline::Outlined,
+                    ));
+                }
             }
-            commands
-                .entity(current_square)
-                .insert(MeshMaterial3d(materials.add(square_material.clone())));
-        }
+        });
     }
 }
 
-
-#[derive(Component)]
-struct Piece;
-
-fn setup_pieces(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>, mut meshes: ResMut<Assets<Mesh>>) {
-    let white_material = StandardMaterial {
-        base_color: Color::WHITE, // Green color
-        alpha_mode: AlphaMode::Opaque,
-        unlit: true, // Flat pixel art look
-        metallic: 0.0,
-        perceptual_roughness: 1.0,
-        reflectance: 0.0,
-        ..default()
-    };
-    let black_material = StandardMaterial {
-        base_color: Color::BLACK, // Green color
-        alpha_mode: AlphaMode::Opaque,
-        unlit: true, // Flat pixel art look
-        metallic: 0.0,
-        perceptual_roughness: 1.0,
-        reflectance: 0.0,
-        ..default()
-    };
-
-    let pawn_mesh = Mesh::from(Sphere::new(0.1,));
-    let rook_mesh = Mesh::from(Cuboid::new(0.2,0.2,0.2));
-    let bishop_mesh = Mesh::from(Cylinder::new(0.1,0.3,));
-    let knight_mesh = Mesh::from(Cylinder::new(0.2,0.2,));
-
-    // Spawn white pieces
-    for i in 0..8 {
-        commands.spawn((
-            Mesh3d(meshes.add(pawn_mesh.clone())),
-            MeshMaterial3d(materials.add(white_material.clone())),
-            Transform::from_xyz(i as f32 * 1.0, 0.5, 1.0),
-            Piece,
-        ));
-    }
-    commands.spawn((
-        Mesh3d(meshes.add(rook_mesh.clone())),
-        MeshMaterial3d(materials.add(white_material.clone())),
-        Transform::from_xyz(1.0, 0.5, 0.0),
-        Piece,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(knight_mesh.clone())),
-        MeshMaterial3d(materials.add(white_material.clone())),
-        Transform::from_xyz(2.0, 0.5, 0.0),
-        Piece,
-    ));
-    commands.spawn((
-        Mesh3d(meshes.add(bishop_mesh.clone())),
-        MeshMaterial3d(materials.add(white_material.clone())),
-        Transform::from_xyz(3.0, 0.5, 0.0),
-        Piece,
-    ));
-}
-
 pub struct BoardPlugin;
-impl Plugin for BoardPlugin{
+impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_board)
-        .add_systems(Startup, setup_pieces);
+        app.add_systems(Update, spawn_board_tiles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_cell_round_trip() {
+        let board = Board::new(10, 4);
+        for index in 0..40 {
+            let cell = board.cell(index).unwrap();
+            assert!(board.contains(cell.as_ivec2()));
+            assert_eq!(board.index(cell), Some(index));
+        }
+        assert_eq!(board.cell(9), Some(UVec2::new(9, 0)));
+        assert_eq!(board.cell(10), Some(UVec2::new(0, 1)));
+        assert_eq!(board.cell(40), None);
+        assert_eq!(board.index(UVec2::new(10, 0)), None);
+        assert_eq!(board.index(UVec2::new(0, 4)), None);
+    }
+
+    #[test]
+    fn empty_board_has_no_cells() {
+        let mut board = Board::new(8, 8);
+        board.width = 0;
+        assert_eq!(board.cell(0), None);
+        assert_eq!(board.index(UVec2::ZERO), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_size_is_rejected() {
+        Board::new(0, 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_tile_size_is_rejected() {
+        Board::new(8, 8).with_tile_size(0.0);
+    }
+
+    #[test]
+    fn local_round_trip() {
+        let board = Board::new(5, 3).with_tile_size(2.5);
+        for index in 0..15 {
+            let cell = board.cell(index).unwrap();
+            let local = board.cell_to_local(cell);
+            assert_eq!(local.y, 0.0);
+            assert_eq!(board.local_to_cell(local), Some(cell));
+            // Qualsiasi punto dentro il tile cade nella stessa cella.
+            let corner = local - Vec3::new(1.2, 0.0, 1.2);
+            assert_eq!(board.local_to_cell(corner), Some(cell));
+        }
+        assert_eq!(board.cell_to_local(UVec2::new(1, 2)), Vec3::new(3.75, 0.0, 6.25));
+        assert_eq!(board.local_to_cell(Vec3::new(-0.1, 0.0, 1.0)), None);
+        assert_eq!(board.local_to_cell(Vec3::new(1.0, 0.0, 7.5)), None);
+        assert_eq!(board.local_to_cell(Vec3::new(12.5, 0.0, 1.0)), None);
+    }
+
+    #[test]
+    fn world_round_trip() {
+        let board = Board::new(8, 6).with_tile_size(0.5);
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(3.0, 1.0, -2.0)
+                .with_rotation(Quat::from_rotation_y(0.7))
+                .with_scale(Vec3::splat(2.0)),
+        );
+        for index in 0..48 {
+            let cell = board.cell(index).unwrap();
+            let world = board.cell_to_world(&transform, cell);
+            assert_eq!(board.world_to_cell(&transform, world), Some(cell));
+            // L'altezza sopra la board non conta.
+            let above = world + transform.up() * 3.0;
+            assert_eq!(board.world_to_cell(&transform, above), Some(cell));
+        }
+        let outside = board.cell_to_world(&transform, UVec2::new(7, 5)) + transform.right() * 1.0;
+        assert_eq!(board.world_to_cell(&transform, outside), None);
     }
 }
@@ -1,5 +1,10 @@
 use bevy::prelude::*;
 use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use crate::input::{Action, ActionState, InputBindings, UiFocus};
+use crate::layout::{HoveredViewport, ViewportCamera, Viewports};
+use bevy::render::primitives::Aabb;
+use bevy_mod_imgui::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f32::consts::{FRAC_PI_2, PI, TAU};
 
 #[derive(Bundle, Default)]
@@ -27,9 +32,6 @@ pub struct PanOrbitSettings {
     pub pan_sensitivity: f32,
     pub orbit_sensitivity: f32,
     pub zoom_sensitivity: f32,
-    pub pan_key: Option<MouseButton>,
-    pub orbit_key: Option<MouseButton>,
-    pub zoom_key: Option<KeyCode>,
     pub scroll_action: Option<PanOrbitAction>,
     pub scroll_line_sensitivity: f32,
     pub scroll_pixel_sensitivity: f32,
@@ -60,9 +62,6 @@ impl Default for PanOrbitSettings {
             pan_sensitivity: 0.001,
             orbit_sensitivity: 0.1f32.to_radians(),
             zoom_sensitivity: 0.01,
-            pan_key: Some(MouseButton::Left),
-            orbit_key: Some(MouseButton::Middle),
-            zoom_key: Some(KeyCode::ShiftLeft),
             scroll_action: Some(PanOrbitAction::Zoom),
             scroll_line_sensitivity: 16.0,
             scroll_pixel_sensitivity: 1.0,
@@ -70,6 +69,9 @@ impl Default for PanOrbitSettings {
     }
 }
 
+/// Nome della camera dell'editor nel gestore delle camere.
+pub const EDITOR_CAMERA: &str = "Editor";
+
 pub fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         PanOrbitCameraBundle {
@@ -96,27 +98,31 @@ pub fn spawn_camera(mut commands: Commands) {
             contrast: 1.2,
             saturation: 1.0,
             scanline_intensity: 0.3,
-            _pad1: 0.0,
             window_size: Vec2::new(1920.0, 1080.0),
-            _pad2: Vec2::ZERO,
             dithering_strength: 0.1,
             edge_threshold: 0.1,
             color_snap_strength: 0.5,
-            _pad3: 0.0,
             palette: [Vec4::ZERO; 32], 
             color_count_and_pad: UVec4::new(8, 1, 0, 0),
+            ..default()
         },
         MeshPickingCamera,
         Pickable::default(),
+        crate::camera_manager::NamedCamera::new(EDITOR_CAMERA),
+        crate::pp_stack::PostProcessStack::shipped(),
     ));
 }
 
+#[allow(clippy::type_complexity)]
 pub fn pan_orbit_camera(
-    kbd: Res<ButtonInput<KeyCode>>,
-    btn: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
+    focus: Res<UiFocus>,
+    viewports: Res<Viewports>,
     mut evr_motion: EventReader<MouseMotion>,
     mut evr_scroll: EventReader<MouseWheel>,
     mut q_camera: Query<(
+        Entity,
+        Has<ViewportCamera>,
         &PanOrbitSettings,
         &mut PanOrbitState,
         &mut Transform,
@@ -124,11 +130,15 @@ pub fn pan_orbit_camera(
     )>,
 ) {
     let mut total_motion: Vec2 = evr_motion.read().map(|ev| ev.delta).sum();
+    // Mouse sopra un pannello imgui: né trascinamenti né rotellina arrivano alla camera.
+    if focus.mouse {
+        total_motion = Vec2::ZERO;
+    }
     total_motion.y = -total_motion.y;
     
     let mut total_scroll_lines = Vec2::ZERO;
     let mut total_scroll_pixels = Vec2::ZERO;
-    for ev in evr_scroll.read() {
+    for ev in evr_scroll.read().filter(|_| !focus.mouse) {
         match ev.unit {
             MouseScrollUnit::Line => {
                 total_scroll_lines.x += ev.x;
@@ -141,24 +151,32 @@ pub fn pan_orbit_camera(
         }
     }
     
-    for (settings, mut state, mut transform, mut projection) in &mut q_camera {
+    for (entity, is_viewport, settings, mut state, mut transform, mut projection) in &mut q_camera {
+        // Con più viewport si muove solo quello sotto il mouse; fuori dai pannelli, la vista principale.
+        let driven = match viewports.hovered {
+            Some(HoveredViewport::Extra(hovered)) => hovered == entity,
+            _ => !is_viewport,
+        };
+        if !driven {
+            continue;
+        }
         // Check if projection is orthographic
         let is_orthographic = matches!(*projection, Projection::Orthographic(_));
         
         // WASD movement for orbit center
         let mut wasd_move = Vec3::ZERO;
-        if kbd.pressed(KeyCode::KeyW) {
+        if actions.pressed(Action::CameraForward) {
             let fwd = transform.forward().as_vec3();
             wasd_move += Vec3::new(fwd.x, 0.0, fwd.z);
         }
-        if kbd.pressed(KeyCode::KeyS) {
+        if actions.pressed(Action::CameraBack) {
             let fwd = transform.forward().as_vec3();
             wasd_move -= Vec3::new(fwd.x, 0.0, fwd.z);
         }
-        if kbd.pressed(KeyCode::KeyA) {
+        if actions.pressed(Action::CameraLeft) {
             wasd_move -= transform.right().as_vec3();
         }
-        if kbd.pressed(KeyCode::KeyD) {
+        if actions.pressed(Action::CameraRight) {
             wasd_move += transform.right().as_vec3();
         }
         
@@ -179,11 +197,7 @@ pub fn pan_orbit_camera(
         }
         
         let mut total_orbit = Vec2::ZERO;
-        let orbiting = settings
-            .orbit_key
-            .map(|mb| btn.pressed(mb))
-            .unwrap_or(false);
-        if orbiting {
+        if actions.pressed(Action::CameraOrbit) {
             total_orbit -= total_motion * settings.orbit_sensitivity;
             if total_motion.length_squared() > 0.0 {
                 state.center = transform.translation + transform.forward().as_vec3() * state.radius;
@@ -198,11 +212,7 @@ pub fn pan_orbit_camera(
         }
         
         let mut total_zoom = Vec2::ZERO;
-        if settings
-            .zoom_key
-            .map(|key| kbd.pressed(key))
-            .unwrap_or(false)
-        {
+        if actions.pressed(Action::CameraZoom) {
             total_zoom -= total_motion * settings.zoom_sensitivity;
         }
         if settings.scroll_action == Some(PanOrbitAction::Zoom) {
@@ -212,11 +222,7 @@ pub fn pan_orbit_camera(
                 total_scroll_pixels * settings.scroll_pixel_sensitivity * settings.zoom_sensitivity;
         }
         
-        if settings
-            .orbit_key
-            .map(|mb| btn.just_pressed(mb))
-            .unwrap_or(false)
-        {
+        if actions.just_pressed(Action::CameraOrbit) {
             state.upside_down = state.pitch < -FRAC_PI_2 || state.pitch > FRAC_PI_2;
         }
         
@@ -275,4 +281,277 @@ pub fn pan_orbit_camera(
             transform.translation = state.center + transform.back() * state.radius;
         }
     }
-}
\ No newline at end of file
+}
+/// Punto di vista salvato della camera dell'editor, memorizzato nel file della scena.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct CameraBookmarks {
+    pub bookmarks: Vec<CameraBookmark>,
+    new_name: String,
+}
+
+/// Volo della camera verso un nuovo centro/raggio/orientamento (focus, viste, segnalibri).
+#[derive(Component)]
+pub struct CameraFlight {
+    from: (Vec3, f32, f32, f32),
+    to: (Vec3, f32, f32, f32),
+    elapsed: f32,
+    duration: f32,
+}
+
+impl CameraFlight {
+    pub fn new(state: &PanOrbitState, center: Vec3, radius: f32, yaw: f32, pitch: f32) -> Self {
+        // Lo yaw prende la strada più corta.
+        let delta = (yaw - state.yaw + PI).rem_euclid(TAU) - PI;
+        Self {
+            from: (state.center, state.radius, state.yaw, state.pitch),
+            to: (center, radius, state.yaw + delta, pitch),
+            elapsed: 0.0,
+            duration: 0.35,
+        }
+    }
+}
+
+fn apply_orbit(state: &PanOrbitState, transform: &mut Transform) {
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, state.yaw, state.pitch, 0.0);
+    transform.translation = state.center + transform.back() * state.radius;
+}
+
+fn animate_camera_flight(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cameras: Query<(Entity, &mut CameraFlight, &mut PanOrbitState, &mut Transform)>,
+) {
+    for (entity, mut flight, mut state, mut transform) in cameras.iter_mut() {
+        flight.elapsed += time.delta_secs();
+        let t = (flight.elapsed / flight.duration).min(1.0);
+        let s = t * t * (3.0 - 2.0 * t);
+        let (from_center, from_radius, from_yaw, from_pitch) = flight.from;
+        let (to_center, to_radius, to_yaw, to_pitch) = flight.to;
+        state.center = from_center.lerp(to_center, s);
+        // Il raggio varia in scala logaritmica, come lo zoom.
+        state.radius = from_radius * (to_radius / from_radius).powf(s);
+        state.yaw = from_yaw.lerp(to_yaw, s);
+        state.pitch = from_pitch.lerp(to_pitch, s);
+        state.upside_down = false;
+        apply_orbit(&state, &mut transform);
+        if t >= 1.0 {
+            state.yaw = state.yaw.rem_euclid(TAU);
+            commands.entity(entity).remove::<CameraFlight>();
+        }
+    }
+}
+
+/// Sfera che contiene le mesh dell'entità e dei suoi figli (o un intorno del suo pivot).
+fn selection_bounds(
+    entity: Entity,
+    children: &Query<&Children>,
+    bounds: &Query<(&GlobalTransform, Option<&Aabb>)>,
+) -> Option<(Vec3, f32)> {
+    let mut min = Vec3::splat(f32::INFINITY);
+    let mut max = Vec3::splat(f32::NEG_INFINITY);
+    for e in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+        let Ok((global, Some(aabb))) = bounds.get(e) else {
+            continue;
+        };
+        let center = Vec3::from(aabb.center);
+        let half = Vec3::from(aabb.half_extents);
+        for corner in 0..8 {
+            let sign = Vec3::new(
+                if corner & 1 == 0 { -1.0 } else { 1.0 },
+                if corner & 2 == 0 { -1.0 } else { 1.0 },
+                if corner & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let point = global.transform_point(center + half * sign);
+            min = min.min(point);
+            max = max.max(point);
+        }
+    }
+    if min.x <= max.x {
+        return Some(((min + max) * 0.5, ((max - min) * 0.5).length().max(0.05)));
+    }
+    let (global, _) = bounds.get(entity).ok()?;
+    Some((global.translation(), 0.5))
+}
+
+/// Inquadra la selezione e passa alle viste sugli assi (di default F e tastierino 1/3/7,
+/// con Ctrl le viste opposte).
+#[allow(clippy::type_complexity)]
+fn camera_view_shortcuts(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    selected: Query<Entity, With<crate::transform::Selected>>,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, Option<&Aabb>)>,
+    mut cameras: Query<
+        (Entity, &Camera, &PanOrbitState, &mut Projection),
+        Without<ViewportCamera>,
+    >,
+) {
+    let Some((camera_entity, camera, state, mut projection)) =
+        cameras.iter_mut().find(|(_, camera, ..)| camera.is_active)
+    else {
+        return;
+    };
+
+    if actions.just_pressed(Action::CameraFocus) {
+        let Some((center, size)) = selected
+            .iter()
+            .next()
+            .and_then(|entity| selection_bounds(entity, &children, &bounds))
+        else {
+            return;
+        };
+        let radius = match &mut *projection {
+            Projection::Perspective(perspective) => {
+                // Il lato più stretto della vista decide la distanza.
+                let half_fov = 0.5 * perspective.fov * perspective.aspect_ratio.min(1.0);
+                size / half_fov.sin()
+            }
+            Projection::Orthographic(ortho) => {
+                // In ortogonale la distanza non cambia l'inquadratura: conta la scala.
+                if let Some(viewport) = camera.logical_viewport_size() {
+                    ortho.scale = (size * 2.2 / viewport.y).clamp(0.01, 100.0);
+                }
+                state.radius.max(size * 2.0)
+            }
+            _ => state.radius,
+        };
+        commands.entity(camera_entity).insert(CameraFlight::new(
+            state,
+            center,
+            radius * 1.1,
+            state.yaw,
+            state.pitch,
+        ));
+        return;
+    }
+
+    let view = [
+        (Action::ViewFront, 0.0, 0.0),
+        (Action::ViewBack, PI, 0.0),
+        (Action::ViewRight, FRAC_PI_2, 0.0),
+        (Action::ViewLeft, -FRAC_PI_2, 0.0),
+        (Action::ViewTop, 0.0, -FRAC_PI_2),
+        (Action::ViewBottom, 0.0, FRAC_PI_2),
+    ]
+    .into_iter()
+    .find(|(action, ..)| actions.just_pressed(*action))
+    .map(|(_, yaw, pitch)| (yaw, pitch));
+    if let Some((yaw, pitch)) = view {
+        commands.entity(camera_entity).insert(CameraFlight::new(
+            state,
+            state.center,
+            state.radius,
+            yaw,
+            pitch,
+        ));
+    }
+}
+
+fn camera_bookmarks_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    bindings: Res<InputBindings>,
+    cameras: Query<(Entity, &Camera, &PanOrbitState), Without<ViewportCamera>>,
+) {
+    let bookmarks = &mut *bookmarks;
+    let active = cameras.iter().find(|(_, camera, _)| camera.is_active);
+    let ui = context.ui();
+    ui.window("Views")
+        .size([320.0, 200.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let Some((entity, _, state)) = active else {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "Editor camera not active");
+                return;
+            };
+            let mut fly = |center: Vec3, radius: f32, yaw: f32, pitch: f32| {
+                commands
+                    .entity(entity)
+                    .insert(CameraFlight::new(state, center, radius, yaw, pitch));
+            };
+            for (label, yaw, pitch) in [
+                ("Front", 0.0, 0.0),
+                ("Back", PI, 0.0),
+                ("Right", FRAC_PI_2, 0.0),
+                ("Left", -FRAC_PI_2, 0.0),
+                ("Top", 0.0, -FRAC_PI_2),
+            ] {
+                if ui.small_button(label) {
+                    fly(state.center, state.radius, yaw, pitch);
+                }
+                ui.same_line();
+            }
+            ui.new_line();
+            ui.text_disabled(format!(
+                "Focus: {}, views: {} / {} / {}",
+                bindings.describe(Action::CameraFocus),
+                bindings.describe(Action::ViewFront),
+                bindings.describe(Action::ViewRight),
+                bindings.describe(Action::ViewTop),
+            ));
+
+            ui.separator();
+            ui.input_text("##bookmark name", &mut bookmarks.new_name)
+                .hint("Bookmark name")
+                .build();
+            ui.same_line();
+            if ui.button("Add") {
+                let name = if bookmarks.new_name.is_empty() {
+                    format!("View {}", bookmarks.bookmarks.len() + 1)
+                } else {
+                    std::mem::take(&mut bookmarks.new_name)
+                };
+                bookmarks.bookmarks.push(CameraBookmark {
+                    name,
+                    center: state.center.to_array(),
+                    radius: state.radius,
+                    yaw: state.yaw,
+                    pitch: state.pitch,
+                });
+            }
+            let mut removed = None;
+            for (i, bookmark) in bookmarks.bookmarks.iter().enumerate() {
+                let _id = ui.push_id(format!("bookmark{}", i));
+                if ui.selectable_config(&bookmark.name).size([220.0, 0.0]).build() {
+                    fly(
+                        Vec3::from_array(bookmark.center),
+                        bookmark.radius,
+                        bookmark.yaw,
+                        bookmark.pitch,
+                    );
+                }
+                ui.same_line_with_pos(240.0);
+                if ui.small_button("Delete") {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                bookmarks.bookmarks.remove(i);
+            }
+        });
+}
+
+pub struct CameraViewPlugin;
+impl Plugin for CameraViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraBookmarks>()
+            .add_systems(
+                Update,
+                (camera_view_shortcuts, animate_camera_flight)
+                    .chain()
+                    .after(pan_orbit_camera)
+                    .in_set(crate::play_mode::EditSet),
+            )
+            .add_systems(Update, camera_bookmarks_ui);
+    }
+}
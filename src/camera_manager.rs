@@ -0,0 +1,414 @@
+use crate::pp::PostProcessSettings;
+use crate::retrocamera::{RetroCamera, RetroRenderTarget};
+use crate::transform::Selected;
+use bevy::prelude::*;
+use bevy::render::{
+    camera::RenderTarget,
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+};
+use bevy_mod_imgui::prelude::*;
+
+/// Camera registrata nel gestore: può diventare la camera attiva e avere un'anteprima.
+#[derive(Component)]
+pub struct NamedCamera {
+    pub name: String,
+}
+
+impl NamedCamera {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+pub enum CameraTarget {
+    Entity(Entity),
+    Name(String),
+}
+
+/// Cambia la camera attiva, con una transizione di `blend` secondi (0 = taglio netto).
+/// È anche il punto d'ingresso per gli script.
+#[derive(Event)]
+pub struct SetActiveCamera {
+    pub target: CameraTarget,
+    pub blend: f32,
+}
+
+impl SetActiveCamera {
+    pub fn entity(entity: Entity) -> Self {
+        Self {
+            target: CameraTarget::Entity(entity),
+            blend: 0.0,
+        }
+    }
+
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            target: CameraTarget::Name(name.into()),
+            blend: 0.0,
+        }
+    }
+
+    pub fn with_blend(mut self, seconds: f32) -> Self {
+        self.blend = seconds;
+        self
+    }
+}
+
+/// Transizione in corso: la camera di blend va da `from` alla camera `to`.
+struct CameraBlend {
+    from: Transform,
+    from_fov: f32,
+    to: Entity,
+    elapsed: f32,
+    duration: f32,
+}
+
+#[derive(Resource)]
+pub struct CameraManager {
+    active: Option<Entity>,
+    blend: Option<CameraBlend>,
+    preview: Option<Entity>,
+    preview_image: Handle<Image>,
+    preview_texture: Option<imgui::TextureId>,
+    blend_time: f32,
+}
+
+impl CameraManager {
+    /// Camera attiva, o quella verso cui si sta sfumando.
+    pub fn active(&self) -> Option<Entity> {
+        self.active
+    }
+}
+
+/// Camera che rende la transizione tra due camere registrate.
+#[derive(Component)]
+struct BlendCamera;
+
+/// Camera che rende l'anteprima picture-in-picture in una texture.
+#[derive(Component)]
+struct PreviewCamera;
+
+const PREVIEW_SIZE: [u32; 2] = [320, 180];
+
+fn fov_of(projection: &Projection) -> Option<f32> {
+    match projection {
+        Projection::Perspective(perspective) => Some(perspective.fov),
+        _ => None,
+    }
+}
+
+fn setup_camera_manager(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = Extent3d {
+        width: PREVIEW_SIZE[0],
+        height: PREVIEW_SIZE[1],
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    let preview_image = images.add(image);
+
+    commands.spawn((
+        Name::new("Camera preview"),
+        PreviewCamera,
+        Camera3d::default(),
+        Camera {
+            is_active: false,
+            hdr: true,
+            order: -1,
+            target: RenderTarget::Image(preview_image.clone().into()),
+            ..default()
+        },
+        PostProcessSettings::default(),
+    ));
+    // Il target della retro texture lo assegna `attach_retro_cameras` al PostStartup.
+    commands.spawn((
+        Name::new("Camera blend"),
+        BlendCamera,
+        Camera3d::default(),
+        Camera {
+            is_active: false,
+            hdr: true,
+            ..default()
+        },
+        RetroCamera,
+        PostProcessSettings::default(),
+    ));
+
+    commands.insert_resource(CameraManager {
+        active: None,
+        blend: None,
+        preview: None,
+        preview_image,
+        preview_texture: None,
+        blend_time: 1.0,
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn set_active_camera(
+    mut events: EventReader<SetActiveCamera>,
+    mut manager: ResMut<CameraManager>,
+    mut cameras: Query<(Entity, &NamedCamera, &mut Camera, &GlobalTransform, &Projection)>,
+    mut blend_camera: Query<
+        (&mut Camera, &mut Transform, &mut Projection, &mut PostProcessSettings),
+        (With<BlendCamera>, Without<NamedCamera>),
+    >,
+    settings: Query<&PostProcessSettings, Without<BlendCamera>>,
+) {
+    for event in events.read() {
+        let target = match &event.target {
+            CameraTarget::Entity(entity) => cameras.contains(*entity).then_some(*entity),
+            CameraTarget::Name(name) => cameras
+                .iter()
+                .find(|(_, named, ..)| named.name == *name)
+                .map(|(entity, ..)| entity),
+        };
+        let Some(target) = target else {
+            warn!("Camera manager: camera non registrata");
+            continue;
+        };
+        let Ok((mut camera, mut transform, mut projection, mut post)) = blend_camera.single_mut()
+        else {
+            continue;
+        };
+
+        // Si parte da dove si trova ora l'inquadratura, anche a metà di un'altra transizione.
+        let from = match manager.active {
+            Some(active) if active != target && event.blend > 0.0 => {
+                if manager.blend.is_some() {
+                    Some((*transform, fov_of(&projection)))
+                } else {
+                    cameras.get(active).ok().map(|(_, _, _, global, active_projection)| {
+                        (global.compute_transform(), fov_of(active_projection))
+                    })
+                }
+            }
+            _ => None,
+        };
+
+        for (entity, _, mut named_camera, _, _) in cameras.iter_mut() {
+            named_camera.is_active = entity == target && from.is_none();
+        }
+        camera.is_active = from.is_some();
+        manager.active = Some(target);
+        manager.blend = None;
+
+        let (Some((from, from_fov)), Ok((.., target_projection))) = (from, cameras.get(target))
+        else {
+            continue;
+        };
+        *transform = from;
+        *projection = target_projection.clone();
+        if let Ok(target_settings) = settings.get(target) {
+            *post = *target_settings;
+        }
+        let to_fov = fov_of(target_projection).unwrap_or(std::f32::consts::FRAC_PI_4);
+        if let (Projection::Perspective(perspective), Some(fov)) = (&mut *projection, from_fov) {
+            perspective.fov = fov;
+        }
+        manager.blend = Some(CameraBlend {
+            from,
+            from_fov: from_fov.unwrap_or(to_fov),
+            to: target,
+            elapsed: 0.0,
+            duration: event.blend,
+        });
+    }
+}
+
+/// Porta la camera di blend verso la destinazione (che può muoversi) e alla fine la disattiva.
+#[allow(clippy::type_complexity)]
+fn update_camera_blend(
+    time: Res<Time>,
+    mut manager: ResMut<CameraManager>,
+    mut cameras: Query<(&mut Camera, &GlobalTransform, &Projection), With<NamedCamera>>,
+    mut blend_camera: Query<
+        (&mut Camera, &mut Transform, &mut Projection),
+        (With<BlendCamera>, Without<NamedCamera>),
+    >,
+) {
+    let Some(blend) = manager.blend.as_mut() else {
+        return;
+    };
+    let Ok((mut camera, mut transform, mut projection)) = blend_camera.single_mut() else {
+        return;
+    };
+    blend.elapsed += time.delta_secs();
+    let Ok((mut target_camera, target_global, target_projection)) = cameras.get_mut(blend.to)
+    else {
+        // La destinazione è sparita: si resta dove si è.
+        manager.blend = None;
+        return;
+    };
+
+    let t = (blend.elapsed / blend.duration).clamp(0.0, 1.0);
+    if t >= 1.0 {
+        target_camera.is_active = true;
+        camera.is_active = false;
+        manager.blend = None;
+        return;
+    }
+    let t = t * t * (3.0 - 2.0 * t);
+    let to = target_global.compute_transform();
+    transform.translation = blend.from.translation.lerp(to.translation, t);
+    transform.rotation = blend.from.rotation.slerp(to.rotation, t);
+    if let (Projection::Perspective(perspective), Some(to_fov)) =
+        (&mut *projection, fov_of(target_projection))
+    {
+        perspective.fov = blend.from_fov.lerp(to_fov, t);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn sync_preview_camera(
+    manager: Res<CameraManager>,
+    cameras: Query<(&GlobalTransform, &Projection, Option<&PostProcessSettings>), With<NamedCamera>>,
+    mut preview: Query<
+        (&mut Camera, &mut Transform, &mut Projection, &mut PostProcessSettings),
+        (With<PreviewCamera>, Without<NamedCamera>),
+    >,
+) {
+    let Ok((mut camera, mut transform, mut projection, mut post)) = preview.single_mut() else {
+        return;
+    };
+    let source = manager.preview.and_then(|entity| cameras.get(entity).ok());
+    camera.is_active = source.is_some();
+    if let Some((global, source_projection, source_settings)) = source {
+        *transform = global.compute_transform();
+        *projection = source_projection.clone();
+        if let Some(source_settings) = source_settings {
+            *post = *source_settings;
+        }
+    }
+}
+
+/// Piramide della vista per le camere piazzate che non stanno rendendo.
+fn draw_named_cameras(
+    mut gizmos: Gizmos,
+    cameras: Query<(&Camera, &GlobalTransform), With<NamedCamera>>,
+) {
+    for (camera, global) in cameras.iter() {
+        if camera.is_active {
+            continue;
+        }
+        let origin = global.translation();
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| global.transform_point(Vec3::new(x * 0.16, y * 0.09, -0.25)));
+        let color = Color::srgb(0.9, 0.9, 0.9);
+        for i in 0..4 {
+            gizmos.line(origin, corners[i], color);
+            gizmos.line(corners[i], corners[(i + 1) % 4], color);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn camera_manager_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut manager: ResMut<CameraManager>,
+    mut events: EventWriter<SetActiveCamera>,
+    cameras: Query<(Entity, &NamedCamera, &Camera, &GlobalTransform, &Projection)>,
+    settings: Query<&PostProcessSettings>,
+    selected: Query<Entity, With<Selected>>,
+    retro: Res<RetroRenderTarget>,
+) {
+    if manager.preview_texture.is_none() {
+        manager.preview_texture = Some(context.register_bevy_texture(manager.preview_image.clone()));
+    }
+    let manager = &mut *manager;
+    let ui = context.ui();
+    ui.window("Cameras")
+        .size([350.0, 480.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            ui.slider("Blend time", 0.0, 5.0, &mut manager.blend_time);
+            ui.separator();
+            for (entity, named, ..) in cameras.iter() {
+                let _id = ui.push_id(format!("{:?}", entity));
+                if ui.radio_button_bool(&named.name, manager.active() == Some(entity)) {
+                    events.write(SetActiveCamera::entity(entity).with_blend(manager.blend_time));
+                }
+                ui.same_line_with_pos(200.0);
+                let previewing = manager.preview == Some(entity);
+                if ui.small_button(if previewing { "Hide" } else { "Preview" }) {
+                    manager.preview = if previewing { None } else { Some(entity) };
+                }
+                ui.same_line();
+                if ui.small_button("Select") {
+                    for other in selected.iter() {
+                        commands.entity(other).remove::<Selected>();
+                    }
+                    commands.entity(entity).insert(Selected);
+                }
+            }
+
+            // Nuova camera dove si trova la vista attuale, con lo stesso post-process.
+            if ui.button("Add camera at view") {
+                if let Some((source, _, _, global, projection)) =
+                    cameras.iter().find(|(_, _, camera, ..)| camera.is_active)
+                {
+                    let count = cameras.iter().count();
+                    let target = retro
+                        .handle
+                        .clone()
+                        .map(|handle| RenderTarget::Image(handle.into()))
+                        .unwrap_or_default();
+                    let mut camera = commands.spawn((
+                        Name::new(format!("Camera {}", count)),
+                        NamedCamera::new(format!("Camera {}", count)),
+                        Camera3d::default(),
+                        Camera {
+                            is_active: false,
+                            hdr: true,
+                            target,
+                            ..default()
+                        },
+                        projection.clone(),
+                        global.compute_transform(),
+                        RetroCamera,
+                    ));
+                    if let Ok(source_settings) = settings.get(source) {
+                        camera.insert(*source_settings);
+                    }
+                }
+            }
+
+            if let (Some(entity), Some(texture)) = (manager.preview, manager.preview_texture) {
+                ui.separator();
+                let name = cameras
+                    .get(entity)
+                    .map_or("?", |(_, named, ..)| named.name.as_str());
+                ui.text(format!("Preview: {}", name));
+                let width = ui.content_region_avail()[0];
+                let height = width * PREVIEW_SIZE[1] as f32 / PREVIEW_SIZE[0] as f32;
+                imgui::Image::new(texture, [width, height]).build(ui);
+            }
+        });
+}
+
+pub struct CameraManagerPlugin;
+impl Plugin for CameraManagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SetActiveCamera>()
+            .add_systems(Startup, setup_camera_manager)
+            .add_systems(
+                Update,
+                (
+                    camera_manager_ui,
+                    set_active_camera,
+                    update_camera_blend,
+                    sync_preview_camera,
+                    draw_named_cameras,
+                )
+                    .chain(),
+            );
+    }
+}
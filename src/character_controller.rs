@@ -1,7 +1,11 @@
+use crate::input::{Action, ActionState, UiFocus};
 use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use bevy_rapier3d::prelude::*;
 use std::f32::consts::FRAC_PI_2;
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct CharacterController {
     pub speed: f32,
     pub sensitivity: f32,
@@ -9,13 +13,80 @@ pub struct CharacterController {
     pub is_jumping: bool,
     pub yaw: f32,
     pub pitch: f32,
+    /// Accelerazione verso il basso (m/s²).
+    pub gravity: f32,
+    /// Velocità verticale corrente, integrata dal sistema di movimento.
+    pub vertical_velocity: f32,
+    /// Quanto velocemente il personaggio si gira verso la direzione di marcia.
+    pub turn_speed: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            speed: 4.0,
+            sensitivity: 1.0,
+            jump_strength: 5.0,
+            is_jumping: false,
+            yaw: 0.0,
+            pitch: -0.3,
+            gravity: 9.81,
+            vertical_velocity: 0.0,
+            turn_speed: 10.0,
+        }
+    }
 }
 
+/// Capsule used both for the character collider and the kinematic controller shape.
+pub const CHARACTER_CAPSULE_HALF_HEIGHT: f32 = 0.6;
+pub const CHARACTER_CAPSULE_RADIUS: f32 = 0.2;
+/// Height of the capsule centre above the character's feet.
+pub const CHARACTER_CAPSULE_OFFSET: f32 = 1.0;
+
+/// Controller Rapier per il personaggio: capsula, gradini e pendenze.
+pub fn kinematic_controller() -> KinematicCharacterController {
+    KinematicCharacterController {
+        custom_shape: Some((
+            Collider::capsule_y(CHARACTER_CAPSULE_HALF_HEIGHT, CHARACTER_CAPSULE_RADIUS),
+            Vec3::Y * CHARACTER_CAPSULE_OFFSET,
+            Quat::IDENTITY,
+        )),
+        offset: CharacterLength::Absolute(0.02),
+        slide: true,
+        autostep: Some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(0.35),
+            min_width: CharacterLength::Absolute(0.2),
+            include_dynamic_bodies: false,
+        }),
+        max_slope_climb_angle: 45.0_f32.to_radians(),
+        min_slope_slide_angle: 30.0_f32.to_radians(),
+        snap_to_ground: Some(CharacterLength::Absolute(0.3)),
+        apply_impulse_to_dynamic_bodies: true,
+        ..default()
+    }
+}
+
+/// Camera in terza persona a braccio telescopico: resta dietro al personaggio e si accorcia
+/// davanti agli ostacoli invece di attraversarli.
 #[derive(Component)]
 pub struct ThirdPersonCamera {
     pub distance: f32,
     pub height: f32,
     pub sensitivity: Vec2,
+    /// Altezza del punto guardato sopra i piedi del personaggio.
+    pub pivot_height: f32,
+    /// Spostamento laterale della camera (positivo = spalla destra, 0 = centrata).
+    pub shoulder_offset: f32,
+    /// Raggio della sfera lanciata lungo il braccio: distanza minima dai muri.
+    pub probe_radius: f32,
+    /// Smorzamento di posizione (1/s): più alto = segue più rigidamente.
+    pub position_damping: f32,
+    /// Smorzamento della rotazione (1/s).
+    pub rotation_damping: f32,
+    /// Lunghezza corrente del braccio, accorciata dalle collisioni.
+    arm_length: f32,
+    /// Pivot smorzato, per non tremare con il corpo fisico.
+    pivot: Option<Vec3>,
 }
 
 impl Default for ThirdPersonCamera {
@@ -24,92 +95,297 @@ impl Default for ThirdPersonCamera {
             distance: 5.0,
             height: 2.0,
             sensitivity: Vec2::new(0.003, 0.002),
+            pivot_height: 1.0,
+            shoulder_offset: 0.0,
+            probe_radius: 0.2,
+            position_damping: 15.0,
+            rotation_damping: 20.0,
+            arm_length: 0.0,
+            pivot: None,
         }
     }
 }
 
-fn setup_third_person_controller(
-    mut commands: Commands,
-    characters: Query<Entity, With<CharacterController>>,
-) {
-    // Find the character entity
-    if let Ok(character_entity) = characters.single() {
-        // Spawn the third person camera as a child of the character
-        commands.entity(character_entity).with_children(|parent| {
-            parent.spawn((
-                ThirdPersonCamera::default(),
-                Camera3d::default(),
-                Transform::from_xyz(0.0, 2.0, -5.0).looking_at(Vec3::ZERO, Vec3::Y),
-                crate::pp::PostProcessSettings {
-                    edge_intensity: 0.5,                       // Intensità bordi
-                    color_levels: 4.0,                         // Livelli colore (4-16)
-                    cel_levels: 100.0,                         // Livelli cel shading (2-8)
-                    scanline_intensity: 2.5,                   // Scanline (0.0-1.0)
-                    contrast: 1.0,                             // Contrasto
-                    saturation: 0.7,                           // Saturazione
-                    window_size: Vec2::new(1920.0, 1080.0),    // Dimensioni finestra
-                    dithering_strength: 0.75,                  // Intensità dithering
-                    edge_threshold: 0.05,                      // Soglia bordi
-                    color_snap_strength: 0.5,                  // Intensità snapping colore
-                    edge_denoise: 0.5,                     // Denoise bordi
-                    ..Default::default()
-                },
-            ));
-        });
+/// Nome della camera in terza persona nel gestore delle camere.
+pub const THIRD_PERSON_CAMERA: &str = "Third person";
+
+fn setup_third_person_controller(mut commands: Commands) {
+    // La camera non è figlia del personaggio: la sua posizione è calcolata in world space
+    // da `update_third_person_camera`. Parte spenta, la accende la modalità Play.
+    commands.spawn((
+        ThirdPersonCamera::default(),
+        crate::camera_manager::NamedCamera::new(THIRD_PERSON_CAMERA),
+        Camera3d::default(),
+        Camera {
+            is_active: false,
+            hdr: true,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 2.0, -5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        crate::retrocamera::RetroCamera,
+        crate::pp::PostProcessSettings {
+            edge_intensity: 0.5,                       // Intensità bordi
+            color_levels: 4.0,                         // Livelli colore (4-16)
+            cel_levels: 100.0,                         // Livelli cel shading (2-8)
+            scanline_intensity: 2.5,                   // Scanline (0.0-1.0)
+            contrast: 1.0,                             // Contrasto
+            saturation: 0.7,                           // Saturazione
+            window_size: Vec2::new(1920.0, 1080.0),    // Dimensioni finestra
+            dithering_strength: 0.75,                  // Intensità dithering
+            edge_threshold: 0.05,                      // Soglia bordi
+            color_snap_strength: 0.5,                  // Intensità snapping colore
+            edge_denoise: 0.5,                     // Denoise bordi
+            ..Default::default()
+        },
+    ));
+}
+
+/// Fattore di interpolazione per uno smorzamento esponenziale indipendente dal framerate.
+fn damp(damping: f32, dt: f32) -> f32 {
+    1.0 - (-damping * dt).exp()
+}
+
+/// Lancia una sfera da `from` a `to` e restituisce il punto più lontano raggiungibile.
+fn sweep(
+    context: &RapierContext,
+    from: Vec3,
+    to: Vec3,
+    radius: f32,
+    filter: QueryFilter,
+) -> Vec3 {
+    let offset = to - from;
+    let length = offset.length();
+    if length < 1e-4 {
+        return to;
+    }
+    let direction = offset / length;
+    let ball = bevy_rapier3d::parry::shape::Ball::new(radius);
+    let options = ShapeCastOptions {
+        max_time_of_impact: length,
+        // Se si parte già dentro un collider non si accorcia il braccio a zero.
+        stop_at_penetration: false,
+        ..default()
+    };
+    match context.cast_shape(from, Quat::IDENTITY, direction, &ball, options, filter) {
+        Some((_, hit)) => from + direction * hit.time_of_impact,
+        None => to,
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn update_third_person_camera(
-    mut camera_query: Query<(&mut Transform, &ThirdPersonCamera)>,
-    character_query: Query<&Transform, (With<CharacterController>, Without<ThirdPersonCamera>)>,
+    time: Res<Time>,
+    rapier: ReadRapierContext,
+    mut camera_query: Query<(&mut Transform, &mut ThirdPersonCamera)>,
+    character_query: Query<
+        (Entity, &Transform),
+        (With<CharacterController>, Without<ThirdPersonCamera>),
+    >,
     mut controller_query: Query<&mut CharacterController>,
+    children: Query<&Children>,
     mouse_motion: Res<bevy::input::mouse::AccumulatedMouseMotion>,
-    time: Res<Time>,
+    focus: Res<UiFocus>,
 ) {
-    if let (
-        Ok((mut camera_transform, camera_settings)),
-        Ok(character_transform),
+    let (
+        Ok((mut camera_transform, mut camera_settings)),
+        Ok((character, character_transform)),
         Ok(mut controller),
     ) = (
         camera_query.single_mut(),
         character_query.single(),
         controller_query.single_mut(),
-    ) {
-        let delta = mouse_motion.delta;
+    )
+    else {
+        return;
+    };
+    let dt = time.delta_secs();
+    // Con il mouse su un pannello imgui la visuale resta ferma.
+    let delta = if focus.mouse {
+        Vec2::ZERO
+    } else {
+        mouse_motion.delta * controller.sensitivity
+    };
+
+    if delta != Vec2::ZERO {
+        // Update yaw and pitch in the controller
+        controller.yaw -= delta.x * camera_settings.sensitivity.x;
+
+        const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.1;
+        controller.pitch = (controller.pitch - delta.y * camera_settings.sensitivity.y)
+            .clamp(-PITCH_LIMIT, PITCH_LIMIT);
+    }
+
+    let rotation = Quat::from_rotation_y(controller.yaw) * Quat::from_rotation_x(controller.pitch);
+
+    // Il pivot segue il personaggio con un po' di ritardo: assorbe i piccoli scatti della fisica.
+    let target_pivot = character_transform.translation + Vec3::Y * camera_settings.pivot_height;
+    let first_frame = camera_settings.pivot.is_none();
+    let pivot = match camera_settings.pivot {
+        Some(pivot) => pivot.lerp(target_pivot, damp(camera_settings.position_damping, dt)),
+        None => target_pivot,
+    };
+    camera_settings.pivot = Some(pivot);
 
-        if delta != Vec2::ZERO {
-            // Update yaw and pitch in the controller
-            controller.yaw -= delta.x * camera_settings.sensitivity.x;
+    // Braccio: dal pivot alla spalla, poi all'indietro fino alla distanza voluta.
+    let arm = rotation
+        * Vec3::new(
+            0.0,
+            camera_settings.height - camera_settings.pivot_height,
+            camera_settings.distance,
+        );
+    let shoulder = pivot + rotation * Vec3::X * camera_settings.shoulder_offset;
+
+    let mut arm_length = arm.length();
+    if let Ok(context) = rapier.single() {
+        // Il personaggio e le sue parti (ragdoll, maniglie) non bloccano la camera.
+        let mut parts: Vec<Entity> = children.iter_descendants(character).collect();
+        parts.push(character);
+        let ignore_character = |entity: Entity| !parts.contains(&entity);
+        let filter = QueryFilter::default()
+            .exclude_sensors()
+            .predicate(&ignore_character);
+        let radius = camera_settings.probe_radius;
+        let shoulder = sweep(&context, pivot, shoulder, radius, filter);
+        let end = sweep(&context, shoulder, shoulder + arm, radius, filter);
+        arm_length = shoulder.distance(end);
+    }
+
+    // Gli ostacoli accorciano subito il braccio, l'allungamento invece è smorzato.
+    camera_settings.arm_length = if first_frame || arm_length < camera_settings.arm_length {
+        arm_length
+    } else {
+        camera_settings
+            .arm_length
+            .lerp(arm_length, damp(camera_settings.position_damping, dt))
+    };
+
+    let direction = arm.normalize_or_zero();
+    camera_transform.translation = shoulder + direction * camera_settings.arm_length;
+
+    // Guarda oltre la spalla, parallela alla direzione del braccio.
+    let look = Transform::default().looking_to(-direction, Vec3::Y).rotation;
+    camera_transform.rotation = if first_frame {
+        look
+    } else {
+        camera_transform
+            .rotation
+            .slerp(look, damp(camera_settings.rotation_damping, dt))
+    };
+}
+
+/// All'ingresso in Play la camera parte già al suo posto, senza smorzare dalla sessione prima.
+fn reset_third_person_camera(mut cameras: Query<&mut ThirdPersonCamera>) {
+    for mut camera in cameras.iter_mut() {
+        camera.pivot = None;
+    }
+}
+
+fn third_person_camera_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut cameras: Query<&mut ThirdPersonCamera>,
+) {
+    let Ok(mut camera) = cameras.single_mut() else {
+        return;
+    };
+    let ui = context.ui();
+    ui.window("Third Person Camera")
+        .size([300.0, 220.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            ui.slider("Distance", 1.0, 15.0, &mut camera.distance);
+            ui.slider("Height", 0.0, 5.0, &mut camera.height);
+            ui.slider("Pivot height", 0.0, 2.5, &mut camera.pivot_height);
+            ui.slider("Shoulder offset", -1.5, 1.5, &mut camera.shoulder_offset);
+            ui.same_line();
+            if ui.small_button("Swap") {
+                camera.shoulder_offset = -camera.shoulder_offset;
+            }
+            ui.slider("Probe radius", 0.05, 0.5, &mut camera.probe_radius);
+            ui.slider("Position damping", 1.0, 50.0, &mut camera.position_damping);
+            ui.slider("Rotation damping", 1.0, 50.0, &mut camera.rotation_damping);
+        });
+}
 
-            const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.1;
-            controller.pitch = (controller.pitch - delta.y * camera_settings.sensitivity.y)
-                .clamp(-PITCH_LIMIT, PITCH_LIMIT);
+/// Movimento relativo allo yaw della camera, salto e gravità tramite il controller cinematico di Rapier.
+fn move_character(
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut characters: Query<
+        (
+            &mut CharacterController,
+            &mut KinematicCharacterController,
+            &mut Transform,
+            Option<&KinematicCharacterControllerOutput>,
+        ),
+        // Con il ragdoll attivo il corpo è in mano alla fisica.
+        Without<crate::ragdoll::RagdollActive>,
+    >,
+) {
+    let dt = time.delta_secs();
+    for (mut controller, mut kinematic, mut transform, output) in characters.iter_mut() {
+        let mut input = Vec2::ZERO;
+        if actions.pressed(Action::MoveForward) {
+            input.y += 1.0;
+        }
+        if actions.pressed(Action::MoveBack) {
+            input.y -= 1.0;
+        }
+        if actions.pressed(Action::MoveLeft) {
+            input.x -= 1.0;
+        }
+        if actions.pressed(Action::MoveRight) {
+            input.x += 1.0;
         }
 
-        // Calculate camera position based on character position and controller rotation
+        // La camera guarda lungo -Z ruotato dello yaw.
         let yaw_rotation = Quat::from_rotation_y(controller.yaw);
-        let pitch_rotation = Quat::from_rotation_x(controller.pitch);
-
-        // Camera offset from character (behind and above)
-        let camera_offset = yaw_rotation
-            * pitch_rotation
-            * Vec3::new(0.0, camera_settings.height, camera_settings.distance);
+        let forward = yaw_rotation * Vec3::NEG_Z;
+        let right = yaw_rotation * Vec3::X;
+        let direction = (forward * input.y + right * input.x).normalize_or_zero();
 
-        // Position camera
-        camera_transform.translation = character_transform.translation + camera_offset;
+        let grounded = output.is_some_and(|o| o.grounded);
+        if grounded {
+            controller.is_jumping = false;
+            // Una piccola spinta verso il basso tiene il personaggio attaccato alle discese.
+            controller.vertical_velocity = controller.vertical_velocity.max(-1.0);
+            if actions.just_pressed(Action::Jump) {
+                controller.vertical_velocity = controller.jump_strength;
+                controller.is_jumping = true;
+            }
+        }
+        controller.vertical_velocity -= controller.gravity * dt;
 
-        // Make camera look at character
-        camera_transform.look_at(
-            character_transform.translation + Vec3::new(0.0, 1.0, 0.0),
-            Vec3::Y,
+        kinematic.translation = Some(
+            direction * controller.speed * dt + Vec3::Y * controller.vertical_velocity * dt,
         );
+
+        if direction != Vec3::ZERO {
+            let target = Transform::default().looking_to(-direction, Vec3::Y).rotation;
+            let t = (controller.turn_speed * dt).min(1.0);
+            transform.rotation = transform.rotation.slerp(target, t);
+        }
     }
 }
 
 pub struct ControllerPlugin;
 impl Plugin for ControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_third_person_controller);
-        app.add_systems(Update, update_third_person_camera);
+        app.register_type::<CharacterController>()
+            .add_systems(Startup, setup_third_person_controller);
+        app.add_systems(
+            OnEnter(crate::play_mode::EditorMode::Play),
+            reset_third_person_camera,
+        );
+        app.add_systems(
+            Update,
+            (move_character.in_set(crate::play_mode::PlaySet), third_person_camera_ui),
+        );
+        // Dopo il writeback di Rapier: la camera vede la posizione del personaggio di questo frame.
+        app.add_systems(
+            PostUpdate,
+            update_third_person_camera
+                .after(PhysicsSet::Writeback)
+                .before(TransformSystem::TransformPropagate)
+                .run_if(in_state(crate::play_mode::EditorMode::Play)),
+        );
     }
 }
@@ -0,0 +1,376 @@
+use crate::ik::{inverse_kinematics_system, IkConstraint};
+use crate::ragdoll::RagdollActive;
+use crate::skeleton::{HumanoidBone, SkeletonMap};
+use crate::transform::Selected;
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Una gamba gestita dal foot IK: il vincolo sta sul piede, la catena è stinco + coscia.
+struct FootChain {
+    foot: Entity,
+    knee: Entity,
+    target: Entity,
+}
+
+/// Appoggia i piedi sul terreno irregolare: un raggio sotto ogni piede sposta il bersaglio
+/// dell'`IkConstraint` della gamba all'altezza del suolo.
+#[derive(Component)]
+pub struct FootIk {
+    pub enabled: bool,
+    /// Altezza sopra il piede da cui parte il raggio.
+    pub ray_height: f32,
+    /// Correzione massima, verso l'alto o verso il basso.
+    pub max_adjust: f32,
+    feet: Vec<FootChain>,
+}
+
+impl Default for FootIk {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ray_height: 0.5,
+            max_adjust: 0.4,
+            feet: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LookAtTarget {
+    Cursor,
+    Entity(Entity),
+}
+
+/// Gira testa e occhi verso un bersaglio, sopra l'animazione.
+#[derive(Component)]
+pub struct LookAt {
+    pub target: Option<LookAtTarget>,
+    /// 0 = solo animazione, 1 = la testa segue del tutto il bersaglio.
+    pub weight: f32,
+    /// Rotazione massima della testa in gradi.
+    pub head_limit: f32,
+    /// Rotazione massima degli occhi oltre la testa, in gradi.
+    pub eye_limit: f32,
+    /// Quanto velocemente la testa insegue il bersaglio.
+    pub speed: f32,
+    current: Quat,
+}
+
+impl Default for LookAt {
+    fn default() -> Self {
+        Self {
+            target: Some(LookAtTarget::Cursor),
+            weight: 1.0,
+            head_limit: 60.0,
+            eye_limit: 25.0,
+            speed: 8.0,
+            current: Quat::IDENTITY,
+        }
+    }
+}
+
+/// Rotazione `rotation` limitata a `limit` radianti.
+fn clamp_rotation(rotation: Quat, limit: f32) -> Quat {
+    let (axis, angle) = rotation.to_axis_angle();
+    if angle <= limit {
+        rotation
+    } else {
+        Quat::from_axis_angle(axis, limit)
+    }
+}
+
+/// Ricalcola i `GlobalTransform` dei discendenti di `entity` dopo che il solver
+/// (o il look-at) l'ha spostato dopo la propagazione.
+fn propagate_subtree(
+    entity: Entity,
+    children: &Query<&Children>,
+    transforms: &mut Query<(&Transform, &mut GlobalTransform)>,
+) {
+    let Ok((_, parent)) = transforms.get(entity) else {
+        return;
+    };
+    let parent = *parent;
+    for child in children.get(entity).into_iter().flatten() {
+        if let Ok((transform, mut global)) = transforms.get_mut(*child) {
+            *global = parent.mul_transform(*transform);
+        }
+        propagate_subtree(*child, children, transforms);
+    }
+}
+
+/// Crea vincoli e bersagli appena lo scheletro è stato riconosciuto.
+fn setup_foot_ik(
+    mut commands: Commands,
+    mut characters: Query<(Entity, &mut FootIk, &SkeletonMap)>,
+    parents: Query<&ChildOf>,
+) {
+    for (root, mut foot_ik, skeleton) in characters.iter_mut() {
+        if !foot_ik.feet.is_empty() || !skeleton.is_resolved() {
+            continue;
+        }
+        let legs = [
+            (HumanoidBone::FootLeft, HumanoidBone::LowerLegLeft),
+            (HumanoidBone::FootRight, HumanoidBone::LowerLegRight),
+        ];
+        for (foot, knee) in legs {
+            let (Some(foot), Some(knee)) = (skeleton.bone(foot), skeleton.bone(knee)) else {
+                continue;
+            };
+            // Il solver risale la gerarchia: il piede deve essere figlio diretto dello stinco.
+            if parents.get(foot).ok().map(|p| p.parent()) != Some(knee) {
+                warn!("Foot IK: il piede {:?} non è figlio dello stinco, gamba ignorata", foot);
+                continue;
+            }
+            let target = commands
+                .spawn((Name::new("Foot IK target"), Transform::default(), ChildOf(root)))
+                .id();
+            commands.entity(foot).insert(IkConstraint {
+                chain_length: 2,
+                iterations: 10,
+                target,
+                pole_target: None,
+                pole_angle: 0.0,
+                enabled: false,
+            });
+            foot_ik.feet.push(FootChain { foot, knee, target });
+        }
+    }
+}
+
+/// Dopo la propagazione, prima del solver: raggio sotto ogni piede animato e bersaglio
+/// spostato di quanto il suolo è più alto o più basso dei piedi del personaggio.
+#[allow(clippy::type_complexity)]
+fn update_foot_targets(
+    rapier: ReadRapierContext,
+    characters: Query<(
+        Entity,
+        &FootIk,
+        &GlobalTransform,
+        Has<RagdollActive>,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+    mut globals: Query<&mut GlobalTransform, Without<FootIk>>,
+    mut constraints: Query<&mut IkConstraint>,
+) {
+    let Ok(context) = rapier.single() else {
+        return;
+    };
+    for (root, foot_ik, root_global, ragdoll, output) in characters.iter() {
+        let grounded = output.is_none_or(|o| o.grounded);
+        let active = foot_ik.enabled && grounded && !ragdoll;
+        for chain in &foot_ik.feet {
+            let Ok(mut constraint) = constraints.get_mut(chain.foot) else {
+                continue;
+            };
+            constraint.enabled = false;
+            if !active {
+                continue;
+            }
+            let Ok(foot) = globals.get(chain.foot).map(|g| g.translation()) else {
+                continue;
+            };
+            let origin = foot + Vec3::Y * foot_ik.ray_height;
+            let filter = QueryFilter::default()
+                .exclude_rigid_body(root)
+                .exclude_sensors();
+            let Some((_, distance)) = context.cast_ray(
+                origin,
+                Vec3::NEG_Y,
+                foot_ik.ray_height + foot_ik.max_adjust,
+                true,
+                filter,
+            ) else {
+                continue;
+            };
+            let ground = origin.y - distance;
+            let offset = (ground - root_global.translation().y)
+                .clamp(-foot_ik.max_adjust, foot_ik.max_adjust);
+            if offset.abs() < 0.001 {
+                continue;
+            }
+            // Il solver legge solo la traslazione globale del bersaglio.
+            if let Ok(mut target) = globals.get_mut(chain.target) {
+                *target = GlobalTransform::from_translation(foot + Vec3::Y * offset);
+                constraint.enabled = true;
+            }
+        }
+    }
+}
+
+/// Il solver aggiorna solo la catena: piede e dita vanno ricalcolati.
+fn refresh_foot_chains(
+    characters: Query<&FootIk>,
+    children: Query<&Children>,
+    mut transforms: Query<(&Transform, &mut GlobalTransform)>,
+) {
+    for foot_ik in characters.iter() {
+        for chain in &foot_ik.feet {
+            propagate_subtree(chain.knee, &children, &mut transforms);
+        }
+    }
+}
+
+/// Ruota testa e occhi verso il bersaglio. Tocca solo i `GlobalTransform`, così il frame
+/// dopo si riparte dalla posa animata e la rotazione non si accumula.
+fn apply_look_at(
+    time: Res<Time>,
+    cursor: Res<crate::cursor::Cursor>,
+    mut characters: Query<(Entity, &mut LookAt, &SkeletonMap, Has<RagdollActive>)>,
+    children: Query<&Children>,
+    mut transforms: Query<(&Transform, &mut GlobalTransform)>,
+) {
+    let dt = time.delta_secs();
+    for (root, mut look_at, skeleton, ragdoll) in characters.iter_mut() {
+        let Some(head) = skeleton.bone(HumanoidBone::Head) else {
+            continue;
+        };
+        let target = match look_at.target {
+            Some(LookAtTarget::Cursor) => Some(cursor.cursor_position),
+            Some(LookAtTarget::Entity(entity)) => {
+                transforms.get(entity).ok().map(|(_, g)| g.translation())
+            }
+            None => None,
+        };
+        let Ok((_, head_global)) = transforms.get(head) else {
+            continue;
+        };
+        let head_global = *head_global;
+        // Il personaggio guarda lungo +Z (vedi `move_character`).
+        let forward = transforms
+            .get(root)
+            .map(|(_, g)| g.rotation() * Vec3::Z)
+            .unwrap_or(Vec3::Z);
+
+        let goal = match target {
+            Some(target) if !ragdoll => {
+                let direction = (target - head_global.translation()).normalize_or_zero();
+                if direction == Vec3::ZERO {
+                    Quat::IDENTITY
+                } else {
+                    let full = Quat::from_rotation_arc(forward.normalize(), direction);
+                    Quat::IDENTITY.slerp(
+                        clamp_rotation(full, look_at.head_limit.to_radians()),
+                        look_at.weight,
+                    )
+                }
+            }
+            _ => Quat::IDENTITY,
+        };
+        let t = 1.0 - (-look_at.speed * dt).exp();
+        look_at.current = look_at.current.slerp(goal, t);
+        let current = look_at.current;
+
+        let rotate = |entity: Entity,
+                      rotation: Quat,
+                      transforms: &mut Query<(&Transform, &mut GlobalTransform)>| {
+            if let Ok((_, mut global)) = transforms.get_mut(entity) {
+                let (scale, old, translation) = global.to_scale_rotation_translation();
+                *global = GlobalTransform::from(
+                    Transform::from_translation(translation)
+                        .with_rotation(rotation * old)
+                        .with_scale(scale),
+                );
+            }
+            propagate_subtree(entity, &children, transforms);
+        };
+        rotate(head, current, &mut transforms);
+
+        // Gli occhi coprono quello che la testa non arriva a girare.
+        let Some(target) = target.filter(|_| !ragdoll) else {
+            continue;
+        };
+        for eye in [HumanoidBone::EyeLeft, HumanoidBone::EyeRight] {
+            let Some(eye) = skeleton.bone(eye) else {
+                continue;
+            };
+            let Ok((_, eye_global)) = transforms.get(eye) else {
+                continue;
+            };
+            let direction = (target - eye_global.translation()).normalize_or_zero();
+            if direction == Vec3::ZERO {
+                continue;
+            }
+            let looking = current * forward.normalize();
+            let remaining = Quat::from_rotation_arc(looking, direction);
+            let eye_rotation = Quat::IDENTITY.slerp(
+                clamp_rotation(remaining, look_at.eye_limit.to_radians()),
+                look_at.weight,
+            );
+            rotate(eye, eye_rotation, &mut transforms);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn character_ik_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut characters: Query<(Entity, Option<&mut FootIk>, Option<&mut LookAt>, Option<&Name>)>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let selected = selected.iter().next();
+    let ui = context.ui();
+    ui.window("Character IK")
+        .size([320.0, 250.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            for (entity, foot_ik, look_at, name) in characters.iter_mut() {
+                if foot_ik.is_none() && look_at.is_none() {
+                    continue;
+                }
+                let _id = ui.push_id(format!("{:?}", entity));
+                let label = name.map_or_else(|| format!("{:?}", entity), |n| n.to_string());
+                ui.text(label);
+
+                if let Some(mut foot_ik) = foot_ik {
+                    ui.checkbox("Foot IK", &mut foot_ik.enabled);
+                    ui.slider("Ray height", 0.1, 2.0, &mut foot_ik.ray_height);
+                    ui.slider("Max adjust", 0.0, 1.0, &mut foot_ik.max_adjust);
+                    if foot_ik.feet.is_empty() {
+                        ui.text_colored([0.7, 0.7, 0.7, 1.0], "Waiting for skeleton...");
+                    }
+                }
+
+                if let Some(mut look_at) = look_at {
+                    ui.separator();
+                    ui.text("Look at:");
+                    if ui.radio_button_bool("None", look_at.target.is_none()) {
+                        look_at.target = None;
+                    }
+                    ui.same_line();
+                    if ui.radio_button_bool("Cursor", look_at.target == Some(LookAtTarget::Cursor)) {
+                        look_at.target = Some(LookAtTarget::Cursor);
+                    }
+                    if let Some(LookAtTarget::Entity(target)) = look_at.target {
+                        ui.same_line();
+                        ui.text(format!("{:?}", target));
+                    }
+                    if let Some(selected) = selected.filter(|&s| s != entity) {
+                        if ui.button("Look at selected") {
+                            look_at.target = Some(LookAtTarget::Entity(selected));
+                        }
+                    }
+                    ui.slider("Weight", 0.0, 1.0, &mut look_at.weight);
+                    ui.slider("Head limit", 0.0, 90.0, &mut look_at.head_limit);
+                    ui.slider("Eye limit", 0.0, 45.0, &mut look_at.eye_limit);
+                    ui.slider("Speed", 0.5, 20.0, &mut look_at.speed);
+                }
+                ui.separator();
+            }
+        });
+}
+
+pub struct CharacterIkPlugin;
+impl Plugin for CharacterIkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (setup_foot_ik, character_ik_ui))
+            .add_systems(
+                PostUpdate,
+                (
+                    update_foot_targets.before(inverse_kinematics_system),
+                    refresh_foot_chains.after(inverse_kinematics_system),
+                    apply_look_at.after(refresh_foot_chains),
+                )
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
@@ -1,9 +1,656 @@
-use crate::board;
+use crate::board::{Board, Tile};
+use crate::chess_ai::{self, SearchLimits};
+use crate::chess_rules::{GameOutcome, Move, PieceColor, PieceKind, Position};
+use crate::input::UiFocus;
+use crate::pgn::PgnGame;
+use crate::uci::UciEngine;
+use bevy::picking::prelude::*;
 use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, IoTaskPool, Task};
+use bevy_mod_imgui::prelude::*;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Partita corrente: posizione iniziale, mosse giocate e cursore di replay.
+#[derive(Resource, Clone)]
+pub struct ChessGame {
+    pub start: Position,
+    pub moves: Vec<Move>,
+    /// Number of moves from `moves` applied to the displayed position.
+    pub cursor: usize,
+    pub tags: Vec<(String, String)>,
+}
+
+impl Default for ChessGame {
+    fn default() -> Self {
+        Self::from_position(Position::default())
+    }
+}
+
+impl ChessGame {
+    pub fn from_position(start: Position) -> Self {
+        let tags = PgnGame::new(start.clone()).tags;
+        Self {
+            start,
+            moves: Vec::new(),
+            cursor: 0,
+            tags,
+        }
+    }
+
+    pub fn from_pgn(game: PgnGame) -> Self {
+        Self {
+            cursor: game.moves.len(),
+            start: game.start,
+            moves: game.moves,
+            tags: game.tags,
+        }
+    }
+
+    pub fn to_pgn(&self) -> PgnGame {
+        PgnGame {
+            tags: self.tags.clone(),
+            start: self.start.clone(),
+            moves: self.moves.clone(),
+        }
+    }
+
+    /// Position after `cursor` moves.
+    pub fn position(&self) -> Position {
+        let mut position = self.start.clone();
+        for &mv in &self.moves[..self.cursor] {
+            position.make_move(mv);
+        }
+        position
+    }
+
+    /// Plays a legal move from the displayed position. Moves after the replay
+    /// cursor are discarded, so playing while stepping back starts a new line.
+    pub fn play(&mut self, mv: Move) -> bool {
+        if !self.position().is_legal(mv) {
+            return false;
+        }
+        self.moves.truncate(self.cursor);
+        self.moves.push(mv);
+        self.cursor += 1;
+        true
+    }
+
+    pub fn seek(&mut self, ply: usize) {
+        self.cursor = ply.min(self.moves.len());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpponentKind {
+    /// Both sides are played from the board / UI.
+    None,
+    /// Built-in alpha-beta search.
+    Local,
+    /// External engine spoken to over UCI.
+    Uci,
+}
+
+/// Avversario automatico: chi gioca, con quale motore e con quali limiti.
+#[derive(Resource)]
+pub struct ChessOpponent {
+    pub kind: OpponentKind,
+    pub color: PieceColor,
+    pub limits: SearchLimits,
+    pub engine_path: String,
+    /// Lazily started and reused across searches; shared with the search task.
+    engine: Arc<Mutex<Option<UciEngine>>>,
+    last_error: Option<String>,
+}
+
+impl Default for ChessOpponent {
+    fn default() -> Self {
+        Self {
+            kind: OpponentKind::None,
+            color: PieceColor::Black,
+            limits: SearchLimits::default(),
+            engine_path: "engines/stockfish".to_string(),
+            engine: Arc::new(Mutex::new(None)),
+            last_error: None,
+        }
+    }
+}
+
+/// Ricerca in corso fuori dal main thread, con la posizione per cui è stata lanciata.
+#[derive(Resource)]
+struct OpponentSearch {
+    task: Task<Result<Option<Move>, String>>,
+    kind: OpponentKind,
+    color: PieceColor,
+    ply: usize,
+    fen: String,
+}
+
+/// Marker della board su cui si gioca la partita.
+#[derive(Component)]
+pub struct ChessBoard;
+
+/// Pezzo sulla scacchiera, con l'indice della casa (0 = a1, 63 = h8).
+#[derive(Component)]
+pub struct Piece {
+    pub square: u8,
+}
+
+#[derive(Resource)]
+struct PieceAssets {
+    white: Handle<StandardMaterial>,
+    black: Handle<StandardMaterial>,
+    meshes: [Handle<Mesh>; 6],
+}
+
+impl PieceAssets {
+    fn mesh(&self, kind: PieceKind) -> Handle<Mesh> {
+        let index = match kind {
+            PieceKind::Pawn => 0,
+            PieceKind::Knight => 1,
+            PieceKind::Bishop => 2,
+            PieceKind::Rook => 3,
+            PieceKind::Queen => 4,
+            PieceKind::King => 5,
+        };
+        self.meshes[index].clone()
+    }
+}
+
+fn setup_chess_board(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let white_material = StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Opaque,
+        unlit: true, // Flat pixel art look
+        metallic: 0.0,
+        perceptual_roughness: 1.0,
+        reflectance: 0.0,
+        ..default()
+    };
+    let black_material = StandardMaterial {
+        base_color: Color::BLACK,
+        ..white_material.clone()
+    };
+
+    // a1 (cella 0, 0) è scura.
+    let tiles = vec![
+        materials.add(black_material.clone()),
+        materials.add(white_material.clone()),
+    ];
+    commands.spawn((
+        Name::new("Chess Board"),
+        Board::new(8, 8).with_tile_size(2.0).with_materials(tiles),
+        ChessBoard,
+        Transform::from_xyz(0.0, 0.1, 0.0),
+    ));
+
+    commands.insert_resource(PieceAssets {
+        white: materials.add(white_material),
+        black: materials.add(black_material),
+        meshes: [
+            meshes.add(Sphere::new(0.3)),
+            meshes.add(Cylinder::new(0.4, 0.6)),
+            meshes.add(Cylinder::new(0.3, 0.9)),
+            meshes.add(Cuboid::new(0.6, 0.6, 0.6)),
+            meshes.add(Cone::new(0.4, 1.1)),
+            meshes.add(Capsule3d::new(0.3, 0.8)),
+        ],
+    });
+}
+
+/// Rispawna i pezzi ogni volta che la posizione mostrata cambia (mossa, FEN, replay).
+fn sync_pieces(
+    mut commands: Commands,
+    game: Res<ChessGame>,
+    assets: Option<Res<PieceAssets>>,
+    board: Option<Single<(Entity, &Board), With<ChessBoard>>>,
+    pieces: Query<Entity, With<Piece>>,
+) {
+    let (Some(assets), Some(board)) = (assets, board) else {
+        return;
+    };
+    let (board_entity, board) = *board;
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (square, piece) in game.position().pieces() {
+        let Some(cell) = board.cell(square as usize) else {
+            continue;
+        };
+        let material = match piece.color {
+            PieceColor::White => assets.white.clone(),
+            PieceColor::Black => assets.black.clone(),
+        };
+        commands.spawn((
+            Mesh3d(assets.mesh(piece.kind)),
+            MeshMaterial3d(material),
+            Transform::from_translation(board.cell_to_local(cell) + Vec3::Y * 0.5),
+            Piece { square },
+            ChildOf(board_entity),
+        ));
+    }
+}
+
+#[derive(Resource, Default)]
+struct ChessSelection {
+    from: Option<u8>,
+}
+
+#[derive(Resource)]
+struct ChessUiState {
+    fen_input: String,
+    pgn_path: String,
+    move_input: String,
+    status: String,
+}
+
+impl Default for ChessUiState {
+    fn default() -> Self {
+        Self {
+            fen_input: Position::default().to_fen(),
+            pgn_path: "games/game.pgn".to_string(),
+            move_input: String::new(),
+            status: String::new(),
+        }
+    }
+}
+
+/// Click su un tile o su un pezzo: il primo click sceglie la casa di partenza, il secondo muove.
+fn handle_board_clicks(
+    mut click_events: EventReader<Pointer<Click>>,
+    focus: Res<UiFocus>,
+    tiles: Query<(&Tile, &ChildOf)>,
+    chess_boards: Query<&Board, With<ChessBoard>>,
+    pieces: Query<&Piece>,
+    mut selection: ResMut<ChessSelection>,
+    mut game: ResMut<ChessGame>,
+) {
+    // Un click su un pannello imgui non muove i pezzi sotto.
+    if focus.mouse {
+        click_events.clear();
+        return;
+    }
+    for click in click_events.read() {
+        if click.button != PointerButton::Primary {
+            continue;
+        }
+        let square = if let Ok((tile, child_of)) = tiles.get(click.target) {
+            // Solo i tile della scacchiera, non quelli di altre board.
+            let Ok(board) = chess_boards.get(child_of.parent()) else {
+                continue;
+            };
+            let Some(index) = board.index(tile.cell) else {
+                continue;
+            };
+            index as u8
+        } else if let Ok(piece) = pieces.get(click.target) {
+            piece.square
+        } else {
+            continue;
+        };
+
+        let position = game.position();
+        let own_piece = position
+            .piece_at(square)
+            .is_some_and(|p| p.color == position.side_to_move);
+
+        match selection.from {
+            Some(from) if from != square && !own_piece => {
+                let mut mv = Move::new(from, square);
+                let is_pawn = position.piece_at(from).map(|p| p.kind) == Some(PieceKind::Pawn);
+                if is_pawn && (square / 8 == 0 || square / 8 == 7) {
+                    mv.promotion = Some(PieceKind::Queen);
+                }
+                if game.play(mv) {
+                    info!("Mossa: {}", mv.to_uci());
+                }
+                selection.from = None;
+            }
+            _ if own_piece => selection.from = Some(square),
+            _ => selection.from = None,
+        }
+    }
+}
+
+fn start_opponent_search(
+    mut commands: Commands,
+    game: Res<ChessGame>,
+    opponent: Res<ChessOpponent>,
+    pending: Option<Res<OpponentSearch>>,
+) {
+    if opponent.kind == OpponentKind::None || pending.is_some() {
+        return;
+    }
+    // Only answer at the end of the game line, not while stepping through a replay.
+    if game.cursor != game.moves.len() {
+        return;
+    }
+    let position = game.position();
+    if position.side_to_move != opponent.color || position.outcome().is_some() {
+        return;
+    }
+
+    let limits = opponent.limits;
+    let task = match opponent.kind {
+        OpponentKind::Local => AsyncComputeTaskPool::get().spawn({
+            let position = position.clone();
+            async move { Ok(chess_ai::search(&position, limits)) }
+        }),
+        // L'engine esterno blocca sul suo IO (con timeout): va nel pool IO, non in quello di calcolo.
+        OpponentKind::Uci => IoTaskPool::get().spawn({
+            let engine = opponent.engine.clone();
+            let path = PathBuf::from(&opponent.engine_path);
+            let start = game.start.clone();
+            let moves = game.moves.clone();
+            async move {
+                let mut engine = engine.lock().map_err(|e| e.to_string())?;
+                // A path edited in the UI takes effect here, once no search holds the engine.
+                if engine.as_ref().is_some_and(|e| e.path != path) {
+                    *engine = None;
+                }
+                if engine.is_none() {
+                    *engine = Some(UciEngine::spawn(&path).map_err(|e| e.to_string())?);
+                }
+                let result = engine
+                    .as_mut()
+                    .map(|e| e.best_move(&start, &moves, limits))
+                    .unwrap_or(Ok(None));
+                if result.is_err() {
+                    // Engine died: restart it on the next request.
+                    *engine = None;
+                }
+                result.map_err(|e| e.to_string())
+            }
+        }),
+        OpponentKind::None => return,
+    };
+
+    commands.insert_resource(OpponentSearch {
+        task,
+        kind: opponent.kind,
+        color: opponent.color,
+        ply: game.moves.len(),
+        fen: position.to_fen(),
+    });
+}
+
+fn poll_opponent_search(
+    mut commands: Commands,
+    mut search: ResMut<OpponentSearch>,
+    mut game: ResMut<ChessGame>,
+    mut opponent: ResMut<ChessOpponent>,
+) {
+    let Some(result) = block_on(poll_once(&mut search.task)) else {
+        return;
+    };
+    commands.remove_resource::<OpponentSearch>();
+
+    // The opponent may have been switched off or changed while the search ran.
+    if opponent.kind != search.kind || opponent.color != search.color {
+        return;
+    }
+    // The board may have changed while the search ran (new FEN, undo, ...).
+    let unchanged = game.moves.len() == search.ply
+        && game.cursor == search.ply
+        && game.position().to_fen() == search.fen;
+    match result {
+        Ok(Some(mv)) if unchanged => {
+            if game.play(mv) {
+                info!("Avversario: {}", mv.to_uci());
+                opponent.last_error = None;
+            } else {
+                opponent.last_error = Some(format!("engine played illegal move {}", mv.to_uci()));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!("Chess engine error: {e}");
+            opponent.last_error = Some(e);
+            opponent.kind = OpponentKind::None;
+        }
+    }
+}
+
+fn draw_chess_selection(
+    mut gizmos: Gizmos,
+    selection: Res<ChessSelection>,
+    game: Res<ChessGame>,
+    board: Option<Single<(&Board, &GlobalTransform), With<ChessBoard>>>,
+) {
+    let (Some(from), Some(board)) = (selection.from, board) else {
+        return;
+    };
+    let (board, transform) = *board;
+    let Some(from_cell) = board.cell(from as usize) else {
+        return;
+    };
+    let size = Vec2::splat(board.tile_size * 0.9);
+    let rotation = transform.rotation() * Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+    let lift = transform.up() * 0.02;
+    gizmos.rect(
+        Isometry3d::new(board.cell_to_world(transform, from_cell) + lift, rotation),
+        size,
+        Color::srgb(1.0, 1.0, 0.0),
+    );
+    let targets = game.position().legal_moves().into_iter().filter(|m| m.from == from);
+    for cell in targets.filter_map(|mv| board.cell(mv.to as usize)) {
+        gizmos.rect(
+            Isometry3d::new(board.cell_to_world(transform, cell) + lift, rotation),
+            size * 0.5,
+            Color::srgb(0.0, 1.0, 0.0),
+        );
+    }
+}
+
+fn chess_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut game: ResMut<ChessGame>,
+    mut ui_state: ResMut<ChessUiState>,
+    mut selection: ResMut<ChessSelection>,
+    mut opponent: ResMut<ChessOpponent>,
+    searching: Option<Res<OpponentSearch>>,
+) {
+    let ui_state = &mut *ui_state;
+    let opponent = &mut *opponent;
+    let ui = context.ui();
+    let window = ui.window("Chess");
+    window
+        .size([360.0, 560.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let position = game.position();
+
+            let side = match position.side_to_move {
+                PieceColor::White => "White",
+                PieceColor::Black => "Black",
+            };
+            match position.outcome() {
+                Some(GameOutcome::Checkmate { winner }) => {
+                    ui.text_colored([1.0, 0.3, 0.3, 1.0], format!("Checkmate, {:?} wins", winner))
+                }
+                Some(outcome) => ui.text_colored([1.0, 0.8, 0.3, 1.0], format!("Draw: {:?}", outcome)),
+                None if position.in_check() => ui.text(format!("{} to move (check)", side)),
+                None => ui.text(format!("{} to move", side)),
+            }
+
+            if ui.collapsing_header("FEN", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                ui.input_text("##fen", &mut ui_state.fen_input).build();
+                if ui.button("Load FEN") {
+                    match Position::from_fen(&ui_state.fen_input) {
+                        Ok(start) => {
+                            *game = ChessGame::from_position(start);
+                            selection.from = None;
+                            ui_state.status = "FEN loaded".to_string();
+                        }
+                        Err(e) => ui_state.status = format!("FEN error: {}", e),
+                    }
+                }
+                ui.same_line();
+                if ui.button("Export FEN") {
+                    ui_state.fen_input = position.to_fen();
+                    ui.set_clipboard_text(&ui_state.fen_input);
+                    ui_state.status = "FEN copied to clipboard".to_string();
+                }
+                ui.same_line();
+                if ui.button("New Game") {
+                    *game = ChessGame::default();
+                    selection.from = None;
+                    ui_state.fen_input = game.start.to_fen();
+                }
+            }
+
+            if ui.collapsing_header("PGN", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                ui.input_text("Path", &mut ui_state.pgn_path).build();
+                if ui.button("Save PGN") {
+                    let path = std::path::Path::new(&ui_state.pgn_path);
+                    if let Some(parent) = path.parent() {
+                        let _ = std::fs::create_dir_all(parent);
+                    }
+                    ui_state.status = match std::fs::write(path, game.to_pgn().to_pgn()) {
+                        Ok(()) => format!("Saved {}", ui_state.pgn_path),
+                        Err(e) => format!("Save failed: {}", e),
+                    };
+                }
+                ui.same_line();
+                if ui.button("Load PGN") {
+                    ui_state.status = match std::fs::read_to_string(&ui_state.pgn_path) {
+                        Ok(text) => match PgnGame::parse(&text) {
+                            Ok(pgn) => {
+                                *game = ChessGame::from_pgn(pgn);
+                                selection.from = None;
+                                format!("Loaded {} moves", game.moves.len())
+                            }
+                            Err(e) => format!("PGN error: {}", e),
+                        },
+                        Err(e) => format!("Load failed: {}", e),
+                    };
+                }
+            }
+
+            if ui.collapsing_header("Opponent", imgui::TreeNodeFlags::empty()) {
+                ui.radio_button("Off", &mut opponent.kind, OpponentKind::None);
+                ui.same_line();
+                ui.radio_button("Built-in", &mut opponent.kind, OpponentKind::Local);
+                ui.same_line();
+                ui.radio_button("UCI engine", &mut opponent.kind, OpponentKind::Uci);
+
+                ui.radio_button("Plays White", &mut opponent.color, PieceColor::White);
+                ui.same_line();
+                ui.radio_button("Plays Black", &mut opponent.color, PieceColor::Black);
+
+                let mut depth = opponent.limits.depth as i32;
+                if ui.slider("Depth", 1, 12, &mut depth) {
+                    opponent.limits.depth = depth as u32;
+                }
+                let mut millis = opponent.limits.time.as_millis() as i32;
+                if ui.slider("Time (ms)", 100, 10000, &mut millis) {
+                    opponent.limits.time = Duration::from_millis(millis as u64);
+                }
+
+                // Il nuovo percorso vale dalla prossima ricerca: l'engine vecchio viene chiuso lì.
+                ui.input_text("Engine path", &mut opponent.engine_path).build();
+
+                if searching.is_some() {
+                    ui.text_colored([0.6, 0.8, 1.0, 1.0], "Thinking...");
+                }
+                if let Some(error) = &opponent.last_error {
+                    ui.text_colored([1.0, 0.3, 0.3, 1.0], error);
+                }
+            }
+
+            ui.separator();
+            ui.input_text("Move", &mut ui_state.move_input).build();
+            ui.same_line();
+            if ui.button("Play") {
+                match position.parse_san(&ui_state.move_input) {
+                    Some(mv) if game.play(mv) => ui_state.move_input.clear(),
+                    _ => ui_state.status = format!("Illegal move: {}", ui_state.move_input),
+                }
+            }
+
+            ui.separator();
+            // Replay
+            if ui.button("|<") {
+                game.seek(0);
+            }
+            ui.same_line();
+            if ui.button("<") && game.cursor > 0 {
+                let ply = game.cursor - 1;
+                game.seek(ply);
+            }
+            ui.same_line();
+            if ui.button(">") {
+                let ply = game.cursor + 1;
+                game.seek(ply);
+            }
+            ui.same_line();
+            if ui.button(">|") {
+                let ply = game.moves.len();
+                game.seek(ply);
+            }
+            ui.same_line();
+            ui.text(format!("{}/{}", game.cursor, game.moves.len()));
+
+            // Lista mosse: click su una mossa per saltare a quella posizione
+            let mut replay = game.start.clone();
+            let mut seek_to = None;
+            for (i, &mv) in game.moves.iter().enumerate() {
+                let san = replay.to_san(mv);
+                if replay.side_to_move == PieceColor::White || i == 0 {
+                    let dots = if replay.side_to_move == PieceColor::White { "." } else { "..." };
+                    ui.text(format!("{}{}", replay.fullmove_number, dots));
+                    ui.same_line();
+                }
+                let label = format!("{}##ply{}", san, i);
+                if ui
+                    .selectable_config(&label)
+                    .selected(game.cursor == i + 1)
+                    .size([60.0, 0.0])
+                    .build()
+                {
+                    seek_to = Some(i + 1);
+                }
+                if replay.side_to_move == PieceColor::White {
+                    ui.same_line();
+                }
+                replay.make_move(mv);
+            }
+            if !game.moves.is_empty() && replay.side_to_move == PieceColor::Black {
+                ui.new_line();
+            }
+            if let Some(ply) = seek_to {
+                game.seek(ply);
+            }
+
+            if !ui_state.status.is_empty() {
+                ui.separator();
+                ui.text_wrapped(&ui_state.status);
+            }
+        });
+}
 
 pub struct ChessPlugin;
 impl Plugin for ChessPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(board::BoardPlugin);
+        app.init_resource::<ChessGame>()
+            .init_resource::<ChessSelection>()
+            .init_resource::<ChessUiState>()
+            .init_resource::<ChessOpponent>()
+            .add_systems(Startup, setup_chess_board)
+            .add_systems(Update, sync_pieces.run_if(resource_changed::<ChessGame>))
+            .add_systems(Update, (handle_board_clicks, draw_chess_selection, chess_ui))
+            .add_systems(
+                Update,
+                (
+                    start_opponent_search,
+                    poll_opponent_search.run_if(resource_exists::<OpponentSearch>),
+                )
+                    .chain(),
+            );
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,303 @@
+use crate::chess_rules::{Move, PieceColor, PieceKind, Position};
+use std::time::{Duration, Instant};
+
+const MATE_SCORE: i32 = 100_000;
+const INFINITY: i32 = 1_000_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    /// Maximum search depth in plies.
+    pub depth: u32,
+    /// Time budget; the last fully searched depth is used when it runs out.
+    pub time: Duration,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            time: Duration::from_millis(1500),
+        }
+    }
+}
+
+fn piece_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 100,
+        PieceKind::Knight => 320,
+        PieceKind::Bishop => 330,
+        PieceKind::Rook => 500,
+        PieceKind::Queen => 900,
+        PieceKind::King => 0,
+    }
+}
+
+// Tabelle posizionali dal punto di vista del bianco, a1 = indice 0.
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5,  5, 10, 25, 25, 10,  5,  5,
+    10, 10, 20, 30, 30, 20, 10, 10,
+    50, 50, 50, 50, 50, 50, 50, 50,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  5,  5,  0,  0,  0,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     5, 10, 10, 10, 10, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+     20, 30, 10,  0,  0, 10, 30, 20,
+     20, 20,  0,  0,  0,  0, 20, 20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+];
+
+fn square_bonus(kind: PieceKind, color: PieceColor, square: u8) -> i32 {
+    // Le tabelle sono per il bianco: per il nero si specchia la traversa.
+    let index = match color {
+        PieceColor::White => square as usize,
+        PieceColor::Black => ((7 - square / 8) * 8 + square % 8) as usize,
+    };
+    match kind {
+        PieceKind::Pawn => PAWN_TABLE[index],
+        PieceKind::Knight => KNIGHT_TABLE[index],
+        PieceKind::Bishop => BISHOP_TABLE[index],
+        PieceKind::Rook => ROOK_TABLE[index],
+        PieceKind::Queen => 0,
+        PieceKind::King => KING_TABLE[index],
+    }
+}
+
+/// Material plus piece-square score, from the side to move's point of view.
+pub fn evaluate(position: &Position) -> i32 {
+    let mut score = 0;
+    for (square, piece) in position.pieces() {
+        let value = piece_value(piece.kind) + square_bonus(piece.kind, piece.color, square);
+        if piece.color == position.side_to_move {
+            score += value;
+        } else {
+            score -= value;
+        }
+    }
+    score
+}
+
+struct Searcher {
+    deadline: Instant,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl Searcher {
+    fn out_of_time(&mut self) -> bool {
+        // Il clock si controlla ogni tanto, non ad ogni nodo.
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(1024) && Instant::now() >= self.deadline {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    fn negamax(&mut self, position: &Position, depth: u32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        if self.out_of_time() {
+            return 0;
+        }
+        if position.halfmove_clock >= 100 {
+            return 0;
+        }
+
+        let mut moves = position.legal_moves();
+        if moves.is_empty() {
+            return if position.in_check() {
+                -MATE_SCORE + ply
+            } else {
+                0
+            };
+        }
+        if depth == 0 {
+            return self.quiescence(position, alpha, beta);
+        }
+
+        order_moves(position, &mut moves, None);
+        for mv in moves {
+            let mut next = position.clone();
+            next.make_move(mv);
+            let score = -self.negamax(&next, depth - 1, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// Continues searching captures only, so the evaluation isn't taken in the middle of an exchange.
+    fn quiescence(&mut self, position: &Position, mut alpha: i32, beta: i32) -> i32 {
+        if self.out_of_time() {
+            return 0;
+        }
+        let stand_pat = evaluate(position);
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut captures: Vec<Move> = position
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| position.piece_at(mv.to).is_some() || mv.promotion.is_some())
+            .collect();
+        order_moves(position, &mut captures, None);
+        for mv in captures {
+            let mut next = position.clone();
+            next.make_move(mv);
+            let score = -self.quiescence(&next, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+/// MVV-LVA: prima le catture di pezzi pesanti con pezzi leggeri, poi le promozioni.
+fn order_moves(position: &Position, moves: &mut [Move], first: Option<Move>) {
+    moves.sort_by_key(|&mv| {
+        if Some(mv) == first {
+            return i32::MIN;
+        }
+        let victim = position.piece_at(mv.to).map_or(0, |p| piece_value(p.kind));
+        let attacker = position.piece_at(mv.from).map_or(0, |p| piece_value(p.kind));
+        let promotion = mv.promotion.map_or(0, piece_value);
+        let mut key = -(promotion);
+        if victim > 0 {
+            key -= victim * 10 - attacker;
+        }
+        key
+    });
+}
+
+/// Iterative deepening alpha-beta search. Returns `None` only if there are no legal moves.
+pub fn search(position: &Position, limits: SearchLimits) -> Option<Move> {
+    let mut root_moves = position.legal_moves();
+    if root_moves.len() <= 1 {
+        return root_moves.pop();
+    }
+
+    let mut searcher = Searcher {
+        deadline: Instant::now() + limits.time,
+        nodes: 0,
+        aborted: false,
+    };
+    let mut best = None;
+
+    for depth in 1..=limits.depth.max(1) {
+        order_moves(position, &mut root_moves, best);
+        let mut alpha = -INFINITY;
+        let mut depth_best = None;
+        for &mv in &root_moves {
+            let mut next = position.clone();
+            next.make_move(mv);
+            let score = -searcher.negamax(&next, depth - 1, 1, -INFINITY, -alpha);
+            if searcher.aborted {
+                break;
+            }
+            if score > alpha || depth_best.is_none() {
+                alpha = score;
+                depth_best = Some(mv);
+            }
+        }
+        if searcher.aborted {
+            break;
+        }
+        best = depth_best;
+        // Mate found, deeper search won't change the move.
+        if alpha.abs() >= MATE_SCORE - 100 {
+            break;
+        }
+    }
+
+    best.or_else(|| root_moves.first().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best(fen: &str, depth: u32) -> Option<String> {
+        let position = Position::from_fen(fen).unwrap();
+        let limits = SearchLimits {
+            depth,
+            time: Duration::from_secs(30),
+        };
+        search(&position, limits).map(Move::to_uci)
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        assert_eq!(best("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3).as_deref(), Some("a1a8"));
+        assert_eq!(best("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1", 3).as_deref(), Some("a8a1"));
+    }
+
+    #[test]
+    fn takes_hanging_piece() {
+        assert_eq!(best("4k3/8/8/3q4/8/8/3Q4/4K3 w - - 0 1", 2).as_deref(), Some("d2d5"));
+        assert_eq!(best("4k3/8/8/2n5/8/3R4/8/4K3 b - - 0 1", 2).as_deref(), Some("c5d3"));
+    }
+
+    #[test]
+    fn no_move_when_game_is_over() {
+        // Matto e stallo.
+        assert_eq!(best("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", 3), None);
+        assert_eq!(best("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3), None);
+    }
+
+    #[test]
+    fn single_legal_move_is_returned() {
+        assert_eq!(best("7k/8/8/6Q1/8/8/8/K7 b - - 0 1", 3).as_deref(), Some("h8h7"));
+    }
+}
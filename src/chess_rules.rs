@@ -0,0 +1,900 @@
+use std::fmt;
+
+/// Standard starting position.
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const PROMOTIONS: [PieceKind; 4] = [
+    PieceKind::Queen,
+    PieceKind::Rook,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceColor {
+    White,
+    Black,
+}
+
+impl PieceColor {
+    pub fn opposite(self) -> Self {
+        match self {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceKind {
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            'p' => Some(PieceKind::Pawn),
+            'n' => Some(PieceKind::Knight),
+            'b' => Some(PieceKind::Bishop),
+            'r' => Some(PieceKind::Rook),
+            'q' => Some(PieceKind::Queen),
+            'k' => Some(PieceKind::King),
+            _ => None,
+        }
+    }
+
+    /// Lowercase letter used by FEN and UCI.
+    pub fn to_char(self) -> char {
+        match self {
+            PieceKind::Pawn => 'p',
+            PieceKind::Knight => 'n',
+            PieceKind::Bishop => 'b',
+            PieceKind::Rook => 'r',
+            PieceKind::Queen => 'q',
+            PieceKind::King => 'k',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChessPiece {
+    pub kind: PieceKind,
+    pub color: PieceColor,
+}
+
+impl ChessPiece {
+    pub fn new(kind: PieceKind, color: PieceColor) -> Self {
+        Self { kind, color }
+    }
+
+    fn from_fen_char(c: char) -> Option<Self> {
+        let kind = PieceKind::from_char(c)?;
+        let color = if c.is_ascii_uppercase() {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        };
+        Some(Self { kind, color })
+    }
+
+    fn fen_char(self) -> char {
+        match self.color {
+            PieceColor::White => self.kind.to_char().to_ascii_uppercase(),
+            PieceColor::Black => self.kind.to_char(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+/// A move between two squares (0 = a1, 63 = h8).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: u8,
+    pub to: u8,
+    pub promotion: Option<PieceKind>,
+}
+
+impl Move {
+    pub fn new(from: u8, to: u8) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    /// Long algebraic notation as used by UCI, e.g. `e2e4` or `e7e8q`.
+    pub fn to_uci(self) -> String {
+        let mut s = format!("{}{}", square_name(self.from), square_name(self.to));
+        if let Some(kind) = self.promotion {
+            s.push(kind.to_char());
+        }
+        s
+    }
+
+    pub fn from_uci(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if !(4..=5).contains(&text.len()) || !text.is_ascii() {
+            return None;
+        }
+        let from = parse_square(&text[0..2])?;
+        let to = parse_square(&text[2..4])?;
+        let promotion = match text[4..].chars().next() {
+            Some(c) => Some(PieceKind::from_char(c)?),
+            None => None,
+        };
+        Some(Self {
+            from,
+            to,
+            promotion,
+        })
+    }
+}
+
+pub fn square_name(square: u8) -> String {
+    let file = (b'a' + square % 8) as char;
+    let rank = (b'1' + square / 8) as char;
+    format!("{}{}", file, rank)
+}
+
+pub fn parse_square(text: &str) -> Option<u8> {
+    let bytes = text.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let file = bytes[0].checked_sub(b'a')?;
+    let rank = bytes[1].checked_sub(b'1')?;
+    if file < 8 && rank < 8 {
+        Some(rank * 8 + file)
+    } else {
+        None
+    }
+}
+
+fn offset(square: u8, df: i8, dr: i8) -> Option<u8> {
+    let file = (square % 8) as i8 + df;
+    let rank = (square / 8) as i8 + dr;
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some((rank * 8 + file) as u8)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingFields(usize),
+    Placement(String),
+    SideToMove(String),
+    Castling(String),
+    EnPassant(String),
+    Clock(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::MissingFields(n) => write!(f, "expected at least 4 FEN fields, found {}", n),
+            FenError::Placement(s) => write!(f, "invalid piece placement: {}", s),
+            FenError::SideToMove(s) => write!(f, "invalid side to move: {}", s),
+            FenError::Castling(s) => write!(f, "invalid castling rights: {}", s),
+            FenError::EnPassant(s) => write!(f, "invalid en passant square: {}", s),
+            FenError::Clock(s) => write!(f, "invalid move clock: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+    Checkmate { winner: PieceColor },
+    Stalemate,
+    FiftyMoveRule,
+    InsufficientMaterial,
+}
+
+/// Full chess position: piece placement plus the state FEN carries around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    squares: [Option<ChessPiece>; 64],
+    pub side_to_move: PieceColor,
+    pub castling: CastlingRights,
+    pub en_passant: Option<u8>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::from_fen(START_FEN).expect("start position FEN is valid")
+    }
+}
+
+impl Position {
+    pub fn empty() -> Self {
+        Self {
+            squares: [None; 64],
+            side_to_move: PieceColor::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    pub fn piece_at(&self, square: u8) -> Option<ChessPiece> {
+        self.squares[square as usize]
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = (u8, ChessPiece)> + '_ {
+        self.squares
+            .iter()
+            .enumerate()
+            .filter_map(|(sq, p)| p.map(|p| (sq as u8, p)))
+    }
+
+    // =======================================
+    // FEN
+    // =======================================
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(FenError::MissingFields(fields.len()));
+        }
+
+        let mut position = Self::empty();
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::Placement(format!(
+                "expected 8 ranks, found {}",
+                ranks.len()
+            )));
+        }
+        for (i, rank_text) in ranks.iter().enumerate() {
+            let rank = 7 - i as u8;
+            let mut file: u8 = 0;
+            let mut after_digit = false;
+            for c in rank_text.chars() {
+                if let Some(skip) = c.to_digit(10) {
+                    if after_digit {
+                        return Err(FenError::Placement(format!(
+                            "rank {} has consecutive digits",
+                            rank + 1
+                        )));
+                    }
+                    after_digit = true;
+                    file = file
+                        .checked_add(skip as u8)
+                        .filter(|&f| skip > 0 && f <= 8)
+                        .ok_or_else(|| {
+                            FenError::Placement(format!("rank {} is too long", rank + 1))
+                        })?;
+                } else if let Some(piece) = ChessPiece::from_fen_char(c) {
+                    after_digit = false;
+                    if file >= 8 {
+                        return Err(FenError::Placement(format!("rank {} is too long", rank + 1)));
+                    }
+                    position.squares[(rank * 8 + file) as usize] = Some(piece);
+                    file += 1;
+                } else {
+                    return Err(FenError::Placement(format!("unexpected '{}'", c)));
+                }
+            }
+            if file != 8 {
+                return Err(FenError::Placement(format!(
+                    "rank {} has {} squares",
+                    rank + 1,
+                    file
+                )));
+            }
+        }
+
+        position.side_to_move = match fields[1] {
+            "w" => PieceColor::White,
+            "b" => PieceColor::Black,
+            other => return Err(FenError::SideToMove(other.to_string())),
+        };
+
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                match c {
+                    'K' => position.castling.white_king_side = true,
+                    'Q' => position.castling.white_queen_side = true,
+                    'k' => position.castling.black_king_side = true,
+                    'q' => position.castling.black_queen_side = true,
+                    _ => return Err(FenError::Castling(fields[2].to_string())),
+                }
+            }
+        }
+
+        position.en_passant = match fields[3] {
+            "-" => None,
+            text => Some(parse_square(text).ok_or_else(|| FenError::EnPassant(text.to_string()))?),
+        };
+
+        if let Some(text) = fields.get(4) {
+            position.halfmove_clock = text
+                .parse()
+                .map_err(|_| FenError::Clock(text.to_string()))?;
+        }
+        if let Some(text) = fields.get(5) {
+            position.fullmove_number = text
+                .parse()
+                .map_err(|_| FenError::Clock(text.to_string()))?;
+        }
+
+        Ok(position)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.squares[rank * 8 + file] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.fen_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push(' ');
+        fen.push(match self.side_to_move {
+            PieceColor::White => 'w',
+            PieceColor::Black => 'b',
+        });
+
+        fen.push(' ');
+        let start = fen.len();
+        if self.castling.white_king_side {
+            fen.push('K');
+        }
+        if self.castling.white_queen_side {
+            fen.push('Q');
+        }
+        if self.castling.black_king_side {
+            fen.push('k');
+        }
+        if self.castling.black_queen_side {
+            fen.push('q');
+        }
+        if fen.len() == start {
+            fen.push('-');
+        }
+
+        fen.push(' ');
+        match self.en_passant {
+            Some(sq) => fen.push_str(&square_name(sq)),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(" {} {}", self.halfmove_clock, self.fullmove_number));
+        fen
+    }
+
+    // =======================================
+    // Rules
+    // =======================================
+
+    pub fn king_square(&self, color: PieceColor) -> Option<u8> {
+        self.pieces()
+            .find(|(_, p)| p.kind == PieceKind::King && p.color == color)
+            .map(|(sq, _)| sq)
+    }
+
+    pub fn is_square_attacked(&self, square: u8, by: PieceColor) -> bool {
+        let is = |sq: Option<u8>, kinds: &[PieceKind]| {
+            sq.and_then(|sq| self.piece_at(sq))
+                .is_some_and(|p| p.color == by && kinds.contains(&p.kind))
+        };
+
+        // Pawns attack diagonally forward, so look backwards from the target.
+        let pawn_dr = match by {
+            PieceColor::White => -1,
+            PieceColor::Black => 1,
+        };
+        if is(offset(square, -1, pawn_dr), &[PieceKind::Pawn])
+            || is(offset(square, 1, pawn_dr), &[PieceKind::Pawn])
+        {
+            return true;
+        }
+
+        if KNIGHT_STEPS
+            .iter()
+            .any(|&(df, dr)| is(offset(square, df, dr), &[PieceKind::Knight]))
+        {
+            return true;
+        }
+        if KING_STEPS
+            .iter()
+            .any(|&(df, dr)| is(offset(square, df, dr), &[PieceKind::King]))
+        {
+            return true;
+        }
+
+        let slides = |dirs: &[(i8, i8)], kinds: &[PieceKind]| {
+            dirs.iter().any(|&(df, dr)| {
+                let mut current = square;
+                while let Some(next) = offset(current, df, dr) {
+                    if let Some(piece) = self.piece_at(next) {
+                        return piece.color == by && kinds.contains(&piece.kind);
+                    }
+                    current = next;
+                }
+                false
+            })
+        };
+        slides(&BISHOP_DIRS, &[PieceKind::Bishop, PieceKind::Queen])
+            || slides(&ROOK_DIRS, &[PieceKind::Rook, PieceKind::Queen])
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.king_square(self.side_to_move)
+            .is_some_and(|king| self.is_square_attacked(king, self.side_to_move.opposite()))
+    }
+
+    /// Moves that obey piece movement rules but may leave the own king in check.
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let us = self.side_to_move;
+        let mut moves = Vec::with_capacity(48);
+
+        for (from, piece) in self.pieces().filter(|(_, p)| p.color == us) {
+            match piece.kind {
+                PieceKind::Pawn => self.pawn_moves(from, us, &mut moves),
+                PieceKind::Knight => self.step_moves(from, us, &KNIGHT_STEPS, &mut moves),
+                PieceKind::King => {
+                    self.step_moves(from, us, &KING_STEPS, &mut moves);
+                    self.castling_moves(from, us, &mut moves);
+                }
+                PieceKind::Bishop => self.slide_moves(from, us, &BISHOP_DIRS, &mut moves),
+                PieceKind::Rook => self.slide_moves(from, us, &ROOK_DIRS, &mut moves),
+                PieceKind::Queen => {
+                    self.slide_moves(from, us, &BISHOP_DIRS, &mut moves);
+                    self.slide_moves(from, us, &ROOK_DIRS, &mut moves);
+                }
+            }
+        }
+
+        moves
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let us = self.side_to_move;
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|&mv| {
+                let mut next = self.clone();
+                next.make_move(mv);
+                next.king_square(us)
+                    .is_none_or(|king| !next.is_square_attacked(king, us.opposite()))
+            })
+            .collect()
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.legal_moves().contains(&mv)
+    }
+
+    fn pawn_moves(&self, from: u8, us: PieceColor, moves: &mut Vec<Move>) {
+        let (dr, start_rank, last_rank) = match us {
+            PieceColor::White => (1, 1, 7),
+            PieceColor::Black => (-1, 6, 0),
+        };
+        let mut push = |to: u8| {
+            if to / 8 == last_rank {
+                for kind in PROMOTIONS {
+                    moves.push(Move {
+                        from,
+                        to,
+                        promotion: Some(kind),
+                    });
+                }
+            } else {
+                moves.push(Move::new(from, to));
+            }
+        };
+
+        if let Some(one) = offset(from, 0, dr) {
+            if self.piece_at(one).is_none() {
+                push(one);
+                if from / 8 == start_rank {
+                    if let Some(two) = offset(from, 0, dr * 2) {
+                        if self.piece_at(two).is_none() {
+                            push(two);
+                        }
+                    }
+                }
+            }
+        }
+
+        for df in [-1, 1] {
+            if let Some(to) = offset(from, df, dr) {
+                let enemy = self.piece_at(to).is_some_and(|p| p.color != us);
+                if enemy || self.en_passant == Some(to) {
+                    push(to);
+                }
+            }
+        }
+    }
+
+    fn step_moves(&self, from: u8, us: PieceColor, steps: &[(i8, i8)], moves: &mut Vec<Move>) {
+        for &(df, dr) in steps {
+            if let Some(to) = offset(from, df, dr) {
+                if self.piece_at(to).is_none_or(|p| p.color != us) {
+                    moves.push(Move::new(from, to));
+                }
+            }
+        }
+    }
+
+    fn slide_moves(&self, from: u8, us: PieceColor, dirs: &[(i8, i8)], moves: &mut Vec<Move>) {
+        for &(df, dr) in dirs {
+            let mut current = from;
+            while let Some(to) = offset(current, df, dr) {
+                match self.piece_at(to) {
+                    None => moves.push(Move::new(from, to)),
+                    Some(p) => {
+                        if p.color != us {
+                            moves.push(Move::new(from, to));
+                        }
+                        break;
+                    }
+                }
+                current = to;
+            }
+        }
+    }
+
+    fn castling_moves(&self, from: u8, us: PieceColor, moves: &mut Vec<Move>) {
+        let (home, king_side, queen_side) = match us {
+            PieceColor::White => (4, self.castling.white_king_side, self.castling.white_queen_side),
+            PieceColor::Black => (60, self.castling.black_king_side, self.castling.black_queen_side),
+        };
+        if from != home || self.is_square_attacked(home, us.opposite()) {
+            return;
+        }
+        let rook = Some(ChessPiece::new(PieceKind::Rook, us));
+        let empty = |squares: &[u8]| squares.iter().all(|&sq| self.piece_at(sq).is_none());
+        let safe = |squares: &[u8]| {
+            squares
+                .iter()
+                .all(|&sq| !self.is_square_attacked(sq, us.opposite()))
+        };
+
+        if king_side
+            && self.piece_at(home + 3) == rook
+            && empty(&[home + 1, home + 2])
+            && safe(&[home + 1, home + 2])
+        {
+            moves.push(Move::new(home, home + 2));
+        }
+        if queen_side
+            && self.piece_at(home - 4) == rook
+            && empty(&[home - 1, home - 2, home - 3])
+            && safe(&[home - 1, home - 2])
+        {
+            moves.push(Move::new(home, home - 2));
+        }
+    }
+
+    /// Applies a move without checking legality. Use `legal_moves` to validate first.
+    pub fn make_move(&mut self, mv: Move) {
+        let Some(piece) = self.piece_at(mv.from) else {
+            return;
+        };
+        let mut is_capture = self.piece_at(mv.to).is_some();
+        let previous_en_passant = self.en_passant.take();
+
+        if piece.kind == PieceKind::Pawn && Some(mv.to) == previous_en_passant && !is_capture {
+            let captured = (mv.from / 8) * 8 + mv.to % 8;
+            self.squares[captured as usize] = None;
+            is_capture = true;
+        }
+
+        if piece.kind == PieceKind::King && (mv.from as i8 - mv.to as i8).abs() == 2 {
+            let (rook_from, rook_to) = if mv.to > mv.from {
+                (mv.to + 1, mv.to - 1)
+            } else {
+                (mv.to - 2, mv.to + 1)
+            };
+            self.squares[rook_to as usize] = self.squares[rook_from as usize].take();
+        }
+
+        self.squares[mv.from as usize] = None;
+        self.squares[mv.to as usize] = Some(match mv.promotion {
+            Some(kind) => ChessPiece::new(kind, piece.color),
+            None => piece,
+        });
+
+        if piece.kind == PieceKind::Pawn && (mv.from as i8 - mv.to as i8).abs() == 16 {
+            self.en_passant = Some((mv.from + mv.to) / 2);
+        }
+
+        for sq in [mv.from, mv.to] {
+            match sq {
+                0 => self.castling.white_queen_side = false,
+                7 => self.castling.white_king_side = false,
+                4 => {
+                    self.castling.white_king_side = false;
+                    self.castling.white_queen_side = false;
+                }
+                56 => self.castling.black_queen_side = false,
+                63 => self.castling.black_king_side = false,
+                60 => {
+                    self.castling.black_king_side = false;
+                    self.castling.black_queen_side = false;
+                }
+                _ => {}
+            }
+        }
+
+        if piece.kind == PieceKind::Pawn || is_capture {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opposite();
+    }
+
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if self.legal_moves().is_empty() {
+            return Some(if self.in_check() {
+                GameOutcome::Checkmate {
+                    winner: self.side_to_move.opposite(),
+                }
+            } else {
+                GameOutcome::Stalemate
+            });
+        }
+        if self.halfmove_clock >= 100 {
+            return Some(GameOutcome::FiftyMoveRule);
+        }
+        let heavy = self.pieces().any(|(_, p)| {
+            matches!(p.kind, PieceKind::Pawn | PieceKind::Rook | PieceKind::Queen)
+        });
+        let minors = self
+            .pieces()
+            .filter(|(_, p)| matches!(p.kind, PieceKind::Knight | PieceKind::Bishop))
+            .count();
+        if !heavy && minors <= 1 {
+            return Some(GameOutcome::InsufficientMaterial);
+        }
+        None
+    }
+
+    // =======================================
+    // SAN
+    // =======================================
+
+    /// Standard algebraic notation for a legal move in this position, e.g. `Nbd7`, `exd6`, `O-O+`.
+    pub fn to_san(&self, mv: Move) -> String {
+        let Some(piece) = self.piece_at(mv.from) else {
+            return mv.to_uci();
+        };
+
+        let mut san = if piece.kind == PieceKind::King && (mv.from as i8 - mv.to as i8).abs() == 2 {
+            if mv.to > mv.from {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let is_capture =
+                self.piece_at(mv.to).is_some() || (piece.kind == PieceKind::Pawn && mv.from % 8 != mv.to % 8);
+            let mut s = String::new();
+            if piece.kind == PieceKind::Pawn {
+                if is_capture {
+                    s.push((b'a' + mv.from % 8) as char);
+                }
+            } else {
+                s.push(piece.kind.to_char().to_ascii_uppercase());
+                let rivals: Vec<Move> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|m| {
+                        m.to == mv.to
+                            && m.from != mv.from
+                            && self.piece_at(m.from).map(|p| p.kind) == Some(piece.kind)
+                    })
+                    .collect();
+                if !rivals.is_empty() {
+                    let same_file = rivals.iter().any(|m| m.from % 8 == mv.from % 8);
+                    let same_rank = rivals.iter().any(|m| m.from / 8 == mv.from / 8);
+                    if !same_file {
+                        s.push((b'a' + mv.from % 8) as char);
+                    } else if !same_rank {
+                        s.push((b'1' + mv.from / 8) as char);
+                    } else {
+                        s.push_str(&square_name(mv.from));
+                    }
+                }
+            }
+            if is_capture {
+                s.push('x');
+            }
+            s.push_str(&square_name(mv.to));
+            if let Some(kind) = mv.promotion {
+                s.push('=');
+                s.push(kind.to_char().to_ascii_uppercase());
+            }
+            s
+        };
+
+        let mut next = self.clone();
+        next.make_move(mv);
+        if next.in_check() {
+            san.push(if next.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// Parses a SAN move (annotations like `+`, `#`, `!`, `?` are ignored).
+    pub fn parse_san(&self, san: &str) -> Option<Move> {
+        let normalize = |s: &str| {
+            s.trim()
+                .trim_end_matches(['+', '#', '!', '?'])
+                .replace('0', "O")
+                .replace('=', "")
+        };
+        let wanted = normalize(san);
+        if wanted.is_empty() {
+            return None;
+        }
+        self.legal_moves()
+            .into_iter()
+            .find(|&mv| normalize(&self.to_san(mv)) == wanted)
+            .or_else(|| Move::from_uci(san).filter(|&mv| self.is_legal(mv)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    fn perft(position: &Position, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        position
+            .legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let mut next = position.clone();
+                next.make_move(mv);
+                perft(&next, depth - 1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn fen_round_trip() {
+        for fen in [
+            START_FEN,
+            KIWIPETE,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        ] {
+            assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn malformed_fen_is_rejected() {
+        for fen in [
+            "99999999/8/8/8/8/8/8/8 w - - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/44/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/08/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w",
+        ] {
+            assert!(Position::from_fen(fen).is_err(), "accepted {}", fen);
+        }
+    }
+
+    #[test]
+    fn san_round_trip() {
+        let position = Position::from_fen(KIWIPETE).unwrap();
+        for mv in position.legal_moves() {
+            assert_eq!(position.parse_san(&position.to_san(mv)), Some(mv));
+        }
+        assert_eq!(
+            position.parse_san("O-O"),
+            Some(Move::new(parse_square("e1").unwrap(), parse_square("g1").unwrap()))
+        );
+        assert_eq!(position.parse_san("Qxf6+!"), position.parse_san("Qxf6"));
+        assert_eq!(position.parse_san("Ke3"), None);
+    }
+
+    #[test]
+    fn uci_moves() {
+        let e2 = parse_square("e2").unwrap();
+        let e4 = parse_square("e4").unwrap();
+        assert_eq!(Move::from_uci("e2e4"), Some(Move::new(e2, e4)));
+        assert_eq!(Move::from_uci(" e7e8q ").map(Move::to_uci).as_deref(), Some("e7e8q"));
+        for text in ["e2e", "e2e4qzz", "e2e4x", "i2e4", "e2e9", "(none)"] {
+            assert_eq!(Move::from_uci(text), None, "accepted {}", text);
+        }
+    }
+
+    #[test]
+    fn perft_start_position() {
+        let position = Position::default();
+        assert_eq!(perft(&position, 1), 20);
+        assert_eq!(perft(&position, 2), 400);
+        assert_eq!(perft(&position, 3), 8_902);
+    }
+
+    #[test]
+    fn perft_kiwipete() {
+        let position = Position::from_fen(KIWIPETE).unwrap();
+        assert_eq!(perft(&position, 1), 48);
+        assert_eq!(perft(&position, 2), 2_039);
+        assert_eq!(perft(&position, 3), 97_862);
+    }
+
+    #[test]
+    fn perft_en_passant_and_pins() {
+        let position = Position::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(perft(&position, 1), 14);
+        assert_eq!(perft(&position, 2), 191);
+        assert_eq!(perft(&position, 3), 2_812);
+    }
+}
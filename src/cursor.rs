@@ -4,14 +4,19 @@ use bevy_mod_outline::{OutlineMode, OutlineVolume};
 pub struct Cursor {
    pub cursor_position: Vec3,
 }
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn calc_cursor_pos(
     retro_camera_query: Query<(&Camera, &GlobalTransform), With<crate::retrocamera::RetroCamera>>,
     sprite_query: Query<&Transform, With<Sprite>>,
-    main_camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    main_camera_query: Query<
+        (&Camera, &GlobalTransform),
+        (With<Camera3d>, Without<crate::layout::ViewportCamera>),
+    >,
     ground: Single<&GlobalTransform, With<crate::ground::Ground>>,
     windows: Query<&Window>,
     mut cursor: ResMut<Cursor>,
     target_opt: Option<Res<crate::retrocamera::RetroRenderTarget>>,
+    viewports: Res<crate::layout::Viewports>,
 ) {
     let Ok(window) = windows.single() else {
         return;
@@ -22,8 +27,32 @@ pub fn calc_cursor_pos(
 
     // Try retrocamera path first (if resource and entities exist)
     if let Some(target) = target_opt {
-        if let (Ok((retro_camera, retro_transform)), Ok(sprite_transform)) =
-            (retro_camera_query.single(), sprite_query.single())
+        // Ci possono essere più retro camere (editor, terza persona): si usa quella attiva.
+        let active_retro = retro_camera_query.iter().find(|(camera, _)| camera.is_active);
+        // Vista nel pannello "Viewport": si mappa sul rettangolo dell'immagine, non sullo sprite.
+        if viewports.main_open {
+            let (Some((retro_camera, retro_transform)), Some(rect)) = (active_retro, viewports.main_rect)
+            else {
+                return;
+            };
+            if !rect.contains(cursor_position) {
+                return;
+            }
+            let texture_size = Vec2::new(target.width as f32, target.height as f32);
+            let texture_coords = (cursor_position - rect.min) / rect.size() * texture_size;
+            let Ok(ray) = retro_camera.viewport_to_world(retro_transform, texture_coords) else {
+                return;
+            };
+            let Some(distance) =
+                ray.intersect_plane(ground.translation(), InfinitePlane3d::new(ground.up()))
+            else {
+                return;
+            };
+            cursor.cursor_position = ray.get_point(distance);
+            return;
+        }
+        if let (Some((retro_camera, retro_transform)), Ok(sprite_transform)) =
+            (active_retro, sprite_query.single())
         {
             let window_size = Vec2::new(window.width(), window.height());
             let texture_size = Vec2::new(target.width as f32, target.height as f32);
@@ -67,7 +96,9 @@ pub fn calc_cursor_pos(
     }
 
     // Fallback: use main 3D camera if retrocamera is not active
-    if let Ok((main_camera, main_transform)) = main_camera_query.single() {
+    if let Some((main_camera, main_transform)) =
+        main_camera_query.iter().find(|(camera, _)| camera.is_active)
+    {
         let Ok(ray) = main_camera.viewport_to_world(main_transform, cursor_position) else {
             return;
         };
@@ -82,16 +113,18 @@ pub fn calc_cursor_pos(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_cursor(
     cursor: Res<Cursor>,
     ground: Single<&GlobalTransform, With<crate::ground::Ground>>,
     buttons: Res<ButtonInput<MouseButton>>,
+    focus: Res<crate::input::UiFocus>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut cursor_entities: Query<(Entity, &mut Transform), With<CursorToroid>>,
 ) {
-    if buttons.just_pressed(MouseButton::Left) {
+    if buttons.just_pressed(MouseButton::Left) && !focus.mouse {
         // Remove existing cursor toroids
         for (entity, _) in cursor_entities.iter() {
             commands.entity(entity).despawn();
@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Nodo della gerarchia esportata. I nomi devono essere quelli del modello originale:
+/// Bevy risale agli `AnimationTargetId` dal percorso dei nomi.
+pub struct ExportNode {
+    pub name: String,
+    pub transform: Transform,
+    pub children: Vec<usize>,
+}
+
+/// Chiavi di un nodo: un valore per ogni tempo di `ExportAnimation::times`.
+pub struct ExportTrack {
+    pub node: usize,
+    pub transforms: Vec<Transform>,
+}
+
+pub struct ExportAnimation {
+    pub name: String,
+    pub times: Vec<f32>,
+    pub tracks: Vec<ExportTrack>,
+}
+
+/// Dati binari del file e accessor glTF che li descrivono.
+#[derive(Default)]
+struct BufferBuilder {
+    data: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl BufferBuilder {
+    /// Aggiunge un accessor di float (`kind` = "SCALAR", "VEC3", "VEC4") e ne restituisce l'indice.
+    fn push(&mut self, kind: &str, components: usize, values: &[f32], bounds: bool) -> usize {
+        let offset = self.data.len();
+        for value in values {
+            self.data.extend_from_slice(&value.to_le_bytes());
+        }
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": values.len() * 4,
+        }));
+        let mut accessor = json!({
+            "bufferView": self.views.len() - 1,
+            "componentType": 5126,
+            "count": values.len() / components,
+            "type": kind,
+        });
+        // Gli input dei sampler devono avere min e max.
+        if bounds {
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            accessor["min"] = json!([min]);
+            accessor["max"] = json!([max]);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+}
+
+/// Scrive un `.glb` con la gerarchia `nodes` (radici in `roots`) e un'animazione lineare.
+pub fn write_animation_glb(
+    path: &Path,
+    nodes: &[ExportNode],
+    roots: &[usize],
+    animation: &ExportAnimation,
+) -> io::Result<()> {
+    let mut buffer = BufferBuilder::default();
+    let input = buffer.push("SCALAR", 1, &animation.times, true);
+
+    let mut samplers = Vec::new();
+    let mut channels = Vec::new();
+    for track in &animation.tracks {
+        let translations: Vec<f32> = track
+            .transforms
+            .iter()
+            .flat_map(|t| t.translation.to_array())
+            .collect();
+        let rotations: Vec<f32> = track
+            .transforms
+            .iter()
+            .flat_map(|t| t.rotation.to_array())
+            .collect();
+        let scales: Vec<f32> = track
+            .transforms
+            .iter()
+            .flat_map(|t| t.scale.to_array())
+            .collect();
+        let outputs = [
+            ("translation", buffer.push("VEC3", 3, &translations, false)),
+            ("rotation", buffer.push("VEC4", 4, &rotations, false)),
+            ("scale", buffer.push("VEC3", 3, &scales, false)),
+        ];
+        for (target, output) in outputs {
+            samplers.push(json!({
+                "input": input,
+                "output": output,
+                "interpolation": "LINEAR",
+            }));
+            channels.push(json!({
+                "sampler": samplers.len() - 1,
+                "target": { "node": track.node, "path": target },
+            }));
+        }
+    }
+
+    let nodes: Vec<Value> = nodes
+        .iter()
+        .map(|node| {
+            let mut value = json!({
+                "name": node.name,
+                "translation": node.transform.translation.to_array(),
+                "rotation": node.transform.rotation.to_array(),
+                "scale": node.transform.scale.to_array(),
+            });
+            if !node.children.is_empty() {
+                value["children"] = json!(node.children);
+            }
+            value
+        })
+        .collect();
+
+    let document = json!({
+        "asset": { "version": "2.0", "generator": "Xirai pose editor" },
+        "scene": 0,
+        "scenes": [{ "nodes": roots }],
+        "nodes": nodes,
+        "animations": [{
+            "name": animation.name,
+            "channels": channels,
+            "samplers": samplers,
+        }],
+        "buffers": [{ "byteLength": buffer.data.len() }],
+        "bufferViews": buffer.views,
+        "accessors": buffer.accessors,
+    });
+
+    // GLB: header, chunk JSON (padding con spazi) e chunk BIN (padding con zeri).
+    let mut json_chunk = serde_json::to_vec(&document).map_err(io::Error::other)?;
+    while !json_chunk.len().is_multiple_of(4) {
+        json_chunk.push(b' ');
+    }
+    let mut bin_chunk = buffer.data;
+    while !bin_chunk.len().is_multiple_of(4) {
+        bin_chunk.push(0);
+    }
+    let total = 12 + 8 + json_chunk.len() + 8 + bin_chunk.len();
+
+    let mut glb = Vec::with_capacity(total);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json_chunk);
+    glb.extend_from_slice(&(bin_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin_chunk);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, glb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> usize {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+    }
+
+    fn node(name: &str, children: Vec<usize>) -> ExportNode {
+        ExportNode {
+            name: name.to_string(),
+            transform: Transform::default(),
+            children,
+        }
+    }
+
+    #[test]
+    fn two_key_clip_round_trip() {
+        let nodes = [
+            node("Armature", vec![1]),
+            node("Hips", vec![2]),
+            node("Spine", vec![]),
+        ];
+        let animation = ExportAnimation {
+            name: "Wave".to_string(),
+            times: vec![0.0, 0.75],
+            tracks: vec![ExportTrack {
+                node: 2,
+                transforms: vec![
+                    Transform::default(),
+                    Transform::from_xyz(0.0, 1.0, 0.0).with_rotation(Quat::from_rotation_z(0.5)),
+                ],
+            }],
+        };
+        let path = std::env::temp_dir()
+            .join("xirai_gltf_export_test")
+            .join("wave.glb");
+        write_animation_glb(&path, &nodes, &[0], &animation).unwrap();
+        let glb = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(u32_at(&glb, 4), 2);
+        assert_eq!(u32_at(&glb, 8), glb.len());
+        let json_len = u32_at(&glb, 12);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(json_len % 4, 0);
+        let bin_start = 20 + json_len;
+        let bin_len = u32_at(&glb, bin_start);
+        assert_eq!(&glb[bin_start + 4..bin_start + 8], b"BIN\0");
+        assert_eq!(bin_start + 8 + bin_len, glb.len());
+
+        let document: Value = serde_json::from_slice(&glb[20..bin_start]).unwrap();
+        // Input: 2 tempi; per la traccia translation, rotation e scale.
+        let floats = 2 + 2 * (3 + 4 + 3);
+        assert_eq!(document["buffers"][0]["byteLength"], floats * 4);
+        assert_eq!(bin_len, floats * 4);
+
+        let accessors = document["accessors"].as_array().unwrap();
+        let counts: Vec<(u64, &str)> = accessors
+            .iter()
+            .map(|a| (a["count"].as_u64().unwrap(), a["type"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            counts,
+            [(2, "SCALAR"), (2, "VEC3"), (2, "VEC4"), (2, "VEC3")]
+        );
+        assert_eq!(accessors[0]["min"], json!([0.0]));
+        assert_eq!(accessors[0]["max"], json!([0.75]));
+        assert!(accessors[1].get("min").is_none());
+
+        let clip = &document["animations"][0];
+        assert_eq!(clip["name"], "Wave");
+        let samplers = clip["samplers"].as_array().unwrap();
+        assert_eq!(samplers.len(), 3);
+        assert!(samplers.iter().all(|s| s["input"] == 0));
+
+        // Il percorso dei nomi dalla radice al nodo animato resta quello del modello.
+        let target = clip["channels"][0]["target"]["node"].as_u64().unwrap();
+        let mut path = Vec::new();
+        let mut current = document["scenes"][0]["nodes"][0].as_u64().unwrap();
+        loop {
+            let node = &document["nodes"][current as usize];
+            path.push(node["name"].as_str().unwrap().to_string());
+            if current == target {
+                break;
+            }
+            current = node["children"][0].as_u64().unwrap();
+        }
+        assert_eq!(path, ["Armature", "Hips", "Spine"]);
+    }
+}
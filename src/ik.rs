@@ -171,7 +171,12 @@ pub struct IkConstraint {
 
 impl Plugin for InverseKinematicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, inverse_kinematics_system);
+        // Dopo la propagazione: il solver parte dalla posa animata e aggiorna da sé
+        // i `GlobalTransform` della catena.
+        app.add_systems(
+            PostUpdate,
+            inverse_kinematics_system.after(TransformSystem::TransformPropagate),
+        );
         app.register_type::<IkConstraint>();
     }
 }
@@ -0,0 +1,399 @@
+use crate::ik::IkConstraint;
+use crate::input::{Action, InputBindings};
+use crate::save_load::SavedIkConstraint;
+use crate::skeleton::skin_joints;
+use crate::transform::{scene_root, PickableExt, Selected};
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy_mod_imgui::prelude::*;
+use std::collections::HashMap;
+
+/// Maniglia trascinabile (bersaglio o pole) di un rig IK, figlia della radice del modello.
+/// Si sposta con il gizmo di transform.rs come qualsiasi altra entità.
+#[derive(Component)]
+pub struct IkHandle {
+    pub pole: bool,
+}
+
+/// Vincoli letti dal file della scena, applicati quando la scena del modello è spawnata.
+/// Sostituiscono il rig che il modello ha già (vuoto = nessun vincolo).
+#[derive(Component)]
+pub struct PendingIkRig(pub Vec<SavedIkConstraint>);
+
+#[derive(Resource)]
+struct IkHandleAssets {
+    mesh: Handle<Mesh>,
+    target: Handle<StandardMaterial>,
+    pole: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Default)]
+struct IkEditorState {
+    root: Option<Entity>,
+    bone: Option<Entity>,
+    filter: String,
+}
+
+fn setup_ik_handle_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = |color: Color| StandardMaterial {
+        base_color: color,
+        unlit: true,
+        ..default()
+    };
+    commands.insert_resource(IkHandleAssets {
+        mesh: meshes.add(Sphere::new(0.06)),
+        target: materials.add(material(Color::srgb(1.0, 0.8, 0.1))),
+        pole: materials.add(material(Color::srgb(0.2, 0.7, 1.0))),
+    });
+}
+
+fn spawn_handle(
+    commands: &mut Commands,
+    assets: &IkHandleAssets,
+    root: Entity,
+    pole: bool,
+    position: Vec3,
+) -> Entity {
+    let (name, material) = if pole {
+        ("IK pole", assets.pole.clone())
+    } else {
+        ("IK target", assets.target.clone())
+    };
+    commands
+        .spawn((
+            Name::new(name),
+            IkHandle { pole },
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(material),
+            NotShadowCaster,
+            Transform::from_translation(position),
+            ChildOf(root),
+        ))
+        .with_pickable()
+        .id()
+}
+
+/// Quante ossa sopra `bone` il solver può usare (gli serve anche il genitore della catena).
+fn max_chain_length(bone: Entity, parents: &Query<&ChildOf>) -> usize {
+    let mut depth: usize = 0;
+    let mut current = bone;
+    while let Ok(child_of) = parents.get(current) {
+        current = child_of.parent();
+        depth += 1;
+    }
+    depth.saturating_sub(1).max(1)
+}
+
+/// Crea maniglie e vincoli dei rig caricati dal file, appena esistono le ossa con quei nomi.
+fn apply_pending_ik_rigs(
+    mut commands: Commands,
+    assets: Res<IkHandleAssets>,
+    rigs: Query<(Entity, &PendingIkRig)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    constraints: Query<(), With<IkConstraint>>,
+    handles: Query<(), With<IkHandle>>,
+) {
+    for (root, rig) in rigs.iter() {
+        let bones: HashMap<&str, Entity> = children
+            .iter_descendants(root)
+            .filter_map(|e| Some((names.get(e).ok()?.as_str(), e)))
+            .collect();
+        if bones.is_empty() {
+            continue;
+        }
+        for entity in children.iter_descendants(root) {
+            if handles.contains(entity) {
+                commands.entity(entity).despawn();
+            } else if constraints.contains(entity) {
+                commands.entity(entity).remove::<IkConstraint>();
+            }
+        }
+        for saved in &rig.0 {
+            let Some(&bone) = bones.get(saved.bone.as_str()) else {
+                warn!("IK: osso '{}' non trovato, vincolo ignorato", saved.bone);
+                continue;
+            };
+            let target = spawn_handle(
+                &mut commands,
+                &assets,
+                root,
+                false,
+                Vec3::from_array(saved.target),
+            );
+            let pole_target = saved
+                .pole
+                .map(|pole| spawn_handle(&mut commands, &assets, root, true, Vec3::from_array(pole)));
+            commands.entity(bone).insert(IkConstraint {
+                chain_length: saved.chain_length,
+                iterations: saved.iterations,
+                target,
+                pole_target,
+                pole_angle: saved.pole_angle,
+                enabled: saved.enabled,
+            });
+        }
+        commands.entity(root).remove::<PendingIkRig>();
+    }
+}
+
+/// Catene, bersagli e pole dei vincoli del modello in modifica.
+fn draw_ik_rigs(
+    mut gizmos: Gizmos,
+    state: Res<IkEditorState>,
+    constraints: Query<(Entity, &IkConstraint)>,
+    parents: Query<&ChildOf>,
+    globals: Query<&GlobalTransform>,
+) {
+    let Some(root) = state.root else {
+        return;
+    };
+    for (bone, constraint) in constraints.iter() {
+        if scene_root(bone, &parents) != root {
+            continue;
+        }
+        let Ok(tail) = globals.get(bone).map(|g| g.translation()) else {
+            continue;
+        };
+        let color = if Some(bone) == state.bone {
+            Color::srgb(1.0, 1.0, 0.0)
+        } else {
+            Color::srgb(1.0, 0.5, 0.0)
+        };
+        let mut joint = bone;
+        let mut position = tail;
+        for _ in 0..constraint.chain_length {
+            let Ok(parent) = parents.get(joint).map(|p| p.parent()) else {
+                break;
+            };
+            let Ok(parent_position) = globals.get(parent).map(|g| g.translation()) else {
+                break;
+            };
+            gizmos.line(position, parent_position, color);
+            gizmos.sphere(Isometry3d::from_translation(parent_position), 0.02, color);
+            joint = parent;
+            position = parent_position;
+        }
+        if let Ok(target) = globals.get(constraint.target) {
+            gizmos.line(tail, target.translation(), Color::srgb(1.0, 0.8, 0.1));
+        }
+        if let Some(pole) = constraint.pole_target.and_then(|p| globals.get(p).ok()) {
+            gizmos.line(position, pole.translation(), Color::srgb(0.2, 0.7, 1.0));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn ik_editor_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut state: ResMut<IkEditorState>,
+    assets: Res<IkHandleAssets>,
+    bindings: Res<InputBindings>,
+    selected: Query<Entity, With<Selected>>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    skins: Query<&SkinnedMesh>,
+    handles: Query<(Entity, &IkHandle, Option<&Name>)>,
+    globals: Query<&GlobalTransform>,
+    mut constraints: Query<(Entity, &mut IkConstraint)>,
+) {
+    let state = &mut *state;
+    let mut removed = None;
+
+    // Selezionare una maniglia non cambia il modello in modifica.
+    if let Some(entity) = selected.iter().next() {
+        if !handles.contains(entity) {
+            let root = scene_root(entity, &parents);
+            if state.root != Some(root) {
+                state.root = Some(root);
+                state.bone = None;
+            }
+        }
+    }
+
+    let ui = context.ui();
+    ui.window("IK Rig")
+        .size([330.0, 480.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let Some(root) = state.root.filter(|&r| globals.contains(r)) else {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "Select a model");
+                return;
+            };
+            ui.text(format!("Model: {:?}", root));
+
+            // Le ossa sono i joint delle skin del modello.
+            let joints = skin_joints(root, &children, &skins);
+            if joints.is_empty() {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "No skeleton");
+                return;
+            }
+
+            ui.input_text("Filter", &mut state.filter).build();
+            let filter = state.filter.to_lowercase();
+            ui.child_window("bones").size([0.0, 160.0]).build(|| {
+                for bone in children.iter_descendants(root) {
+                    if !joints.contains(&bone) {
+                        continue;
+                    }
+                    let Ok(name) = names.get(bone) else {
+                        continue;
+                    };
+                    if !filter.is_empty() && !name.as_str().to_lowercase().contains(&filter) {
+                        continue;
+                    }
+                    let label = if constraints.contains(bone) {
+                        format!("[IK] {}##{:?}", name, bone)
+                    } else {
+                        format!("{}##{:?}", name, bone)
+                    };
+                    if ui
+                        .selectable_config(&label)
+                        .selected(state.bone == Some(bone))
+                        .build()
+                    {
+                        state.bone = Some(bone);
+                    }
+                }
+            });
+
+            let Some(bone) = state.bone else {
+                ui.text("Pick the end bone of a chain");
+                return;
+            };
+            ui.separator();
+            ui.text(format!(
+                "Bone: {}",
+                names.get(bone).map_or("?", |n| n.as_str())
+            ));
+
+            let to_local = globals
+                .get(root)
+                .map(|g| g.affine().inverse())
+                .unwrap_or_default();
+            let local_position = |entity: Entity| {
+                globals
+                    .get(entity)
+                    .map(|g| to_local.transform_point3(g.translation()))
+                    .unwrap_or_default()
+            };
+
+            let Ok((_, mut constraint)) = constraints.get_mut(bone) else {
+                if ui.button("Add IK constraint") {
+                    let target = spawn_handle(&mut commands, &assets, root, false, local_position(bone));
+                    commands.entity(bone).insert(IkConstraint {
+                        chain_length: 2.min(max_chain_length(bone, &parents)),
+                        iterations: 10,
+                        target,
+                        pole_target: None,
+                        pole_angle: 0.0,
+                        enabled: true,
+                    });
+                }
+                return;
+            };
+
+            ui.checkbox("Enabled", &mut constraint.enabled);
+            let max_chain = max_chain_length(bone, &parents);
+            ui.slider("Chain length", 1, max_chain, &mut constraint.chain_length);
+            ui.slider("Iterations", 1, 50, &mut constraint.iterations);
+
+            // Maniglie di questo modello: un bersaglio può essere condiviso tra più vincoli.
+            let rig_handles: Vec<(Entity, bool, String)> = handles
+                .iter()
+                .filter(|(e, _, _)| parents.get(*e).is_ok_and(|p| p.parent() == root))
+                .map(|(e, handle, name)| {
+                    let label = name.map_or_else(|| "IK handle".to_string(), |n| n.to_string());
+                    (e, handle.pole, format!("{} {:?}", label, e))
+                })
+                .collect();
+            let label_of = |entity: Entity| {
+                rig_handles
+                    .iter()
+                    .find(|(e, _, _)| *e == entity)
+                    .map_or_else(|| format!("{:?}", entity), |(_, _, label)| label.clone())
+            };
+
+            if let Some(_combo) = ui.begin_combo("Target", label_of(constraint.target)) {
+                for (entity, pole, label) in &rig_handles {
+                    if !pole && ui.selectable(label) {
+                        constraint.target = *entity;
+                    }
+                }
+                if ui.selectable("New handle") {
+                    constraint.target =
+                        spawn_handle(&mut commands, &assets, root, false, local_position(bone));
+                }
+            }
+
+            let pole_label = constraint
+                .pole_target
+                .map_or_else(|| "None".to_string(), label_of);
+            if let Some(_combo) = ui.begin_combo("Pole", pole_label) {
+                if ui.selectable("None") {
+                    constraint.pole_target = None;
+                }
+                for (entity, pole, label) in &rig_handles {
+                    if *pole && ui.selectable(label) {
+                        constraint.pole_target = Some(*entity);
+                    }
+                }
+                if ui.selectable("New pole handle") {
+                    // Davanti al giunto intermedio della catena, dove di solito punta il gomito/ginocchio.
+                    let middle = parents.get(bone).map_or(bone, |p| p.parent());
+                    let position = local_position(middle) + Vec3::Z * 0.5;
+                    constraint.pole_target =
+                        Some(spawn_handle(&mut commands, &assets, root, true, position));
+                }
+            }
+            if constraint.pole_target.is_some() {
+                let mut degrees = constraint.pole_angle.to_degrees();
+                if ui.slider("Pole angle", -180.0, 180.0, &mut degrees) {
+                    constraint.pole_angle = degrees.to_radians();
+                }
+            }
+
+            ui.text_disabled(format!(
+                "Select a handle and drag it with {} + {}",
+                bindings.describe(Action::GizmoTranslate),
+                bindings.describe(Action::GizmoDrag),
+            ));
+            ui.text_disabled(format!(
+                "(hold {} to move it vertically)",
+                bindings.describe(Action::GizmoVertical),
+            ));
+
+            if ui.button("Remove constraint") {
+                removed = Some((bone, constraint.target, constraint.pole_target));
+            }
+        });
+
+    if let Some((bone, target, pole)) = removed {
+        commands.entity(bone).remove::<IkConstraint>();
+        // Le maniglie usate solo da questo vincolo se ne vanno con lui.
+        for handle in std::iter::once(target).chain(pole) {
+            let shared = constraints.iter().any(|(other, constraint)| {
+                other != bone
+                    && (constraint.target == handle || constraint.pole_target == Some(handle))
+            });
+            if !shared && handles.contains(handle) {
+                commands.entity(handle).despawn();
+            }
+        }
+    }
+}
+
+pub struct IkEditorPlugin;
+impl Plugin for IkEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IkEditorState>()
+            .add_systems(Startup, setup_ik_handle_assets)
+            .add_systems(Update, (apply_pending_ik_rigs, ik_editor_ui, draw_ik_rigs));
+    }
+}
@@ -0,0 +1,625 @@
+use crate::play_mode::EditorMode;
+use crate::ron_file;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+const CONFIG_PATH: &str = "config/input.ron";
+
+/// Quando un'azione è attiva: le mappe dell'editor e del gioco non si disturbano.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputContext {
+    Editor,
+    Play,
+    Global,
+}
+
+impl InputContext {
+    const ALL: [InputContext; 3] = [InputContext::Editor, InputContext::Play, InputContext::Global];
+
+    fn label(self) -> &'static str {
+        match self {
+            InputContext::Editor => "Editor",
+            InputContext::Play => "Play",
+            InputContext::Global => "Global",
+        }
+    }
+
+    fn overlaps(self, other: InputContext) -> bool {
+        self == other || self == InputContext::Global || other == InputContext::Global
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    CameraForward,
+    CameraBack,
+    CameraLeft,
+    CameraRight,
+    CameraOrbit,
+    CameraZoom,
+    CameraFocus,
+    ViewFront,
+    ViewBack,
+    ViewRight,
+    ViewLeft,
+    ViewTop,
+    ViewBottom,
+    GizmoTranslate,
+    GizmoRotate,
+    GizmoScale,
+    GizmoDrag,
+    GizmoVertical,
+    Deselect,
+    Duplicate,
+    Delete,
+    PlaceAsset,
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Attack,
+    ToggleRagdoll,
+    TogglePlay,
+}
+
+impl Action {
+    pub const ALL: [Action; 30] = [
+        Action::CameraForward,
+        Action::CameraBack,
+        Action::CameraLeft,
+        Action::CameraRight,
+        Action::CameraOrbit,
+        Action::CameraZoom,
+        Action::CameraFocus,
+        Action::ViewFront,
+        Action::ViewBack,
+        Action::ViewRight,
+        Action::ViewLeft,
+        Action::ViewTop,
+        Action::ViewBottom,
+        Action::GizmoTranslate,
+        Action::GizmoRotate,
+        Action::GizmoScale,
+        Action::GizmoDrag,
+        Action::GizmoVertical,
+        Action::Deselect,
+        Action::Duplicate,
+        Action::Delete,
+        Action::PlaceAsset,
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Attack,
+        Action::ToggleRagdoll,
+        Action::TogglePlay,
+    ];
+
+    pub fn context(self) -> InputContext {
+        use Action::*;
+        match self {
+            MoveForward | MoveBack | MoveLeft | MoveRight | Jump | Attack | ToggleRagdoll => {
+                InputContext::Play
+            }
+            TogglePlay => InputContext::Global,
+            _ => InputContext::Editor,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        use Action::*;
+        match self {
+            CameraForward => "Camera forward",
+            CameraBack => "Camera back",
+            CameraLeft => "Camera left",
+            CameraRight => "Camera right",
+            CameraOrbit => "Camera orbit (hold)",
+            CameraZoom => "Camera zoom (hold + drag)",
+            CameraFocus => "Focus selection",
+            ViewFront => "Front view",
+            ViewBack => "Back view",
+            ViewRight => "Right view",
+            ViewLeft => "Left view",
+            ViewTop => "Top view",
+            ViewBottom => "Bottom view",
+            GizmoTranslate => "Translate mode",
+            GizmoRotate => "Rotate mode",
+            GizmoScale => "Scale mode",
+            GizmoDrag => "Transform drag",
+            GizmoVertical => "Translate on Y (hold)",
+            Deselect => "Deselect",
+            Duplicate => "Duplicate",
+            Delete => "Delete",
+            PlaceAsset => "Place asset",
+            MoveForward => "Move forward",
+            MoveBack => "Move back",
+            MoveLeft => "Move left",
+            MoveRight => "Move right",
+            Jump => "Jump",
+            Attack => "Attack",
+            ToggleRagdoll => "Toggle ragdoll",
+            TogglePlay => "Play / Stop",
+        }
+    }
+
+    /// Mappa di partenza. La camera dell'editor usa le frecce: WASD lascerebbe S in
+    /// conflitto con la modalità Scale, e il destro del mouse è del trascinamento.
+    fn default_bindings(self) -> Vec<Binding> {
+        use Action::*;
+        let key = Binding::key;
+        let mouse = Binding::mouse;
+        match self {
+            CameraForward => vec![key(KeyCode::ArrowUp)],
+            CameraBack => vec![key(KeyCode::ArrowDown)],
+            CameraLeft => vec![key(KeyCode::ArrowLeft)],
+            CameraRight => vec![key(KeyCode::ArrowRight)],
+            CameraOrbit => vec![mouse(MouseButton::Middle)],
+            CameraZoom => vec![key(KeyCode::AltLeft)],
+            CameraFocus => vec![key(KeyCode::KeyF)],
+            ViewFront => vec![key(KeyCode::Numpad1)],
+            ViewBack => vec![key(KeyCode::Numpad1).ctrl()],
+            ViewRight => vec![key(KeyCode::Numpad3)],
+            ViewLeft => vec![key(KeyCode::Numpad3).ctrl()],
+            ViewTop => vec![key(KeyCode::Numpad7)],
+            ViewBottom => vec![key(KeyCode::Numpad7).ctrl()],
+            GizmoTranslate => vec![key(KeyCode::KeyG)],
+            GizmoRotate => vec![key(KeyCode::KeyR)],
+            GizmoScale => vec![key(KeyCode::KeyS)],
+            GizmoDrag => vec![mouse(MouseButton::Right)],
+            GizmoVertical => vec![key(KeyCode::ShiftLeft)],
+            Deselect => vec![key(KeyCode::Escape)],
+            Duplicate => vec![key(KeyCode::KeyD).shift()],
+            Delete => vec![key(KeyCode::Delete), key(KeyCode::KeyX)],
+            PlaceAsset => vec![mouse(MouseButton::Right).ctrl()],
+            MoveForward => vec![key(KeyCode::KeyW)],
+            MoveBack => vec![key(KeyCode::KeyS)],
+            MoveLeft => vec![key(KeyCode::KeyA)],
+            MoveRight => vec![key(KeyCode::KeyD)],
+            Jump => vec![key(KeyCode::Space)],
+            Attack => vec![mouse(MouseButton::Left)],
+            ToggleRagdoll => vec![key(KeyCode::KeyK)],
+            TogglePlay => vec![key(KeyCode::F5)],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// Tasto o pulsante con i modificatori richiesti.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub button: InputButton,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl Binding {
+    pub fn key(key: KeyCode) -> Self {
+        Self::new(InputButton::Key(key), Modifiers::default())
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self::new(InputButton::Mouse(button), Modifiers::default())
+    }
+
+    fn new(button: InputButton, modifiers: Modifiers) -> Self {
+        Self {
+            button,
+            shift: modifiers.shift,
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+        }
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            shift: self.shift,
+            ctrl: self.ctrl,
+            alt: self.alt,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        for (held, name) in [(self.ctrl, "Ctrl+"), (self.shift, "Shift+"), (self.alt, "Alt+")] {
+            if held {
+                label.push_str(name);
+            }
+        }
+        match self.button {
+            InputButton::Key(key) => {
+                let name = format!("{:?}", key);
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                label.push_str(name);
+            }
+            InputButton::Mouse(button) => label.push_str(&format!("Mouse {:?}", button)),
+        }
+        label
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+struct Modifiers {
+    shift: bool,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl Modifiers {
+    /// Modificatori tenuti premuti, escluso `except` (per i binding che sono essi stessi un modificatore).
+    fn held(keys: &ButtonInput<KeyCode>, except: Option<KeyCode>) -> Self {
+        let down = |a: KeyCode, b: KeyCode| {
+            (keys.pressed(a) && except != Some(a)) || (keys.pressed(b) && except != Some(b))
+        };
+        Self {
+            shift: down(KeyCode::ShiftLeft, KeyCode::ShiftRight),
+            ctrl: down(KeyCode::ControlLeft, KeyCode::ControlRight),
+            alt: down(KeyCode::AltLeft, KeyCode::AltRight),
+        }
+    }
+
+    fn contains(self, other: Modifiers) -> bool {
+        (self.shift || !other.shift) && (self.ctrl || !other.ctrl) && (self.alt || !other.alt)
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+    )
+}
+
+/// Mappa azione → binding, salvata in `config/input.ron`.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct InputBindings {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], |b| b.as_slice())
+    }
+
+    /// Binding leggibili dell'azione, per i testi d'aiuto dei pannelli.
+    pub fn describe(&self, action: Action) -> String {
+        let bindings = self.get(action);
+        if bindings.is_empty() {
+            return "unbound".to_string();
+        }
+        bindings.iter().map(Binding::label).collect::<Vec<_>>().join(" / ")
+    }
+
+    /// Coppie di azioni con lo stesso binding in contesti attivi insieme.
+    pub fn conflicts(&self) -> Vec<(Action, Action, Binding)> {
+        let mut conflicts = Vec::new();
+        for (i, &a) in Action::ALL.iter().enumerate() {
+            for &b in &Action::ALL[i + 1..] {
+                if !a.context().overlaps(b.context()) {
+                    continue;
+                }
+                for binding in self.get(a) {
+                    if self.get(b).contains(binding) {
+                        conflicts.push((a, b, *binding));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut loaded: Self = ron_file::load(path)?;
+        // Le azioni aggiunte dopo il salvataggio prendono il binding di default.
+        for action in Action::ALL {
+            loaded
+                .bindings
+                .entry(action)
+                .or_insert_with(|| action.default_bindings());
+        }
+        Ok(loaded)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        ron_file::save(self, path)
+    }
+}
+
+/// Stato delle azioni nel frame corrente, calcolato in PreUpdate dai binding.
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+/// Cosa vuole imgui in questo momento: se il mouse o la tastiera sono suoi, il mondo non li vede.
+/// Le azioni sono già filtrate; chi legge input grezzo (rotellina, click di picking) controlla qui.
+#[derive(Resource, Default)]
+pub struct UiFocus {
+    pub mouse: bool,
+    pub keyboard: bool,
+}
+
+/// Rimappatura in corso dal pannello: `index` None aggiunge un binding.
+#[derive(Resource, Default)]
+struct InputSettingsState {
+    capture: Option<(Action, Option<usize>)>,
+    status: String,
+}
+
+/// Un'azione tenuta accetta modificatori in più; una premuta vuole esattamente i suoi,
+/// così Shift+D non fa scattare anche D.
+fn update_action_state(
+    mut state: ResMut<ActionState>,
+    bindings: Res<InputBindings>,
+    settings: Res<InputSettingsState>,
+    focus: Res<UiFocus>,
+    mode: Res<State<EditorMode>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+) {
+    state.pressed.clear();
+    state.just_pressed.clear();
+    state.just_released.clear();
+    if settings.capture.is_some() {
+        return;
+    }
+    let playing = *mode.get() == EditorMode::Play;
+    for (&action, action_bindings) in &bindings.bindings {
+        let active = match action.context() {
+            InputContext::Editor => !playing,
+            InputContext::Play => playing,
+            InputContext::Global => true,
+        };
+        if !active {
+            continue;
+        }
+        for binding in action_bindings {
+            // Il rilascio passa sempre, così un trascinamento finito sopra un pannello si chiude.
+            let blocked = match binding.button {
+                InputButton::Key(_) => focus.keyboard,
+                InputButton::Mouse(_) => focus.mouse,
+            };
+            let (pressed, just_pressed, just_released, own_key) = match binding.button {
+                InputButton::Key(key) => (
+                    keys.pressed(key),
+                    keys.just_pressed(key),
+                    keys.just_released(key),
+                    Some(key),
+                ),
+                InputButton::Mouse(button) => (
+                    mouse.pressed(button),
+                    mouse.just_pressed(button),
+                    mouse.just_released(button),
+                    None,
+                ),
+            };
+            let held = Modifiers::held(&keys, own_key);
+            if pressed && !blocked && held.contains(binding.modifiers()) {
+                state.pressed.insert(action);
+            }
+            if just_pressed && !blocked && held == binding.modifiers() {
+                state.just_pressed.insert(action);
+            }
+            if just_released {
+                state.just_released.insert(action);
+            }
+        }
+    }
+}
+
+/// Letto a fine frame, vale per l'input del frame successivo (imgui non espone un punto
+/// d'aggancio prima del suo `new_frame`).
+fn update_ui_focus(
+    mut context: NonSendMut<ImguiContext>,
+    mut focus: ResMut<UiFocus>,
+    viewports: Res<crate::layout::Viewports>,
+) {
+    let io = context.ui().io();
+    // Un pannello viewport è di imgui, ma il mouse sopra va al mondo.
+    let mouse = io.want_capture_mouse && viewports.hovered.is_none();
+    let keyboard = io.want_capture_keyboard || io.want_text_input;
+    if focus.mouse != mouse || focus.keyboard != keyboard {
+        *focus = UiFocus { mouse, keyboard };
+    }
+}
+
+fn input_settings_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut bindings: ResMut<InputBindings>,
+    mut settings: ResMut<InputSettingsState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+) {
+    let settings = &mut *settings;
+    let ui = context.ui();
+
+    // Cattura del nuovo binding: un modificatore da solo vale quando viene rilasciato.
+    if let Some((action, index)) = settings.capture {
+        let captured = if keys.just_pressed(KeyCode::Escape) {
+            settings.capture = None;
+            None
+        } else if let Some(&key) = keys.get_just_pressed().find(|k| !is_modifier(**k)) {
+            Some(Binding::new(InputButton::Key(key), Modifiers::held(&keys, None)))
+        } else if let Some(&key) = keys.get_just_released().find(|k| is_modifier(**k)) {
+            Some(Binding::key(key))
+        } else if ui.io().want_capture_mouse {
+            None
+        } else {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|&button| Binding::new(InputButton::Mouse(button), Modifiers::held(&keys, None)))
+        };
+        if let Some(binding) = captured {
+            let list = bindings.bindings.entry(action).or_default();
+            match index {
+                Some(i) if i < list.len() => list[i] = binding,
+                _ => list.push(binding),
+            }
+            settings.capture = None;
+        }
+    }
+
+    ui.window("Input Bindings")
+        .size([380.0, 520.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            if ui.button("Save") {
+                settings.status = match bindings.save(Path::new(CONFIG_PATH)) {
+                    Ok(()) => format!("Saved to {}", CONFIG_PATH),
+                    Err(e) => format!("Save failed: {}", e),
+                };
+            }
+            ui.same_line();
+            if ui.button("Reload") {
+                settings.status = match InputBindings::load(Path::new(CONFIG_PATH)) {
+                    Ok(loaded) => {
+                        *bindings = loaded;
+                        format!("Loaded {}", CONFIG_PATH)
+                    }
+                    Err(e) => format!("Load failed: {}", e),
+                };
+            }
+            ui.same_line();
+            if ui.button("Reset to defaults") {
+                *bindings = InputBindings::default();
+            }
+            if !settings.status.is_empty() {
+                ui.text_wrapped(&settings.status);
+            }
+
+            let conflicts = bindings.conflicts();
+            for (a, b, binding) in &conflicts {
+                ui.text_colored(
+                    [1.0, 0.4, 0.3, 1.0],
+                    format!("{}: {} / {}", binding.label(), a.label(), b.label()),
+                );
+            }
+            if let Some((action, _)) = settings.capture {
+                ui.text_colored(
+                    [1.0, 0.9, 0.3, 1.0],
+                    format!("{}: press a key or mouse button (Esc cancels)", action.label()),
+                );
+            }
+
+            let mut removed = None;
+            for input_context in InputContext::ALL {
+                if !ui.collapsing_header(input_context.label(), imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    continue;
+                }
+                for action in Action::ALL {
+                    if action.context() != input_context {
+                        continue;
+                    }
+                    let _id = ui.push_id(format!("{:?}", action));
+                    let conflicted = conflicts.iter().any(|(a, b, _)| *a == action || *b == action);
+                    if conflicted {
+                        ui.text_colored([1.0, 0.4, 0.3, 1.0], action.label());
+                    } else {
+                        ui.text(action.label());
+                    }
+                    ui.same_line_with_pos(190.0);
+                    for (i, binding) in bindings.get(action).iter().enumerate() {
+                        let _binding_id = ui.push_id_usize(i);
+                        if ui.small_button(binding.label()) {
+                            settings.capture = Some((action, Some(i)));
+                        }
+                        if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+                            removed = Some((action, i));
+                        }
+                        ui.same_line();
+                    }
+                    if ui.small_button("+") {
+                        settings.capture = Some((action, None));
+                    }
+                }
+            }
+            ui.text_disabled("Click a binding to change it, right-click to remove it");
+            if let Some((action, i)) = removed {
+                if let Some(list) = bindings.bindings.get_mut(&action) {
+                    list.remove(i);
+                }
+            }
+        });
+}
+
+pub struct InputPlugin;
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        let bindings = match InputBindings::load(Path::new(CONFIG_PATH)) {
+            Ok(bindings) => bindings,
+            Err(e) => {
+                info!("Input: uso i binding di default ({})", e);
+                InputBindings::default()
+            }
+        };
+        for (a, b, binding) in bindings.conflicts() {
+            warn!("Input: {} assegnato sia a {:?} che a {:?}", binding.label(), a, b);
+        }
+        app.insert_resource(bindings)
+            .init_resource::<ActionState>()
+            .init_resource::<InputSettingsState>()
+            .init_resource::<UiFocus>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem))
+            .add_systems(Update, input_settings_ui)
+            .add_systems(PostUpdate, update_ui_focus);
+    }
+}
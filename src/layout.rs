@@ -0,0 +1,664 @@
+use bevy::prelude::*;
+use bevy::render::camera::{RenderTarget, ScalingMode};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::window::PrimaryWindow;
+use bevy_mod_imgui::prelude::*;
+use imgui::sys;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::f32::consts::FRAC_PI_2;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::camera::{PanOrbitSettings, PanOrbitState};
+use crate::pp::PostProcessSettings;
+use crate::preferences::Preferences;
+use crate::retrocamera::{RetroRenderTarget, RetroScreen};
+use crate::ron_file;
+
+const LAYOUT_DIR: &str = "config/layouts";
+/// Pannello con la vista principale (la retro texture della camera attiva).
+pub const MAIN_VIEWPORT: &str = "Viewport";
+
+#[derive(Clone, Copy)]
+enum DockArea {
+    Center,
+    Left,
+    RightTop,
+    RightBottom,
+    Bottom,
+}
+
+/// Dove finiscono i pannelli nel layout di default; quelli non elencati restano flottanti.
+const DEFAULT_DOCKING: &[(&str, DockArea)] = &[
+    (MAIN_VIEWPORT, DockArea::Center),
+    ("Asset Browser", DockArea::Left),
+    ("Entity List", DockArea::Left),
+    ("Alive entities", DockArea::Left),
+    ("Animation Clips", DockArea::Left),
+    ("Scene", DockArea::RightTop),
+    ("Transform Gizmo", DockArea::RightTop),
+    ("Cameras", DockArea::RightTop),
+    ("Skeleton", DockArea::RightTop),
+    ("Chess", DockArea::RightTop),
+    ("Tactics", DockArea::RightTop),
+    ("Shader Editor", DockArea::RightBottom),
+    ("Post-process Presets", DockArea::RightBottom),
+    ("Palette Import", DockArea::RightBottom),
+    ("Post-process Stack", DockArea::RightBottom),
+    ("Light Color", DockArea::RightBottom),
+    ("Camera Controls", DockArea::RightBottom),
+    ("Views", DockArea::RightBottom),
+    ("Input Bindings", DockArea::RightBottom),
+    ("Preferences", DockArea::RightBottom),
+    ("Toolbar", DockArea::Bottom),
+    ("Pose", DockArea::Bottom),
+    ("IK Rig", DockArea::Bottom),
+    ("Character IK", DockArea::Bottom),
+    ("Ragdoll", DockArea::Bottom),
+    ("Third Person Camera", DockArea::Bottom),
+];
+
+/// Tipo di un pannello viewport aggiuntivo.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ViewportKind {
+    /// Prospettica a piena risoluzione, senza post-process.
+    Perspective,
+    /// Ortogonale dall'alto: pan e zoom, niente orbita.
+    Top,
+    /// Alla risoluzione della retro texture, con il post-process della camera dell'editor.
+    Retro,
+}
+
+impl ViewportKind {
+    const ALL: [ViewportKind; 3] = [ViewportKind::Perspective, ViewportKind::Top, ViewportKind::Retro];
+
+    fn label(self) -> &'static str {
+        match self {
+            ViewportKind::Perspective => "Perspective",
+            ViewportKind::Top => "Top (ortho)",
+            ViewportKind::Retro => "Retro preview",
+        }
+    }
+}
+
+/// Camera di un viewport aggiuntivo: renderizza in una texture grande quanto il pannello.
+#[derive(Component)]
+pub struct ViewportCamera {
+    pub kind: ViewportKind,
+    slot: u32,
+    texture: Option<imgui::TextureId>,
+    size: UVec2,
+    wanted: UVec2,
+}
+
+impl ViewportCamera {
+    fn title(&self) -> String {
+        // Il titolo visibile può ripetersi, l'id dopo ### no (è la chiave nell'ini).
+        format!("{} {}###Viewport{}", self.kind.label(), self.slot, self.slot)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HoveredViewport {
+    Main,
+    Extra(Entity),
+}
+
+/// Stato dei pannelli viewport, letto da camera, cursore e routing dell'input.
+#[derive(Resource)]
+pub struct Viewports {
+    /// Con il pannello aperto la vista principale non è più lo sprite a tutto schermo.
+    pub main_open: bool,
+    /// Dove è disegnata la vista principale in questo frame (coordinate logiche della finestra).
+    pub main_rect: Option<Rect>,
+    /// Viewport sotto il mouse: riceve pan/orbita/zoom anche se imgui ha il mouse.
+    pub hovered: Option<HoveredViewport>,
+    main_texture: Option<(Handle<Image>, imgui::TextureId)>,
+}
+
+impl Default for Viewports {
+    fn default() -> Self {
+        Self {
+            main_open: true,
+            main_rect: None,
+            hovered: None,
+            main_texture: None,
+        }
+    }
+}
+
+/// File di layout: l'ini di imgui (docking e finestre) più i viewport aggiuntivi aperti.
+#[derive(Serialize, Deserialize)]
+struct LayoutFile {
+    #[serde(default)]
+    viewports: Vec<(u32, ViewportKind)>,
+    ini: String,
+}
+
+impl LayoutFile {
+    fn load(path: &Path) -> Result<Self, String> {
+        ron_file::load(path)
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        ron_file::save(self, path)
+    }
+}
+
+#[derive(Resource, Default)]
+struct EditorLayout {
+    /// Layout da caricare al prossimo `First`, fuori dal frame di imgui.
+    pending: Option<String>,
+    /// Ricostruisce il layout di default appena il dockspace esiste.
+    rebuild: bool,
+    saved: Vec<String>,
+    new_name: String,
+    status: String,
+}
+
+impl EditorLayout {
+    fn refresh(&mut self) {
+        self.saved = fs::read_dir(LAYOUT_DIR)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+                    .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.saved.sort();
+    }
+}
+
+fn layout_path(name: &str) -> PathBuf {
+    Path::new(LAYOUT_DIR).join(format!("{}.ron", name))
+}
+
+fn render_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x.max(1),
+            height: size.y.max(1),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Spawna la camera di un viewport aggiuntivo, partendo dall'orbita della camera dell'editor.
+fn spawn_viewport(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    kind: ViewportKind,
+    slot: u32,
+    from: Option<&PanOrbitState>,
+) {
+    let size = UVec2::new(320, 180);
+    let (center, radius, yaw, pitch) = from
+        .map(|state| (state.center, state.radius, state.yaw, state.pitch))
+        .unwrap_or((Vec3::ZERO, 10.0, 0.0, -0.4));
+    let (state, settings, projection) = match kind {
+        ViewportKind::Top => (
+            PanOrbitState {
+                center,
+                radius: 40.0,
+                pitch: -FRAC_PI_2,
+                ..default()
+            },
+            PanOrbitSettings {
+                orbit_sensitivity: 0.0,
+                ..default()
+            },
+            Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: (radius * 2.0).max(10.0),
+                },
+                ..OrthographicProjection::default_3d()
+            }),
+        ),
+        _ => (
+            PanOrbitState {
+                center,
+                radius,
+                yaw,
+                pitch,
+                ..default()
+            },
+            PanOrbitSettings::default(),
+            Projection::Perspective(PerspectiveProjection::default()),
+        ),
+    };
+    let mut camera = commands.spawn((
+        Name::new(format!("Viewport {}", slot)),
+        ViewportCamera {
+            kind,
+            slot,
+            texture: None,
+            size,
+            wanted: size,
+        },
+        Camera3d::default(),
+        Camera {
+            hdr: true,
+            order: -1,
+            target: RenderTarget::Image(images.add(render_image(size)).into()),
+            ..default()
+        },
+        projection,
+        state,
+        settings,
+    ));
+    if kind == ViewportKind::Retro {
+        camera.insert(PostProcessSettings::default());
+    }
+}
+
+/// Il docking va abilitato prima del primo `new_frame`; all'avvio si carica il layout salvato.
+fn setup_editor_layout(
+    _context: NonSendMut<ImguiContext>,
+    mut layout: ResMut<EditorLayout>,
+    prefs: Res<Preferences>,
+) {
+    // Il contesto imgui è quello corrente anche fuori dal frame.
+    unsafe {
+        let io = &mut *sys::igGetIO();
+        io.ConfigFlags |= sys::ImGuiConfigFlags_DockingEnable as sys::ImGuiConfigFlags;
+        // Trascinare dentro un viewport flottante muove la camera, non la finestra.
+        io.ConfigWindowsMoveFromTitleBarOnly = true;
+    }
+    layout.refresh();
+    if layout.saved.contains(&prefs.layout) {
+        layout.pending = Some(prefs.layout.clone());
+    } else {
+        layout.rebuild = true;
+    }
+}
+
+/// Gira in `First`: imgui vuole le impostazioni ini fuori da `new_frame`/`render`.
+#[allow(clippy::type_complexity)]
+fn apply_pending_layout(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut layout: ResMut<EditorLayout>,
+    mut images: ResMut<Assets<Image>>,
+    viewports: Query<(Entity, &ViewportCamera)>,
+    editor: Query<&PanOrbitState, Without<ViewportCamera>>,
+) {
+    let Some(name) = layout.pending.take() else {
+        return;
+    };
+    let file = match LayoutFile::load(&layout_path(&name)) {
+        Ok(file) => file,
+        Err(e) => {
+            layout.status = format!("Load failed: {}", e);
+            return;
+        }
+    };
+    for (entity, viewport) in viewports.iter() {
+        if let Some(texture) = viewport.texture {
+            context.unregister_bevy_texture(&texture);
+        }
+        commands.entity(entity).despawn();
+    }
+    let from = editor.iter().next();
+    for &(slot, kind) in &file.viewports {
+        spawn_viewport(&mut commands, &mut images, kind, slot, from);
+    }
+    unsafe {
+        sys::igLoadIniSettingsFromMemory(file.ini.as_ptr() as *const _, file.ini.len());
+    }
+    layout.rebuild = false;
+    layout.status = format!("Loaded {}", name);
+}
+
+fn save_layout(name: &str, viewports: &Query<&ViewportCamera>) -> Result<(), String> {
+    let ini = unsafe {
+        let mut size = 0;
+        let data = sys::igSaveIniSettingsToMemory(&mut size);
+        String::from_utf8_lossy(std::slice::from_raw_parts(data as *const u8, size)).into_owned()
+    };
+    let mut slots: Vec<_> = viewports
+        .iter()
+        .map(|viewport| (viewport.slot, viewport.kind))
+        .collect();
+    slots.sort_by_key(|(slot, _)| *slot);
+    LayoutFile {
+        viewports: slots,
+        ini,
+    }
+    .save(&layout_path(name))
+}
+
+/// Layout di default: browser a sinistra, ispettori a destra, strumenti in basso, vista al centro.
+fn build_default_layout(dockspace: sys::ImGuiID) {
+    unsafe {
+        let work_size = (*sys::igGetMainViewport()).WorkSize;
+        sys::igDockBuilderRemoveNode(dockspace);
+        sys::igDockBuilderAddNode(
+            dockspace,
+            sys::ImGuiDockNodeFlags_DockSpace
+                | sys::ImGuiDockNodeFlags_PassthruCentralNode as sys::ImGuiDockNodeFlags,
+        );
+        sys::igDockBuilderSetNodeSize(dockspace, work_size);
+        let none = std::ptr::null_mut();
+        let mut center = dockspace;
+        let left = sys::igDockBuilderSplitNode(center, sys::ImGuiDir_Left, 0.2, none, &mut center);
+        let mut right = sys::igDockBuilderSplitNode(center, sys::ImGuiDir_Right, 0.25, none, &mut center);
+        let bottom = sys::igDockBuilderSplitNode(center, sys::ImGuiDir_Down, 0.28, none, &mut center);
+        let right_bottom = sys::igDockBuilderSplitNode(right, sys::ImGuiDir_Down, 0.5, none, &mut right);
+        for &(window, area) in DEFAULT_DOCKING {
+            let node = match area {
+                DockArea::Center => center,
+                DockArea::Left => left,
+                DockArea::RightTop => right,
+                DockArea::RightBottom => right_bottom,
+                DockArea::Bottom => bottom,
+            };
+            let window = CString::new(window).expect("window names have no NUL");
+            sys::igDockBuilderDockWindow(window.as_ptr(), node);
+        }
+        sys::igDockBuilderFinish(dockspace);
+    }
+}
+
+/// Menu dei layout e dei viewport, poi il dockspace su tutta la finestra.
+#[allow(clippy::too_many_arguments)]
+fn editor_layout_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut layout: ResMut<EditorLayout>,
+    mut viewports: ResMut<Viewports>,
+    mut images: ResMut<Assets<Image>>,
+    cameras: Query<&ViewportCamera>,
+    editor: Query<&PanOrbitState, Without<ViewportCamera>>,
+    prefs: Res<Preferences>,
+) {
+    let layout = &mut *layout;
+    let ui = context.ui();
+    let mut added = None;
+    ui.main_menu_bar(|| {
+        ui.menu("Layout", || {
+            for name in layout.saved.clone() {
+                if ui.menu_item(&name) {
+                    layout.pending = Some(name);
+                }
+            }
+            if layout.saved.is_empty() {
+                ui.text_disabled("No saved layouts");
+            }
+            ui.separator();
+            ui.input_text("##layout name", &mut layout.new_name)
+                .hint(&prefs.layout)
+                .build();
+            ui.same_line();
+            if ui.button("Save") {
+                let name = if layout.new_name.trim().is_empty() {
+                    prefs.layout.clone()
+                } else {
+                    layout.new_name.clone()
+                };
+                layout.status = match ron_file::valid_name(&name) {
+                    Some(name) => match save_layout(name, &cameras) {
+                        Ok(()) => format!("Saved {}", layout_path(name).display()),
+                        Err(e) => format!("Save failed: {}", e),
+                    },
+                    None => format!("Invalid layout name: {:?}", name),
+                };
+                layout.refresh();
+            }
+            if !layout.saved.is_empty() {
+                ui.menu("Delete", || {
+                    for name in layout.saved.clone() {
+                        if ui.menu_item(&name) {
+                            if let Err(e) = fs::remove_file(layout_path(&name)) {
+                                layout.status = format!("Delete failed: {}", e);
+                            }
+                            layout.refresh();
+                        }
+                    }
+                });
+            }
+            if ui.menu_item("Reset to default") {
+                layout.rebuild = true;
+            }
+            ui.text_disabled(format!("\"{}\" is loaded at startup (Preferences)", prefs.layout));
+            if !layout.status.is_empty() {
+                ui.text_disabled(&layout.status);
+            }
+        });
+        ui.menu("Viewports", || {
+            ui.menu_item_config(MAIN_VIEWPORT)
+                .build_with_ref(&mut viewports.main_open);
+            ui.separator();
+            for kind in ViewportKind::ALL {
+                if ui.menu_item(format!("Add {}", kind.label())) {
+                    added = Some(kind);
+                }
+            }
+        });
+    });
+
+    let dockspace = unsafe {
+        sys::igDockSpaceOverViewport(
+            sys::igGetMainViewport(),
+            sys::ImGuiDockNodeFlags_PassthruCentralNode as sys::ImGuiDockNodeFlags,
+            std::ptr::null(),
+        )
+    };
+    if std::mem::take(&mut layout.rebuild) {
+        build_default_layout(dockspace);
+    }
+
+    if let Some(kind) = added {
+        // Primo slot libero, così i titoli (e le chiavi dell'ini) restano stabili.
+        let slot = (1..)
+            .find(|slot| cameras.iter().all(|viewport| viewport.slot != *slot))
+            .unwrap_or(1);
+        spawn_viewport(&mut commands, &mut images, kind, slot, editor.iter().next());
+    }
+}
+
+/// Disegna i pannelli viewport; le texture si ridimensionano sul frame dopo.
+#[allow(clippy::too_many_arguments)]
+fn viewports_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut viewports: ResMut<Viewports>,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<(Entity, &mut ViewportCamera, &mut Camera)>,
+    retro: Res<RetroRenderTarget>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    // La retro texture cambia handle quando cambia risoluzione.
+    if viewports.main_texture.as_ref().map(|(handle, _)| handle) != retro.handle.as_ref() {
+        if let Some((_, texture)) = viewports.main_texture.take() {
+            context.unregister_bevy_texture(&texture);
+        }
+        if let Some(handle) = retro.handle.clone() {
+            let texture = context.register_bevy_texture(handle.clone());
+            viewports.main_texture = Some((handle, texture));
+        }
+    }
+    let scale = windows.single().map_or(1.0, |window| window.scale_factor());
+    let retro_size = Vec2::new(retro.width as f32, retro.height as f32);
+    let viewports = &mut *viewports;
+    viewports.main_rect = None;
+    viewports.hovered = None;
+    let mut closed = Vec::new();
+    {
+        let ui = context.ui();
+        let flags = imgui::WindowFlags::NO_SCROLLBAR
+            | imgui::WindowFlags::NO_SCROLL_WITH_MOUSE
+            | imgui::WindowFlags::NO_COLLAPSE;
+        let _padding = ui.push_style_var(imgui::StyleVar::WindowPadding([0.0, 0.0]));
+
+        if viewports.main_open {
+            let mut open = true;
+            ui.window(MAIN_VIEWPORT)
+                .size([960.0, 540.0], imgui::Condition::FirstUseEver)
+                .opened(&mut open)
+                .flags(flags)
+                .build(|| {
+                    let Some((_, texture)) = viewports.main_texture else {
+                        return;
+                    };
+                    // La retro texture ha dimensione fissa: la si adatta al pannello.
+                    let avail = Vec2::from(ui.content_region_avail());
+                    let fit = (avail / retro_size).min_element().max(0.0);
+                    let size = retro_size * fit;
+                    let origin = Vec2::from(ui.cursor_screen_pos()) + (avail - size) * 0.5;
+                    ui.set_cursor_screen_pos(origin.to_array());
+                    imgui::Image::new(texture, size.to_array()).build(ui);
+                    viewports.main_rect = Some(Rect::from_corners(origin, origin + size));
+                    if ui.is_item_hovered() {
+                        viewports.hovered = Some(HoveredViewport::Main);
+                    }
+                });
+            viewports.main_open = open;
+        }
+
+        for (entity, mut viewport, _) in cameras.iter_mut() {
+            let mut open = true;
+            ui.window(viewport.title())
+                .size([480.0, 320.0], imgui::Condition::FirstUseEver)
+                .opened(&mut open)
+                .flags(flags)
+                .build(|| {
+                    let avail = Vec2::from(ui.content_region_avail()).max(Vec2::ONE);
+                    let shown = match viewport.kind {
+                        ViewportKind::Retro => {
+                            viewport.wanted = UVec2::new(retro.width, retro.height).max(UVec2::ONE);
+                            retro_size * (avail / retro_size).min_element()
+                        }
+                        _ => {
+                            viewport.wanted = (avail * scale).as_uvec2().max(UVec2::ONE);
+                            avail
+                        }
+                    };
+                    let origin = Vec2::from(ui.cursor_screen_pos()) + (avail - shown) * 0.5;
+                    ui.set_cursor_screen_pos(origin.to_array());
+                    if let Some(texture) = viewport.texture {
+                        imgui::Image::new(texture, shown.to_array()).build(ui);
+                    } else {
+                        ui.dummy(shown.to_array());
+                    }
+                    if ui.is_item_hovered() {
+                        viewports.hovered = Some(HoveredViewport::Extra(entity));
+                    }
+                });
+            if !open {
+                closed.push(entity);
+            }
+        }
+    }
+
+    for (entity, mut viewport, mut camera) in cameras.iter_mut() {
+        if closed.contains(&entity) {
+            if let Some(texture) = viewport.texture.take() {
+                context.unregister_bevy_texture(&texture);
+            }
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if viewport.texture.is_some() && viewport.size == viewport.wanted {
+            continue;
+        }
+        // Al primo frame si registra il target della camera, poi una texture nuova a ogni
+        // ridimensionamento del pannello.
+        let handle = if viewport.size == viewport.wanted {
+            match &camera.target {
+                RenderTarget::Image(target) => target.handle.clone(),
+                _ => continue,
+            }
+        } else {
+            let handle = images.add(render_image(viewport.wanted));
+            camera.target = RenderTarget::Image(handle.clone().into());
+            viewport.size = viewport.wanted;
+            handle
+        };
+        if let Some(texture) = viewport.texture.take() {
+            context.unregister_bevy_texture(&texture);
+        }
+        viewport.texture = Some(context.register_bevy_texture(handle));
+    }
+}
+
+/// Con il salvataggio automatico, il layout di avvio segue i pannelli spostati o agganciati
+/// (imgui lo segnala qualche secondo dopo l'ultima modifica).
+fn autosave_layout(
+    mut context: NonSendMut<ImguiContext>,
+    mut layout: ResMut<EditorLayout>,
+    prefs: Res<Preferences>,
+    cameras: Query<&ViewportCamera>,
+) {
+    if !prefs.autosave_layout || !context.ui().io().want_save_ini_settings {
+        return;
+    }
+    let Some(name) = ron_file::valid_name(&prefs.layout) else {
+        return;
+    };
+    if let Err(e) = save_layout(name, &cameras) {
+        layout.status = format!("Save failed: {}", e);
+    }
+    if !layout.saved.iter().any(|saved| saved == name) {
+        layout.refresh();
+    }
+}
+
+/// Il viewport retro mostra il post-process della camera dell'editor.
+#[allow(clippy::type_complexity)]
+fn sync_viewport_post_process(
+    editor: Query<&PostProcessSettings, (With<PanOrbitState>, Without<ViewportCamera>)>,
+    mut viewports: Query<(&ViewportCamera, &mut PostProcessSettings)>,
+) {
+    let Some(source) = editor.iter().next() else {
+        return;
+    };
+    for (viewport, mut settings) in viewports.iter_mut() {
+        *settings = *source;
+        settings.window_size = viewport.size.as_vec2();
+    }
+}
+
+/// Con la vista nel pannello lo sprite a tutto schermo sparisce.
+fn sync_retro_screen(viewports: Res<Viewports>, mut screens: Query<&mut Visibility, With<RetroScreen>>) {
+    let visibility = if viewports.main_open {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut screen in screens.iter_mut() {
+        screen.set_if_neq(visibility);
+    }
+}
+
+pub struct EditorLayoutPlugin;
+impl Plugin for EditorLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorLayout>()
+            .init_resource::<Viewports>()
+            .add_systems(Startup, setup_editor_layout)
+            .add_systems(First, apply_pending_layout)
+            .add_systems(
+                Update,
+                (
+                    editor_layout_ui,
+                    viewports_ui,
+                    autosave_layout,
+                    sync_viewport_post_process,
+                    sync_retro_screen,
+                )
+                    .chain(),
+            );
+    }
+}
@@ -1,18 +1,41 @@
+mod animation;
+mod animation_browser;
 mod assets;
 mod cursor;
 mod character;
 mod pp;
-//mod jump_flood;
+mod pp_presets;
+mod pp_effects;
+mod pp_stack;
 mod chess;
+mod chess_ai;
+mod chess_rules;
+mod pgn;
+mod play_mode;
+mod ragdoll;
+mod save_load;
+mod shader_params;
+mod uci;
 mod transform;
 use bevy::color::palettes::css::*;
+mod blue_noise;
 mod camera;
+mod camera_manager;
 mod character_controller;
+mod character_ik;
 mod ground;
+mod gltf_export;
 mod ik;
-mod pastel;
+mod ik_editor;
+mod input;
+mod layout;
+mod palette;
+mod pose_editor;
+mod preferences;
 mod retrocamera;
-mod simple_outline;
+mod ron_file;
+mod skeleton;
+mod tactics;
 mod ui;
 use bevy::image::Image;
 use bevy::image::*;
@@ -49,19 +72,42 @@ fn main() {
         .add_plugins(ui::UiPlugin)
         .add_systems(
             Update,
-            camera::pan_orbit_camera.run_if(any_with_component::<camera::PanOrbitState>),
+            camera::pan_orbit_camera
+                .run_if(any_with_component::<camera::PanOrbitState>)
+                .in_set(play_mode::EditSet),
         )
         .add_plugins(RapierPickingPlugin)
         .add_systems(Startup, camera::spawn_camera)
+        .add_plugins(camera::CameraViewPlugin)
         .add_plugins(pp::PostProcessPlugin)
+        .add_plugins(pp_presets::PostProcessPresetsPlugin)
+        .add_plugins(palette::PalettePlugin)
+        .add_plugins(pp_stack::PostProcessStackPlugin)
         .add_plugins(RemotePlugin::default())
         .add_plugins(assets::AssetsPlugin)
         .add_plugins(RemoteHttpPlugin::default())
+        .add_plugins(board::BoardPlugin)
         .add_plugins(chess::ChessPlugin)
+        .add_plugins(tactics::TacticsPlugin)
         .add_plugins((OutlinePlugin, AutoGenerateOutlineNormalsPlugin::default()))
         .add_plugins(transform::TransformGizmoPlugin)
         .add_plugins(MeshPickingPlugin)
         .add_plugins(character::CharacterPlugin)
+        .add_plugins(animation::AnimationControllerPlugin)
+        .add_plugins(animation_browser::AnimationBrowserPlugin)
+        .add_plugins(character_controller::ControllerPlugin)
+        .add_plugins(play_mode::PlayModePlugin)
+        .add_plugins(ragdoll::RagdollPlugin)
+        .add_plugins(skeleton::SkeletonPlugin)
+        .add_plugins(ik::InverseKinematicsPlugin)
+        .add_plugins(character_ik::CharacterIkPlugin)
+        .add_plugins(ik_editor::IkEditorPlugin)
+        .add_plugins(save_load::SavePlugin)
+        .add_plugins(pose_editor::PoseEditorPlugin)
+        .add_plugins(camera_manager::CameraManagerPlugin)
+        .add_plugins(input::InputPlugin)
+        .add_plugins(layout::EditorLayoutPlugin)
+        .add_plugins(preferences::PreferencesPlugin)
         .run();
 }
 
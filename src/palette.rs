@@ -0,0 +1,508 @@
+use bevy::image::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::TextureFormat;
+use bevy_mod_imgui::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use crate::pp::PostProcessSettings;
+
+/// Posti nella palette dello shader.
+pub const MAX_COLORS: usize = 32;
+/// Oltre questi pixel l'immagine di riferimento si campiona a passo fisso.
+const MAX_SAMPLES: usize = 65536;
+const KMEANS_ITERATIONS: usize = 12;
+
+/// Colori in sRGB 0..1, come li usano i preset e il color picker.
+type Rgb = [f32; 3];
+
+fn rgb_from_bytes(r: u8, g: u8, b: u8) -> Rgb {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0]
+}
+
+fn parse_channel(token: &str, line: usize) -> Result<u8, String> {
+    token
+        .parse::<u8>()
+        .map_err(|_| format!("line {}: invalid channel {:?}", line, token))
+}
+
+/// GIMP `.gpl`: intestazione, `Name:`/`Columns:`, commenti `#`, poi `R G B nome`.
+pub fn parse_gpl(text: &str) -> Result<Vec<Rgb>, String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == "GIMP Palette" => {}
+        _ => return Err("missing \"GIMP Palette\" header".to_string()),
+    }
+    let mut colors = Vec::new();
+    for (i, line) in lines {
+        let line = line.trim();
+        // `Name:`/`Columns:` solo prima dei colori: i `:` possono stare anche nei nomi dei colori.
+        let header_key = line
+            .split_once(':')
+            .is_some_and(|(key, _)| !key.contains(char::is_whitespace));
+        if line.is_empty() || line.starts_with('#') || (colors.is_empty() && header_key) {
+            continue;
+        }
+        let channels: Vec<&str> = line.split_whitespace().take(3).collect();
+        if channels.len() < 3 {
+            return Err(format!("line {}: expected R G B", i + 1));
+        }
+        colors.push(rgb_from_bytes(
+            parse_channel(channels[0], i + 1)?,
+            parse_channel(channels[1], i + 1)?,
+            parse_channel(channels[2], i + 1)?,
+        ));
+    }
+    Ok(colors)
+}
+
+/// `.hex` di Lospec: un `RRGGBB` per riga, `#` opzionale.
+pub fn parse_hex(text: &str) -> Result<Vec<Rgb>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim().trim_start_matches('#')))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            let value = u32::from_str_radix(line, 16)
+                .ok()
+                .filter(|_| line.len() == 6)
+                .ok_or_else(|| format!("line {}: expected RRGGBB, got {:?}", i + 1, line))?;
+            let [_, r, g, b] = value.to_be_bytes();
+            Ok(rgb_from_bytes(r, g, b))
+        })
+        .collect()
+}
+
+/// JASC `.pal` (quello esportato da Lospec): `JASC-PAL`, versione, numero di colori, `R G B`.
+pub fn parse_pal(text: &str) -> Result<Vec<Rgb>, String> {
+    let mut lines = text.lines().map(str::trim).enumerate();
+    if lines.next().map(|(_, line)| line) != Some("JASC-PAL") {
+        return Err("missing \"JASC-PAL\" header".to_string());
+    }
+    lines.next();
+    let count: usize = lines
+        .next()
+        .and_then(|(_, line)| line.parse().ok())
+        .ok_or("missing color count")?;
+    let colors = lines
+        .filter(|(_, line)| !line.is_empty())
+        .take(count)
+        .map(|(i, line)| {
+            let channels: Vec<&str> = line.split_whitespace().collect();
+            if channels.len() < 3 {
+                return Err(format!("line {}: expected R G B", i + 1));
+            }
+            Ok(rgb_from_bytes(
+                parse_channel(channels[0], i + 1)?,
+                parse_channel(channels[1], i + 1)?,
+                parse_channel(channels[2], i + 1)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if colors.len() < count {
+        return Err(format!("expected {} colors, found {}", count, colors.len()));
+    }
+    Ok(colors)
+}
+
+/// Pixel RGB (sRGB) di un'immagine, saltando quelli trasparenti.
+fn load_pixels(path: &Path) -> Result<Vec<Rgb>, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension(&extension),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::MAIN_WORLD,
+    )
+    .map_err(|e| e.to_string())?;
+    let image = if image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb {
+        image
+    } else {
+        image
+            .convert(TextureFormat::Rgba8UnormSrgb)
+            .ok_or("unsupported pixel format")?
+    };
+    let data = image.data.ok_or("image has no pixel data")?;
+    Ok(data
+        .chunks_exact(4)
+        .filter(|pixel| pixel[3] >= 128)
+        .map(|pixel| rgb_from_bytes(pixel[0], pixel[1], pixel[2]))
+        .collect())
+}
+
+/// Striscia di campioni (PNG): i colori distinti nell'ordine in cui compaiono, righe dall'alto.
+/// Funziona sia con un pixel per colore sia con campioni ingranditi.
+pub fn parse_swatch_image(path: &Path) -> Result<Vec<Rgb>, String> {
+    distinct_colors(load_pixels(path)?)
+}
+
+fn distinct_colors(pixels: Vec<Rgb>) -> Result<Vec<Rgb>, String> {
+    let mut colors: Vec<Rgb> = Vec::new();
+    for pixel in pixels {
+        if !colors.contains(&pixel) {
+            if colors.len() == MAX_COLORS {
+                return Err(format!(
+                    "more than {} colors: use quantization instead",
+                    MAX_COLORS
+                ));
+            }
+            colors.push(pixel);
+        }
+    }
+    Ok(colors)
+}
+
+fn mean(pixels: &[Rgb]) -> Rgb {
+    let sum = pixels.iter().fold([0.0; 3], |acc, p| {
+        [acc[0] + p[0], acc[1] + p[1], acc[2] + p[2]]
+    });
+    let n = pixels.len().max(1) as f32;
+    [sum[0] / n, sum[1] / n, sum[2] / n]
+}
+
+fn distance2(a: &Rgb, b: &Rgb) -> f32 {
+    (0..3).map(|c| (a[c] - b[c]) * (a[c] - b[c])).sum()
+}
+
+/// Median cut: si divide sempre la scatola con il canale più esteso, a metà dei suoi pixel.
+pub fn median_cut(pixels: &[Rgb], count: usize) -> Vec<Rgb> {
+    let mut boxes: Vec<Vec<Rgb>> = vec![pixels.to_vec()];
+    while boxes.len() < count {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let (channel, range) = (0..3)
+                    .map(|c| {
+                        let (lo, hi) = b.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+                            (lo.min(p[c]), hi.max(p[c]))
+                        });
+                        (c, hi - lo)
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap_or((0, 0.0));
+                (i, channel, range)
+            })
+            .filter(|(_, _, range)| *range > 0.0)
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((index, channel, _)) = widest else {
+            break;
+        };
+        let mut split = boxes.swap_remove(index);
+        split.sort_by(|a, b| a[channel].total_cmp(&b[channel]));
+        let upper = split.split_off(split.len() / 2);
+        boxes.push(split);
+        boxes.push(upper);
+    }
+    boxes.iter().map(|b| mean(b)).collect()
+}
+
+/// K-means (Lloyd) partendo dal median cut, così il risultato è deterministico.
+pub fn kmeans(pixels: &[Rgb], count: usize) -> Vec<Rgb> {
+    let mut centers = median_cut(pixels, count);
+    let mut assignment = vec![0; pixels.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (pixel, assigned) in pixels.iter().zip(assignment.iter_mut()) {
+            let nearest = centers
+                .iter()
+                .enumerate()
+                .min_by(|a, b| distance2(pixel, a.1).total_cmp(&distance2(pixel, b.1)))
+                .map_or(0, |(i, _)| i);
+            changed |= nearest != *assigned;
+            *assigned = nearest;
+        }
+        for (i, center) in centers.iter_mut().enumerate() {
+            let members: Vec<Rgb> = pixels
+                .iter()
+                .zip(&assignment)
+                .filter(|(_, a)| **a == i)
+                .map(|(p, _)| *p)
+                .collect();
+            // Un centro rimasto senza pixel tiene la posizione di prima.
+            if !members.is_empty() {
+                *center = mean(&members);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    centers
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum QuantizeMethod {
+    MedianCut,
+    KMeans,
+}
+
+/// Palette di `count` colori ricavata da un'immagine di riferimento.
+pub fn quantize_image(path: &Path, count: usize, method: QuantizeMethod) -> Result<Vec<Rgb>, String> {
+    let pixels = load_pixels(path)?;
+    if pixels.is_empty() {
+        return Err("image has no opaque pixels".to_string());
+    }
+    let step = pixels.len().div_ceil(MAX_SAMPLES);
+    let samples: Vec<Rgb> = pixels.into_iter().step_by(step).collect();
+    let count = count.clamp(1, MAX_COLORS);
+    Ok(match method {
+        QuantizeMethod::MedianCut => median_cut(&samples, count),
+        QuantizeMethod::KMeans => kmeans(&samples, count),
+    })
+}
+
+/// Legge un file di palette scegliendo il formato dall'estensione.
+pub fn import_palette(path: &Path) -> Result<Vec<Rgb>, String> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let colors = match extension.as_str() {
+        "png" => parse_swatch_image(path)?,
+        "gpl" | "hex" | "pal" => {
+            let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            match extension.as_str() {
+                "gpl" => parse_gpl(&text)?,
+                "hex" => parse_hex(&text)?,
+                _ => parse_pal(&text)?,
+            }
+        }
+        _ => return Err(format!("unknown palette format {:?}", extension)),
+    };
+    if colors.is_empty() {
+        return Err("palette is empty".to_string());
+    }
+    if colors.len() > MAX_COLORS {
+        warn!("Palette: {} colori, si tengono i primi {}", colors.len(), MAX_COLORS);
+    }
+    Ok(colors.into_iter().take(MAX_COLORS).collect())
+}
+
+/// Scrive i colori nella palette dello shader e la attiva.
+pub fn write_palette(settings: &mut PostProcessSettings, colors: &[Rgb]) {
+    let count = colors.len().min(MAX_COLORS);
+    for (i, slot) in settings.palette.iter_mut().enumerate() {
+        *slot = colors
+            .get(i)
+            .filter(|_| i < count)
+            .map_or(Vec4::ZERO, |[r, g, b]| Vec4::new(*r, *g, *b, 1.0));
+    }
+    settings.color_count_and_pad.x = count as u32;
+    settings.color_count_and_pad.y = 1;
+}
+
+#[derive(Resource)]
+struct PaletteImportState {
+    file: String,
+    reference: String,
+    colors: i32,
+    method: QuantizeMethod,
+    preview: Vec<Rgb>,
+    status: String,
+}
+
+impl Default for PaletteImportState {
+    fn default() -> Self {
+        Self {
+            file: String::new(),
+            reference: String::new(),
+            colors: 16,
+            method: QuantizeMethod::KMeans,
+            preview: Vec::new(),
+            status: String::new(),
+        }
+    }
+}
+
+fn palette_import_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut state: ResMut<PaletteImportState>,
+    mut settings: Query<&mut PostProcessSettings>,
+) {
+    let state = &mut *state;
+    let ui = context.ui();
+    ui.window("Palette Import")
+        .size([340.0, 320.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            ui.input_text("Palette file", &mut state.file)
+                .hint(".gpl / .hex / .pal / .png strip")
+                .build();
+            if ui.button("Import") {
+                match import_palette(Path::new(state.file.trim())) {
+                    Ok(colors) => {
+                        state.status = format!("Imported {} colors", colors.len());
+                        state.preview = colors;
+                    }
+                    Err(e) => state.status = format!("Import failed: {}", e),
+                }
+            }
+
+            ui.separator();
+            ui.input_text("Reference image", &mut state.reference)
+                .hint("assets/reference.png")
+                .build();
+            ui.slider("Colors", 2, MAX_COLORS as i32, &mut state.colors);
+            ui.radio_button("K-means", &mut state.method, QuantizeMethod::KMeans);
+            ui.same_line();
+            ui.radio_button("Median cut", &mut state.method, QuantizeMethod::MedianCut);
+            if ui.button("Generate") {
+                let path = Path::new(state.reference.trim());
+                match quantize_image(path, state.colors as usize, state.method) {
+                    Ok(colors) => {
+                        state.status = format!("Generated {} colors", colors.len());
+                        state.preview = colors;
+                    }
+                    Err(e) => state.status = format!("Quantization failed: {}", e),
+                }
+            }
+
+            if !state.preview.is_empty() {
+                ui.separator();
+                for (i, [r, g, b]) in state.preview.iter().enumerate() {
+                    if i % 8 != 0 {
+                        ui.same_line();
+                    }
+                    ui.color_button(format!("##swatch{}", i), [*r, *g, *b, 1.0]);
+                }
+                if ui.button("Apply to palette") {
+                    for mut settings in settings.iter_mut() {
+                        write_palette(&mut settings, &state.preview);
+                    }
+                    state.status = format!("Palette set to {} colors", state.preview.len());
+                }
+            }
+            if !state.status.is_empty() {
+                ui.text_wrapped(&state.status);
+            }
+        });
+}
+
+pub struct PalettePlugin;
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PaletteImportState>()
+            .add_systems(Update, palette_import_ui);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(colors: &[Rgb]) -> Vec<[u8; 3]> {
+        colors
+            .iter()
+            .map(|c| c.map(|channel| (channel * 255.0).round() as u8))
+            .collect()
+    }
+
+    #[test]
+    fn gpl_skips_header_keys_only_before_colors() {
+        let text = "GIMP Palette\nName: Test: palette\nColumns: 2\n# comment\n\n\
+                    255 0 0 red: warm\n0 128 255 sky: blue\n  1   2   3\n";
+        let colors = parse_gpl(text).unwrap();
+        assert_eq!(bytes(&colors), [[255, 0, 0], [0, 128, 255], [1, 2, 3]]);
+    }
+
+    #[test]
+    fn malformed_gpl_is_rejected() {
+        assert!(parse_gpl("Name: no header\n255 0 0\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n255 0\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n255 0 256\n").is_err());
+        assert!(parse_gpl("GIMP Palette\n1 2 3\nName: late\n").is_err());
+    }
+
+    #[test]
+    fn hex_accepts_optional_hash() {
+        let colors = parse_hex("ff0000\n#00FF80\n\n").unwrap();
+        assert_eq!(bytes(&colors), [[255, 0, 0], [0, 255, 128]]);
+    }
+
+    #[test]
+    fn malformed_hex_is_rejected() {
+        assert!(parse_hex("ff00\n").is_err());
+        assert!(parse_hex("ff00000\n").is_err());
+        assert!(parse_hex("gg0000\n").is_err());
+    }
+
+    #[test]
+    fn pal_reads_the_declared_count() {
+        let colors = parse_pal("JASC-PAL\n0100\n2\n255 0 0\n\n0 0 255\n9 9 9\n").unwrap();
+        assert_eq!(bytes(&colors), [[255, 0, 0], [0, 0, 255]]);
+    }
+
+    #[test]
+    fn malformed_pal_is_rejected() {
+        assert!(parse_pal("0100\n1\n0 0 0\n").is_err());
+        assert!(parse_pal("JASC-PAL\n0100\nmany\n0 0 0\n").is_err());
+        assert!(parse_pal("JASC-PAL\n0100\n2\n0 0 0\n").is_err());
+        assert!(parse_pal("JASC-PAL\n0100\n1\n0 0\n").is_err());
+    }
+
+    #[test]
+    fn swatches_keep_first_appearance_order() {
+        let red = [1.0, 0.0, 0.0];
+        let blue = [0.0, 0.0, 1.0];
+        let colors = distinct_colors(vec![blue, blue, red, blue, red]).unwrap();
+        assert_eq!(colors, [blue, red]);
+
+        let too_many = (0..=MAX_COLORS).map(|i| [i as f32 / 255.0, 0.0, 0.0]).collect();
+        assert!(distinct_colors(too_many).is_err());
+        assert!(parse_swatch_image(Path::new("missing/swatches.png")).is_err());
+    }
+
+    /// Due gruppi ben separati: entrambi i metodi devono trovare i loro centri.
+    fn clusters() -> Vec<Rgb> {
+        let mut pixels = Vec::new();
+        for i in 0..10 {
+            let jitter = i as f32 * 0.002;
+            pixels.push([0.1 + jitter, 0.1, 0.1]);
+            pixels.push([0.9 - jitter, 0.8, 0.7]);
+        }
+        pixels
+    }
+
+    fn sorted(mut colors: Vec<Rgb>) -> Vec<Rgb> {
+        colors.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        colors
+    }
+
+    #[test]
+    fn median_cut_splits_clusters() {
+        let colors = sorted(median_cut(&clusters(), 2));
+        assert_eq!(colors.len(), 2);
+        assert!(distance2(&colors[0], &[0.109, 0.1, 0.1]) < 1e-4);
+        assert!(distance2(&colors[1], &[0.891, 0.8, 0.7]) < 1e-4);
+    }
+
+    #[test]
+    fn median_cut_stops_when_colors_run_out() {
+        let pixels = vec![[0.5; 3]; 8];
+        assert_eq!(median_cut(&pixels, 4), vec![[0.5; 3]]);
+        assert_eq!(median_cut(&[[0.0; 3], [1.0; 3]], 8).len(), 2);
+    }
+
+    #[test]
+    fn kmeans_converges_on_clusters() {
+        let pixels = clusters();
+        let colors = sorted(kmeans(&pixels, 2));
+        assert_eq!(colors.len(), 2);
+        assert!(distance2(&colors[0], &[0.109, 0.1, 0.1]) < 1e-4);
+        assert!(distance2(&colors[1], &[0.891, 0.8, 0.7]) < 1e-4);
+        // Ogni pixel è più vicino al centro del suo gruppo.
+        for pixel in &pixels {
+            let own = if pixel[0] < 0.5 { &colors[0] } else { &colors[1] };
+            let other = if pixel[0] < 0.5 { &colors[1] } else { &colors[0] };
+            assert!(distance2(pixel, own) < distance2(pixel, other));
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::chess_rules::{FenError, GameOutcome, Move, PieceColor, Position, START_FEN};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    Fen(FenError),
+    IllegalMove { ply: usize, san: String },
+    Unterminated(&'static str),
+    /// A `)` without the `(` that opens its variation.
+    Unbalanced,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::Fen(e) => write!(f, "invalid FEN tag: {}", e),
+            PgnError::IllegalMove { ply, san } => {
+                write!(f, "illegal or unknown move '{}' at ply {}", san, ply + 1)
+            }
+            PgnError::Unterminated(what) => write!(f, "unterminated {}", what),
+            PgnError::Unbalanced => write!(f, "unbalanced ')' in movetext"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(e: FenError) -> Self {
+        PgnError::Fen(e)
+    }
+}
+
+/// A single game: tag pairs, the starting position and the moves played from it.
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub start: Position,
+    pub moves: Vec<Move>,
+}
+
+impl PgnGame {
+    pub fn new(start: Position) -> Self {
+        let mut tags: Vec<(String, String)> = [
+            ("Event", "Xirai game"),
+            ("Site", "Xirai"),
+            ("Date", "????.??.??"),
+            ("Round", "-"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", "*"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        if start.to_fen() != START_FEN {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), start.to_fen()));
+        }
+        Self {
+            tags,
+            start,
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Result token derived from the final position (or the `Result` tag if the game is unfinished).
+    pub fn result(&self) -> String {
+        let mut position = self.start.clone();
+        for &mv in &self.moves {
+            position.make_move(mv);
+        }
+        match position.outcome() {
+            Some(GameOutcome::Checkmate {
+                winner: PieceColor::White,
+            }) => "1-0".to_string(),
+            Some(GameOutcome::Checkmate {
+                winner: PieceColor::Black,
+            }) => "0-1".to_string(),
+            Some(_) => "1/2-1/2".to_string(),
+            None => self.tag("Result").unwrap_or("*").to_string(),
+        }
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let result = self.result();
+        let mut out = String::new();
+        for (key, value) in &self.tags {
+            let value = if key == "Result" { result.as_str() } else { value };
+            out.push_str(&format!("[{} \"{}\"]\n", key, value.replace('"', "\\\"")));
+        }
+        out.push('\n');
+
+        let mut tokens = Vec::with_capacity(self.moves.len() * 2 + 1);
+        let mut position = self.start.clone();
+        for (i, &mv) in self.moves.iter().enumerate() {
+            if position.side_to_move == PieceColor::White {
+                tokens.push(format!("{}.", position.fullmove_number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", position.fullmove_number));
+            }
+            tokens.push(position.to_san(mv));
+            position.make_move(mv);
+        }
+        tokens.push(result);
+
+        // Movetext lines are kept under 80 columns as the export format asks.
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len > 0 && line_len + 1 + token.len() > 79 {
+                out.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                out.push(' ');
+                line_len += 1;
+            }
+            line_len += token.len();
+            out.push_str(&token);
+        }
+        out.push('\n');
+        out
+    }
+
+    /// Parses the first game found in `text`. Comments, variations and NAGs are skipped.
+    pub fn parse(text: &str) -> Result<Self, PgnError> {
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+        let mut in_movetext = false;
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if !in_movetext && trimmed.starts_with('[') && trimmed.ends_with(']') {
+                let inner = &trimmed[1..trimmed.len() - 1];
+                if let Some((key, rest)) = inner.split_once(char::is_whitespace) {
+                    let value = rest.trim().trim_matches('"').replace("\\\"", "\"");
+                    tags.push((key.to_string(), value));
+                }
+                continue;
+            }
+            if trimmed.starts_with('%') {
+                continue;
+            }
+            if !trimmed.is_empty() {
+                in_movetext = true;
+            } else if in_movetext {
+                // A blank line after the movetext ends the first game.
+                break;
+            }
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+
+        let start = match tags.iter().find(|(k, _)| k == "FEN") {
+            Some((_, fen)) => Position::from_fen(fen)?,
+            None => Position::default(),
+        };
+
+        let mut game = PgnGame {
+            tags,
+            start: start.clone(),
+            moves: Vec::new(),
+        };
+        let mut position = start;
+        for token in tokenize_movetext(&movetext)? {
+            let Some(mv) = position.parse_san(&token) else {
+                return Err(PgnError::IllegalMove {
+                    ply: game.moves.len(),
+                    san: token,
+                });
+            };
+            position.make_move(mv);
+            game.moves.push(mv);
+        }
+        Ok(game)
+    }
+}
+
+/// Returns only the SAN move tokens of a movetext section.
+fn tokenize_movetext(movetext: &str) -> Result<Vec<String>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = movetext.chars();
+    let mut variation_depth = 0;
+
+    let flush = |current: &mut String, tokens: &mut Vec<String>| {
+        if !current.is_empty() {
+            tokens.push(std::mem::take(current));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                flush(&mut current, &mut tokens);
+                if !chars.by_ref().any(|c| c == '}') {
+                    return Err(PgnError::Unterminated("comment"));
+                }
+            }
+            ';' => {
+                flush(&mut current, &mut tokens);
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => {
+                flush(&mut current, &mut tokens);
+                variation_depth += 1;
+            }
+            ')' => {
+                flush(&mut current, &mut tokens);
+                if variation_depth == 0 {
+                    return Err(PgnError::Unbalanced);
+                }
+                variation_depth -= 1;
+            }
+            _ if variation_depth > 0 => {}
+            c if c.is_whitespace() => flush(&mut current, &mut tokens),
+            c => current.push(c),
+        }
+    }
+    flush(&mut current, &mut tokens);
+    if variation_depth > 0 {
+        return Err(PgnError::Unterminated("variation"));
+    }
+
+    Ok(tokens
+        .into_iter()
+        .filter_map(|token| {
+            if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") || token.starts_with('$') {
+                return None;
+            }
+            // Strip move numbers like "12." or "12..." glued to the move.
+            let token = match token.rfind('.') {
+                Some(i) => &token[i + 1..],
+                None => token.as_str(),
+            };
+            if token.is_empty() || token.chars().all(|c| c.is_ascii_digit()) {
+                None
+            } else {
+                Some(token.to_string())
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME: &str = r#"[Event "Test"]
+[Site "?"]
+[White "A"]
+[Black "B"]
+[Result "1-0"]
+
+1. e4 e5 2. Qh5 {threatening mate} Nc6 (2... g6 3. Qxe5+) 3. Bc4 Nf6?? $4
+4. Qxf7# 1-0
+"#;
+
+    #[test]
+    fn parses_movetext_with_comments_and_variations() {
+        let game = PgnGame::parse(GAME).unwrap();
+        assert_eq!(game.tag("White"), Some("A"));
+        let uci: Vec<String> = game.moves.iter().map(|mv| mv.to_uci()).collect();
+        assert_eq!(uci, ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7"]);
+        assert_eq!(game.result(), "1-0");
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let game = PgnGame::parse(GAME).unwrap();
+        let again = PgnGame::parse(&game.to_pgn()).unwrap();
+        assert_eq!(again.moves, game.moves);
+        assert_eq!(again.tags, game.tags);
+    }
+
+    #[test]
+    fn custom_start_position_round_trip() {
+        let start = Position::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").unwrap();
+        let mut game = PgnGame::new(start.clone());
+        game.moves.push(start.parse_san("Kd7").unwrap());
+        let text = game.to_pgn();
+        assert!(text.contains("12... Kd7"));
+        let again = PgnGame::parse(&text).unwrap();
+        assert_eq!(again.start, start);
+        assert_eq!(again.moves, game.moves);
+    }
+
+    #[test]
+    fn malformed_movetext_is_rejected() {
+        assert_eq!(PgnGame::parse("1. e4 ) e5").unwrap_err(), PgnError::Unbalanced);
+        assert_eq!(
+            PgnGame::parse("1. e4 (1. d4 e5").unwrap_err(),
+            PgnError::Unterminated("variation")
+        );
+        assert_eq!(
+            PgnGame::parse("1. e4 {open").unwrap_err(),
+            PgnError::Unterminated("comment")
+        );
+        assert!(matches!(
+            PgnGame::parse("1. e4 e4").unwrap_err(),
+            PgnError::IllegalMove { ply: 1, .. }
+        ));
+    }
+}
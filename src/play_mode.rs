@@ -0,0 +1,295 @@
+use crate::assets::SpawnedAsset;
+use crate::camera::EDITOR_CAMERA;
+use crate::camera_manager::SetActiveCamera;
+use crate::input::{Action, ActionState, InputBindings};
+use crate::character_controller::{CharacterController, THIRD_PERSON_CAMERA};
+use bevy::ecs::component::ComponentId;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy::scene::DynamicSceneBuilder;
+use bevy_mod_imgui::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Modalità dell'editor: in Edit la simulazione è ferma, in Play gira il gioco.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditorMode {
+    #[default]
+    Edit,
+    Play,
+}
+
+/// Set per i sistemi di gameplay (controller, script, ...): gira solo in Play.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlaySet;
+
+/// Set per i sistemi che modificano la scena (selezione, gizmo, spawn): gira solo in Edit.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EditSet;
+
+/// Stato del mondo salvato premendo Play, ripristinato con Stop.
+///
+/// Copre gli oggetti della scena (modelli spawnati, personaggio, corpi fisici) con tutti
+/// i loro discendenti: i componenti riflessi finiscono in una `DynamicScene`, così animazioni,
+/// ragdoll e giunti tornano com'erano. Il resto del mondo (scacchiera, mappa tattica, camere
+/// dell'editor) segue le sue risorse e non viene toccato.
+#[derive(Resource)]
+struct WorldSnapshot {
+    scene: DynamicScene,
+    physics: HashMap<Entity, PhysicsSnapshot>,
+    /// Componenti di ogni entità salvata: quelli aggiunti durante il Play vengono tolti.
+    components: HashMap<Entity, HashSet<ComponentId>>,
+    /// Entità radice presenti al momento dello snapshot: le altre vengono rimosse allo Stop.
+    roots: HashSet<Entity>,
+}
+
+/// Componenti Rapier senza `Reflect`, copiati a parte.
+#[derive(Default)]
+struct PhysicsSnapshot {
+    collider: Option<Collider>,
+    kinematic: Option<KinematicCharacterController>,
+    joint: Option<ImpulseJoint>,
+}
+
+impl PhysicsSnapshot {
+    fn take(entity: EntityRef) -> Option<Self> {
+        let snapshot = Self {
+            collider: entity.get::<Collider>().cloned(),
+            kinematic: entity.get::<KinematicCharacterController>().cloned(),
+            joint: entity.get::<ImpulseJoint>().copied(),
+        };
+        (snapshot.collider.is_some() || snapshot.kinematic.is_some() || snapshot.joint.is_some())
+            .then_some(snapshot)
+    }
+
+    /// Rimette solo ciò che manca: reinserire un collider ricreerebbe la forma in Rapier.
+    fn restore(&self, mut entity: EntityWorldMut) {
+        if let Some(collider) = &self.collider {
+            if !entity.contains::<Collider>() {
+                entity.insert(collider.clone());
+            }
+        }
+        if let Some(joint) = self.joint {
+            if !entity.contains::<ImpulseJoint>() {
+                entity.insert(joint);
+            }
+        }
+        // Il movimento richiesto nell'ultimo frame di Play non va applicato in Edit.
+        if let Some(kinematic) = &self.kinematic {
+            entity.insert(kinematic.clone());
+        }
+    }
+}
+
+/// Radici salvate dallo snapshot: ciò che la simulazione del Play può muovere o distruggere.
+fn is_scene_object(entity: EntityRef) -> bool {
+    entity.contains::<SpawnedAsset>()
+        || entity.contains::<CharacterController>()
+        || entity.contains::<RigidBody>()
+        || entity.contains::<Collider>()
+}
+
+fn descendants(world: &World, root: Entity) -> Vec<Entity> {
+    let mut found = Vec::new();
+    let mut stack = vec![root];
+    while let Some(entity) = stack.pop() {
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.iter());
+            found.extend(children.iter());
+        }
+    }
+    found
+}
+
+fn take_snapshot(world: &mut World) {
+    let roots: HashSet<Entity> = world
+        .query_filtered::<Entity, (With<Transform>, Without<ChildOf>)>()
+        .iter(world)
+        .collect();
+
+    let mut entities = Vec::new();
+    for &root in &roots {
+        if is_scene_object(world.entity(root)) {
+            entities.push(root);
+            entities.extend(descendants(world, root));
+        }
+    }
+    let physics = entities
+        .iter()
+        .filter_map(|&entity| Some((entity, PhysicsSnapshot::take(world.entity(entity))?)))
+        .collect();
+    let components = entities
+        .iter()
+        .map(|&entity| {
+            let ids = world.entity(entity).archetype().components().collect();
+            (entity, ids)
+        })
+        .collect();
+
+    // `SceneRoot` riapplicato farebbe rispawnare il glTF sopra la gerarchia già ripristinata.
+    let scene = DynamicSceneBuilder::from_world(world)
+        .deny_component::<SceneRoot>()
+        .extract_entities(entities.into_iter())
+        .build();
+
+    world.insert_resource(WorldSnapshot {
+        scene,
+        physics,
+        components,
+        roots,
+    });
+    info!("Play: snapshot salvato");
+}
+
+fn restore_snapshot(world: &mut World) {
+    let Some(snapshot) = world.remove_resource::<WorldSnapshot>() else {
+        return;
+    };
+
+    // Radici nuove, e figli nuovi sotto le entità salvate (collider del ragdoll, proiettili, ...).
+    // Le ossa staccate dal ragdoll sono radici ma salvate: tornano al loro posto con lo snapshot.
+    let roots: Vec<Entity> = world
+        .query_filtered::<Entity, (With<Transform>, Without<ChildOf>)>()
+        .iter(world)
+        .collect();
+    let mut spawned: Vec<Entity> = roots
+        .into_iter()
+        .filter(|root| {
+            !snapshot.roots.contains(root) && !snapshot.components.contains_key(root)
+        })
+        .collect();
+    for &entity in snapshot.components.keys() {
+        if world.get_entity(entity).is_ok() {
+            spawned.extend(
+                descendants(world, entity)
+                    .into_iter()
+                    .filter(|child| !snapshot.components.contains_key(child)),
+            );
+        }
+    }
+    for entity in spawned {
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn();
+        }
+    }
+
+    // Componenti aggiunti in Play (corpi rigidi e giunti del ragdoll, marker, ...).
+    for (&entity, saved) in &snapshot.components {
+        let Ok(mut entity) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        let added: Vec<ComponentId> = entity
+            .archetype()
+            .components()
+            .filter(|id| !saved.contains(id))
+            .collect();
+        for id in added {
+            entity.remove_by_id(id);
+        }
+    }
+
+    // Le entità ancora vive tengono il loro id; quelle despawnate durante il Play rinascono.
+    let mut entity_map: EntityHashMap<Entity> = snapshot
+        .components
+        .keys()
+        .filter(|&&entity| world.get_entity(entity).is_ok())
+        .map(|&entity| (entity, entity))
+        .collect();
+    if let Err(e) = snapshot.scene.write_to_world(world, &mut entity_map) {
+        warn!("Stop: ripristino incompleto: {e}");
+    }
+    for (saved, physics) in &snapshot.physics {
+        if let Some(entity) = entity_map.get(saved).and_then(|&e| world.get_entity_mut(e).ok()) {
+            physics.restore(entity);
+        }
+    }
+
+    // I corpi dinamici senza `Velocity` ripartono comunque da fermi.
+    let mut bodies = world.query::<(Entity, &RigidBody, Option<&Velocity>)>();
+    let resting: Vec<Entity> = bodies
+        .iter(world)
+        .filter(|(entity, body, velocity)| {
+            **body == RigidBody::Dynamic && velocity.is_none() && entity_map.contains_key(entity)
+        })
+        .map(|(entity, _, _)| entity)
+        .collect();
+    for entity in resting {
+        world.entity_mut(entity).insert(Velocity::zero());
+    }
+
+    info!("Stop: scena ripristinata");
+}
+
+/// Tiene la simulazione Rapier allineata alla modalità (anche per contesti creati dopo).
+fn sync_physics(
+    mode: Res<State<EditorMode>>,
+    mut configurations: Query<&mut RapierConfiguration>,
+) {
+    let active = *mode.get() == EditorMode::Play;
+    for mut configuration in configurations.iter_mut() {
+        if configuration.physics_pipeline_active != active {
+            configuration.physics_pipeline_active = active;
+        }
+    }
+}
+
+/// In Edit si guarda dalla camera dell'editor, in Play da quella in terza persona.
+fn sync_cameras(mode: Res<State<EditorMode>>, mut events: EventWriter<SetActiveCamera>) {
+    events.write(SetActiveCamera::named(match mode.get() {
+        EditorMode::Edit => EDITOR_CAMERA,
+        EditorMode::Play => THIRD_PERSON_CAMERA,
+    }));
+}
+
+fn toggle_play_shortcut(
+    actions: Res<ActionState>,
+    mode: Res<State<EditorMode>>,
+    mut next: ResMut<NextState<EditorMode>>,
+) {
+    if actions.just_pressed(Action::TogglePlay) {
+        next.set(match mode.get() {
+            EditorMode::Edit => EditorMode::Play,
+            EditorMode::Play => EditorMode::Edit,
+        });
+    }
+}
+
+fn toolbar_ui(
+    mut context: NonSendMut<ImguiContext>,
+    bindings: Res<InputBindings>,
+    mode: Res<State<EditorMode>>,
+    mut next: ResMut<NextState<EditorMode>>,
+) {
+    let shortcut = bindings.describe(Action::TogglePlay);
+    let ui = context.ui();
+    ui.window("Toolbar")
+        .size([220.0, 60.0], imgui::Condition::FirstUseEver)
+        .build(|| match mode.get() {
+            EditorMode::Edit => {
+                if ui.button(format!("Play ({})", shortcut)) {
+                    next.set(EditorMode::Play);
+                }
+                ui.same_line();
+                ui.text("Edit");
+            }
+            EditorMode::Play => {
+                if ui.button(format!("Stop ({})", shortcut)) {
+                    next.set(EditorMode::Edit);
+                }
+                ui.same_line();
+                ui.text_colored([0.3, 1.0, 0.3, 1.0], "Playing");
+            }
+        });
+}
+
+pub struct PlayModePlugin;
+impl Plugin for PlayModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<EditorMode>()
+            .configure_sets(Update, PlaySet.run_if(in_state(EditorMode::Play)))
+            .configure_sets(Update, EditSet.run_if(in_state(EditorMode::Edit)))
+            .add_systems(OnEnter(EditorMode::Play), (take_snapshot, sync_cameras))
+            .add_systems(OnEnter(EditorMode::Edit), (restore_snapshot, sync_cameras))
+            .add_systems(Update, (toggle_play_shortcut, sync_physics, toolbar_ui));
+    }
+}
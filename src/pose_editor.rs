@@ -0,0 +1,422 @@
+use crate::animation::{AnimationController, ClipPreview};
+use crate::gltf_export::{write_animation_glb, ExportAnimation, ExportNode, ExportTrack};
+use crate::input::{Action, InputBindings};
+use crate::ron_file;
+use crate::skeleton::skin_joints;
+use crate::transform::{scene_root, Selected};
+use bevy::prelude::*;
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy_mod_imgui::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Posa completa dello scheletro a un certo tempo: trasformazioni locali di tutte le ossa.
+struct PoseKey {
+    time: f32,
+    pose: HashMap<Entity, Transform>,
+}
+
+/// Stato del pannello "Pose": modello in posa, chiavi della timeline e file di destinazione.
+#[derive(Resource)]
+struct PoseEditorState {
+    root: Option<Entity>,
+    /// Player messo in pausa durante la posa, restituito alla macchina a stati all'uscita.
+    player: Option<Entity>,
+    bone: Option<Entity>,
+    filter: String,
+    time: f32,
+    fps: f32,
+    duration: f32,
+    keys: Vec<PoseKey>,
+    /// Posa di partenza, per "Reset pose".
+    rest: HashMap<Entity, Transform>,
+    clip_name: String,
+    status: String,
+}
+
+impl Default for PoseEditorState {
+    fn default() -> Self {
+        Self {
+            root: None,
+            player: None,
+            bone: None,
+            filter: String::new(),
+            time: 0.0,
+            fps: 30.0,
+            duration: 2.0,
+            keys: Vec::new(),
+            rest: HashMap::new(),
+            clip_name: "pose_clip".to_string(),
+            status: String::new(),
+        }
+    }
+}
+
+impl PoseEditorState {
+    fn posing(&self) -> bool {
+        self.player.is_some()
+    }
+
+    fn snap(&self, time: f32) -> f32 {
+        (time * self.fps).round() / self.fps
+    }
+
+    /// Posa interpolata tra le chiavi vicine a `time` (le chiavi sono ordinate per tempo).
+    fn sample(&self, time: f32) -> Option<HashMap<Entity, Transform>> {
+        let next = self.keys.iter().position(|k| k.time >= time);
+        let (a, b) = match next {
+            None => (self.keys.last()?, self.keys.last()?),
+            Some(0) => (&self.keys[0], &self.keys[0]),
+            Some(i) => (&self.keys[i - 1], &self.keys[i]),
+        };
+        let span = b.time - a.time;
+        let t = if span > 0.0 { (time - a.time) / span } else { 0.0 };
+        Some(
+            a.pose
+                .iter()
+                .map(|(&bone, from)| {
+                    let to = b.pose.get(&bone).unwrap_or(from);
+                    let transform = Transform {
+                        translation: from.translation.lerp(to.translation, t),
+                        rotation: from.rotation.slerp(to.rotation, t),
+                        scale: from.scale.lerp(to.scale, t),
+                    };
+                    (bone, transform)
+                })
+                .collect(),
+        )
+    }
+}
+
+enum PoseAction {
+    Enter(Entity),
+    Exit,
+    Key,
+    Seek(f32),
+    Remove(usize),
+    Reset,
+    Export,
+}
+
+/// Gerarchia e tracce da esportare. Per ogni osso si risale fino al primo antenato senza nome
+/// (la radice del mondo glTF): il percorso dei nomi resta quello del modello, così il clip
+/// esportato anima anche il modello originale.
+fn build_export(
+    state: &PoseEditorState,
+    parents: &Query<&ChildOf>,
+    names: &Query<&Name>,
+    transforms: &Query<&mut Transform>,
+) -> (Vec<ExportNode>, Vec<usize>, ExportAnimation) {
+    let mut nodes: Vec<ExportNode> = Vec::new();
+    let mut index: HashMap<Entity, usize> = HashMap::new();
+    let mut roots = Vec::new();
+
+    let mut bones: Vec<Entity> = state.keys.iter().flat_map(|k| k.pose.keys().copied()).collect();
+    bones.sort();
+    bones.dedup();
+
+    for &bone in &bones {
+        let mut chain = Vec::new();
+        let mut current = bone;
+        while names.contains(current) {
+            chain.push(current);
+            match parents.get(current) {
+                Ok(child_of) => current = child_of.parent(),
+                Err(_) => break,
+            }
+        }
+        // Dall'alto verso l'osso: ogni nodo nuovo si aggancia al precedente.
+        let mut parent: Option<usize> = None;
+        for &entity in chain.iter().rev() {
+            let node = *index.entry(entity).or_insert_with(|| {
+                nodes.push(ExportNode {
+                    name: names.get(entity).map(|n| n.to_string()).unwrap_or_default(),
+                    transform: state
+                        .rest
+                        .get(&entity)
+                        .or_else(|| transforms.get(entity).ok())
+                        .copied()
+                        .unwrap_or_default(),
+                    children: Vec::new(),
+                });
+                let node = nodes.len() - 1;
+                match parent {
+                    Some(p) => nodes[p].children.push(node),
+                    None => roots.push(node),
+                }
+                node
+            });
+            parent = Some(node);
+        }
+    }
+
+    let times: Vec<f32> = state.keys.iter().map(|k| k.time).collect();
+    let tracks = bones
+        .iter()
+        .filter_map(|bone| {
+            Some(ExportTrack {
+                node: *index.get(bone)?,
+                transforms: state
+                    .keys
+                    .iter()
+                    .map(|k| k.pose.get(bone).copied().unwrap_or_default())
+                    .collect(),
+            })
+        })
+        .collect();
+    let animation = ExportAnimation {
+        name: state.clip_name.clone(),
+        times,
+        tracks,
+    };
+    (nodes, roots, animation)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pose_editor_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut state: ResMut<PoseEditorState>,
+    bindings: Res<InputBindings>,
+    selected: Query<Entity, With<Selected>>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    skins: Query<&SkinnedMesh>,
+    mut players: Query<&mut AnimationPlayer>,
+    mut controllers: Query<&mut AnimationController>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let state = &mut *state;
+    let mut action = None;
+
+    // Durante la posa il modello resta quello scelto, anche selezionando le ossa.
+    if !state.posing() {
+        if let Some(entity) = selected.iter().next() {
+            let root = scene_root(entity, &parents);
+            if state.root != Some(root) {
+                state.root = Some(root);
+                state.bone = None;
+                state.keys.clear();
+            }
+        }
+    }
+
+    let ui = context.ui();
+    ui.window("Pose")
+        .size([330.0, 560.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let Some(root) = state.root.filter(|&r| transforms.contains(r)) else {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "Select a model");
+                return;
+            };
+            ui.text(format!("Model: {:?}", root));
+            let joints = skin_joints(root, &children, &skins);
+            if joints.is_empty() {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "No skeleton");
+                return;
+            }
+
+            let mut posing = state.posing();
+            if ui.checkbox("Pose mode", &mut posing) {
+                if posing {
+                    match children.iter_descendants(root).find(|e| players.contains(*e)) {
+                        Some(player) => action = Some(PoseAction::Enter(player)),
+                        None => state.status = "No AnimationPlayer on this model".to_string(),
+                    }
+                } else {
+                    action = Some(PoseAction::Exit);
+                }
+            }
+            if !state.posing() {
+                ui.text_disabled("Pose mode pauses the animation of the model");
+                return;
+            }
+
+            // Ossa: selezionarne una la passa al gizmo di transform.rs.
+            ui.input_text("Filter", &mut state.filter).build();
+            let filter = state.filter.to_lowercase();
+            ui.child_window("pose bones").size([0.0, 140.0]).build(|| {
+                for bone in children.iter_descendants(root) {
+                    if !joints.contains(&bone) {
+                        continue;
+                    }
+                    let Ok(name) = names.get(bone) else {
+                        continue;
+                    };
+                    if !filter.is_empty() && !name.as_str().to_lowercase().contains(&filter) {
+                        continue;
+                    }
+                    if ui
+                        .selectable_config(format!("{}##{:?}", name, bone))
+                        .selected(state.bone == Some(bone))
+                        .build()
+                    {
+                        state.bone = Some(bone);
+                        for entity in selected.iter() {
+                            commands.entity(entity).remove::<Selected>();
+                        }
+                        commands.entity(bone).insert(Selected);
+                    }
+                }
+            });
+
+            if let Some(bone) = state.bone {
+                if let Ok(mut transform) = transforms.get_mut(bone) {
+                    let (x, y, z) = transform.rotation.to_euler(EulerRot::XYZ);
+                    let mut degrees = [x.to_degrees(), y.to_degrees(), z.to_degrees()];
+                    if ui
+                        .input_float3("Rotation", &mut degrees)
+                        .display_format("%.1f")
+                        .build()
+                    {
+                        transform.rotation = Quat::from_euler(
+                            EulerRot::XYZ,
+                            degrees[0].to_radians(),
+                            degrees[1].to_radians(),
+                            degrees[2].to_radians(),
+                        );
+                    }
+                }
+                ui.text_disabled(format!(
+                    "{} / {} + {} on the selected bone,",
+                    bindings.describe(Action::GizmoRotate),
+                    bindings.describe(Action::GizmoTranslate),
+                    bindings.describe(Action::GizmoDrag),
+                ));
+                ui.text_disabled("or drag IK handles from the IK Rig window");
+            }
+
+            ui.separator();
+            ui.slider("FPS", 1.0, 60.0, &mut state.fps);
+            ui.slider("Duration", 0.1, 10.0, &mut state.duration);
+            let mut time = state.time;
+            if ui.slider("Time", 0.0, state.duration, &mut time) {
+                action = Some(PoseAction::Seek(state.snap(time)));
+            }
+            ui.text(format!("Frame {}", (state.time * state.fps).round() as i32));
+            if ui.button("Key pose") {
+                action = Some(PoseAction::Key);
+            }
+            ui.same_line();
+            if ui.button("Reset pose") {
+                action = Some(PoseAction::Reset);
+            }
+
+            ui.child_window("pose keys").size([0.0, 100.0]).build(|| {
+                for (i, key) in state.keys.iter().enumerate() {
+                    let _id = ui.push_id(format!("key{}", i));
+                    let frame = (key.time * state.fps).round() as i32;
+                    if ui
+                        .selectable_config(format!("Frame {} ({:.2}s)", frame, key.time))
+                        .selected((key.time - state.time).abs() < 1e-4)
+                        .build()
+                    {
+                        action = Some(PoseAction::Seek(key.time));
+                    }
+                    ui.same_line_with_pos(220.0);
+                    if ui.small_button("Delete") {
+                        action = Some(PoseAction::Remove(i));
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.input_text("Clip name", &mut state.clip_name).build();
+            ui.text_disabled(format!("-> assets/animations/{}.glb", state.clip_name));
+            if ui.button("Export clip") {
+                action = Some(PoseAction::Export);
+            }
+            if !state.status.is_empty() {
+                ui.text_wrapped(&state.status);
+            }
+        });
+
+    let Some(action) = action else {
+        return;
+    };
+    let Some(root) = state.root else {
+        return;
+    };
+    let joints = skin_joints(root, &children, &skins);
+    match action {
+        PoseAction::Enter(player) => {
+            if let Ok(mut animation) = players.get_mut(player) {
+                animation.stop_all();
+            }
+            commands.entity(player).insert(ClipPreview);
+            state.player = Some(player);
+            state.rest = joints
+                .iter()
+                .filter_map(|&bone| Some((bone, *transforms.get(bone).ok()?)))
+                .collect();
+            state.keys.clear();
+            state.time = 0.0;
+            state.status.clear();
+        }
+        PoseAction::Exit => {
+            if let Some(player) = state.player.take() {
+                commands.entity(player).remove::<ClipPreview>();
+            }
+            if let Ok(mut controller) = controllers.get_mut(root) {
+                controller.rebuild();
+            }
+            state.bone = None;
+        }
+        PoseAction::Key => {
+            // Si legge la Transform locale: include anche quello che il solver IK ha scritto.
+            let pose = joints
+                .iter()
+                .filter_map(|&bone| Some((bone, *transforms.get(bone).ok()?)))
+                .collect();
+            let time = state.snap(state.time);
+            state.keys.retain(|k| (k.time - time).abs() > 1e-4);
+            state.keys.push(PoseKey { time, pose });
+            state.keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        }
+        PoseAction::Seek(time) => {
+            state.time = time;
+            for (bone, pose) in state.sample(time).unwrap_or_default() {
+                if let Ok(mut transform) = transforms.get_mut(bone) {
+                    *transform = pose;
+                }
+            }
+        }
+        PoseAction::Remove(i) => {
+            state.keys.remove(i);
+        }
+        PoseAction::Reset => {
+            for (bone, rest) in &state.rest {
+                if let Ok(mut transform) = transforms.get_mut(*bone) {
+                    *transform = *rest;
+                }
+            }
+        }
+        PoseAction::Export => {
+            if state.keys.is_empty() {
+                state.status = "Key at least one pose first".to_string();
+                return;
+            }
+            // Il nome diventa un file sotto assets/animations: stesse regole dei layout e dei preset.
+            let Some(clip_name) = ron_file::valid_name(&state.clip_name) else {
+                state.status = "Invalid clip name".to_string();
+                return;
+            };
+            let file = format!("animations/{}.glb", clip_name);
+            let (nodes, roots, animation) = build_export(state, &parents, &names, &transforms);
+            let path = Path::new("assets").join(&file);
+            state.status = match write_animation_glb(&path, &nodes, &roots, &animation) {
+                Ok(()) => format!("Saved {} keys, load it as {}#Animation0", state.keys.len(), file),
+                Err(e) => format!("Export failed: {}", e),
+            };
+        }
+    }
+}
+
+pub struct PoseEditorPlugin;
+impl Plugin for PoseEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PoseEditorState>()
+            .add_systems(Update, pose_editor_ui);
+    }
+}
@@ -10,6 +10,8 @@ use bevy::{
             ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
             UniformComponentPlugin,
         },
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::RenderAssets,
         render_graph::{
             NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
         },
@@ -18,26 +20,46 @@ use bevy::{
             *,
         },
         renderer::{RenderContext, RenderDevice},
+        texture::{FallbackImage, GpuImage},
         view::ViewTarget,
         RenderApp,
     },
 };
+use crate::shader_params::{add_shader_params, shader_params};
+
 const SHADER_ASSET_PATH: &str = "shaders/pixel_art.wgsl";
 
+/// Modi di misurare la distanza dai colori della palette (`color_distance`).
+pub const COLOR_DISTANCE_MODES: [&str; 5] =
+    ["Luminance", "RGB", "Weighted RGB", "CIELAB", "OKLab"];
+/// Modi di dithering (`dither_mode`).
+pub const DITHER_MODES: [&str; 5] = ["None", "Bayer 2x2", "Bayer 4x4", "Bayer 8x8", "Blue noise"];
+pub const DITHER_BAYER4: u32 = 2;
+
+/// Tessera di blue noise letta dallo shader quando `dither_mode` è "Blue noise".
+#[derive(Resource, Clone, ExtractResource)]
+struct BlueNoiseTexture(Handle<Image>);
+
 fn preload_shader(asset_server: Res<AssetServer>) {
     // Preload the shader to ensure it's available when the pipeline is created
     let _shader_handle: Handle<Shader> = asset_server.load(SHADER_ASSET_PATH);
     info!("Preloading post-process shader: {}", SHADER_ASSET_PATH);
 }
 
+fn setup_blue_noise(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(BlueNoiseTexture(images.add(crate::blue_noise::image())));
+}
+
 pub struct PostProcessPlugin;
 impl Plugin for PostProcessPlugin {
     fn build(&self, app: &mut App) {
+        add_shader_params::<PostProcessSettings>(app);
         app.add_plugins((
             ExtractComponentPlugin::<PostProcessSettings>::default(),
             UniformComponentPlugin::<PostProcessSettings>::default(),
+            ExtractResourcePlugin::<BlueNoiseTexture>::default(),
         ))
-        .add_systems(Startup, preload_shader);
+        .add_systems(Startup, (preload_shader, setup_blue_noise));
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
@@ -68,7 +90,7 @@ impl Plugin for PostProcessPlugin {
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
-struct PostProcessLabel;
+pub(crate) struct PostProcessLabel;
 
 #[derive(Default)]
 struct PostProcessNode;
@@ -101,6 +123,14 @@ impl ViewNode for PostProcessNode {
             return Ok(());
         };
 
+        // Finché la texture non è sul GPU (o se manca) si usa quella di fallback.
+        let blue_noise = world
+            .get_resource::<BlueNoiseTexture>()
+            .and_then(|noise| world.resource::<RenderAssets<GpuImage>>().get(&noise.0))
+            .map_or(&world.resource::<FallbackImage>().d2.texture_view, |image| {
+                &image.texture_view
+            });
+
         let post_process = view_target.post_process_write();
 
         let bind_group = render_context.render_device().create_bind_group(
@@ -110,6 +140,7 @@ impl ViewNode for PostProcessNode {
                 post_process.source,
                 &post_process_pipeline.sampler,
                 settings_binding.clone(),
+                blue_noise,
             )),
         );
 
@@ -152,6 +183,7 @@ impl FromWorld for PostProcessPipeline {
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
                     uniform_buffer::<PostProcessSettings>(true),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
                 ),
             ),
         );
@@ -194,39 +226,39 @@ impl FromWorld for PostProcessPipeline {
     }
 }
 
-#[derive(Component, ShaderType, Default, Clone, Copy, ExtractComponent)]
-pub struct PostProcessSettings {
-    pub edge_denoise: f32,
-    pub edge_intensity: f32,
-    pub color_levels: f32,
-    pub cel_levels: f32,
-
-    pub contrast: f32,
-    pub saturation: f32,
-    pub scanline_intensity: f32,
-    pub _pad1: f32,
-
-    pub window_size: Vec2,
-    pub _pad2: Vec2,
-
-    pub dithering_strength: f32,
-    pub edge_threshold: f32,
-    pub color_snap_strength: f32,
-    pub _pad3: f32,
-    
-    pub palette: [Vec4; 32],
-    pub color_count_and_pad: UVec4,
-}
+shader_params! {
+    #[derive(Component, ExtractComponent)]
+    pub struct PostProcessSettings in "xirai::post_process" {
+        pub edge_denoise: f32 = 0.0 => slider("Edge Denoise", 0.0, 1.0); lerp,
+        pub edge_intensity: f32 = 0.0 => slider("Edge Intensity", 0.0, 1.0); lerp,
+        pub color_levels: f32 = 0.0; lerp,
+        pub cel_levels: f32 = 0.0 => slider("Cel Shading Levels", 0.0, 20.0); lerp,
+
+        pub contrast: f32 = 0.0 => slider("Contrast", 1.0, 10.0); lerp,
+        pub saturation: f32 = 0.0 => slider("Saturation", 1.0, 10.0); lerp,
+        pub scanline_intensity: f32 = 0.0 => slider("Scanline Intensity", 0.0, 10.0); lerp,
+
+        pub window_size: Vec2 = Vec2::ZERO,
+
+        pub dithering_strength: f32 = 0.0 => slider("Dithering", 0.0, 1.0); lerp,
+        pub edge_threshold: f32 = 0.0 => slider("Edge Threshold", 0.0, 1.0); lerp,
+        pub color_snap_strength: f32 = 0.0 => slider("Color Snap Strength", 0.0, 1.0); lerp,
 
-impl PostProcessSettings {
-    pub fn new() -> Self {
-        let mut s: Self = Default::default();
-        s._pad1 = 0.5;
-        s._pad2 = Vec2::ZERO;
-        s._pad3 = 0.0;
-        s.window_size = Vec2::new(1920.0, 1080.0);
-        s.edge_intensity = 1.0;
-        s.color_count_and_pad = UVec4::new(32, 1, 0, 0); // palette abilitata
-        s
+        pub palette: [Vec4; 32] = [Vec4::ZERO; 32],
+        /// x = numero di colori, y = palette attiva.
+        pub color_count_and_pad: UVec4 = UVec4::ZERO,
+
+        /// Indice in `COLOR_DISTANCE_MODES`.
+        pub color_distance: u32 = 0 => combo("Color Distance", &COLOR_DISTANCE_MODES); snap,
+        /// Il Bayer 4x4 era l'unico dithering prima che si potesse scegliere.
+        pub dither_mode: u32 = DITHER_BAYER4 => combo("Dither Mode", &DITHER_MODES); snap,
+    }
+
+    /// Tutti i parametri del post-process che si regolano a mano, palette compresa (niente
+    /// padding né dimensione della finestra, che sono di runtime). La palette la gestisce
+    /// `pp_presets`.
+    preset PostProcessPreset {
+        pub palette_enabled: bool,
+        pub palette: Vec<[f32; 4]>,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::encase::{internal::WriteInto, UniformBuffer};
+use bevy::render::render_resource::ShaderType;
+use serde::{Deserialize, Serialize};
+
+use crate::shader_params::{add_shader_params, shader_params, ShaderParams};
+
+/// Parametri liberi di uno shader scritto a mano: `values: array<vec4<f32>, 8>`.
+pub const MAX_PARAMS: usize = 32;
+/// Spazio per i parametri di una passata nel buffer: 256 è il massimo che wgpu ammette
+/// per `min_uniform_buffer_offset_alignment`, quindi l'offset è valido su ogni GPU.
+pub const PARAMS_STRIDE: usize = 256;
+
+shader_params! {
+    /// `shaders/stack/pastel.wgsl`: toni pastello, livelli morbidi e bordi sfumati.
+    #[derive(Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct PastelParams in "xirai::stack::pastel" {
+        pub intensity: f32 = 1.0 => slider("Intensity", 0.0, 1.0),
+        pub saturation: f32 = 0.8 => slider("Saturation", 0.0, 2.0),
+        pub brightness: f32 = 1.2 => slider("Brightness", 0.5, 2.0),
+        pub contrast: f32 = 0.8 => slider("Contrast", 0.0, 2.0),
+        /// 0 = livelli spenti.
+        pub levels: f32 = 8.0 => slider("Levels", 0.0, 16.0),
+        pub outline_strength: f32 = 1.5 => slider("Outline Strength", 0.0, 3.0),
+        pub outline_threshold: f32 = 0.12 => slider("Outline Threshold", 0.0, 1.0),
+        pub edge_softness: f32 = 0.5 => slider("Edge Softness", 0.0, 1.0),
+    }
+}
+
+shader_params! {
+    /// `shaders/stack/quantize.wgsl`: `levels` livelli per canale.
+    #[derive(Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct QuantizeParams in "xirai::stack::quantize" {
+        pub levels: f32 = 16.0 => slider("Levels", 2.0, 32.0),
+        pub grayscale: f32 = 0.0 => slider("Grayscale", 0.0, 1.0),
+    }
+}
+
+shader_params! {
+    /// `shaders/stack/outline.wgsl`: bordi Sobel scuri.
+    #[derive(Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct OutlineParams in "xirai::stack::outline" {
+        pub threshold: f32 = 0.2 => slider("Threshold", 0.0, 1.0),
+        pub strength: f32 = 0.8 => slider("Strength", 0.0, 1.0),
+        /// In pixel.
+        pub thickness: f32 = 1.0 => slider("Thickness", 1.0, 4.0),
+    }
+}
+
+shader_params! {
+    /// `shaders/stack/simple_outline.wgsl`: bordo colorato sui salti di luminanza.
+    #[derive(Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct SimpleOutlineParams in "xirai::stack::simple_outline" {
+        /// In pixel.
+        pub thickness: f32 = 2.0 => slider("Thickness", 0.0, 10.0),
+        pub threshold: f32 = 0.1 => slider("Threshold", 0.0, 1.0),
+        pub intensity: f32 = 1.0 => slider("Intensity", 0.0, 1.0),
+        pub color: Vec3 = Vec3::ZERO => color("Color"),
+    }
+}
+
+shader_params! {
+    /// `shaders/stack/wide_outline.wgsl`: contorno spesso con bordo sfumato.
+    #[derive(Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct WideOutlineParams in "xirai::stack::wide_outline" {
+        pub threshold: f32 = 0.1 => slider("Threshold", 0.0, 1.0),
+        /// In pixel, al massimo 8.
+        pub thickness: f32 = 3.0 => slider("Thickness", 1.0, 8.0),
+        /// In pixel.
+        pub softness: f32 = 1.0 => slider("Softness", 0.0, 4.0),
+        pub color: Vec3 = Vec3::ZERO => color("Color"),
+    }
+}
+
+shader_params! {
+    /// Parametri liberi degli shader scritti a mano, nell'ordine in `values[i / 4][i % 4]`.
+    pub struct CustomParams in "xirai::stack::custom" {
+        pub values: [Vec4; 8] = [Vec4::ZERO; 8],
+    }
+}
+
+impl CustomParams {
+    fn from_params(params: &[PassParam]) -> Self {
+        let mut values = [Vec4::ZERO; 8];
+        for (i, param) in params.iter().take(MAX_PARAMS).enumerate() {
+            values[i / 4][i % 4] = param.value;
+        }
+        Self { values }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PassParam {
+    pub name: String,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for PassParam {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            value: 0.0,
+            min: 0.0,
+            max: 1.0,
+        }
+    }
+}
+
+/// Il blocco di parametri di una passata: la struct dell'effetto, o quelli liberi per
+/// uno shader che non ne ha una.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum PassParams {
+    Pastel(PastelParams),
+    Quantize(QuantizeParams),
+    Outline(OutlineParams),
+    SimpleOutline(SimpleOutlineParams),
+    WideOutline(WideOutlineParams),
+    Custom(Vec<PassParam>),
+}
+
+impl Default for PassParams {
+    fn default() -> Self {
+        Self::Custom(Vec::new())
+    }
+}
+
+/// La struct con il layout WGSL, pronta per l'uniform al binding 3.
+fn uniform_bytes<T: ShaderType + WriteInto>(params: &T) -> Vec<u8> {
+    let mut buffer = UniformBuffer::new(Vec::new());
+    buffer.write(params).expect("shader params fit in a Vec");
+    buffer.into_inner()
+}
+
+macro_rules! stack_effects {
+    ($($variant:ident($params:ident) => $label:literal, $shader:literal;)*) => {
+        impl PassParams {
+            /// Effetti pronti: nome e shader.
+            pub const EFFECTS: &'static [(&'static str, &'static str)] = &[$(($label, $shader),)*];
+
+            /// I parametri di default dell'effetto di `shader`; liberi per gli altri.
+            pub fn for_shader(shader: &str) -> Self {
+                match shader {
+                    $($shader => Self::$variant($params::default()),)*
+                    _ => Self::Custom(Vec::new()),
+                }
+            }
+
+            /// Disegna i controlli dei parametri; `true` se qualcosa è cambiato.
+            pub fn edit_ui(&mut self, ui: &imgui::Ui) -> bool {
+                match self {
+                    $(Self::$variant(params) => params.edit_ui(ui),)*
+                    Self::Custom(params) => {
+                        let mut changed = false;
+                        for param in params {
+                            changed |= ui.slider(&param.name, param.min, param.max, &mut param.value);
+                        }
+                        changed
+                    }
+                }
+            }
+
+            pub fn uniform_bytes(&self) -> Vec<u8> {
+                match self {
+                    $(Self::$variant(params) => uniform_bytes(params),)*
+                    Self::Custom(params) => uniform_bytes(&CustomParams::from_params(params)),
+                }
+            }
+        }
+
+        /// Registra i moduli WGSL di tutti i blocchi di parametri.
+        pub fn add_effect_params(app: &mut App) {
+            $(add_shader_params::<$params>(app);)*
+            add_shader_params::<CustomParams>(app);
+        }
+    };
+}
+
+stack_effects! {
+    Pastel(PastelParams) => "Pastel", "shaders/stack/pastel.wgsl";
+    Quantize(QuantizeParams) => "Quantize", "shaders/stack/quantize.wgsl";
+    Outline(OutlineParams) => "Outline", "shaders/stack/outline.wgsl";
+    SimpleOutline(SimpleOutlineParams) => "Simple Outline", "shaders/stack/simple_outline.wgsl";
+    WideOutline(WideOutlineParams) => "Wide Outline", "shaders/stack/wide_outline.wgsl";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_effect_fits_its_slot() {
+        let custom = PassParams::Custom(vec![PassParam::default(); MAX_PARAMS]);
+        let effects = PassParams::EFFECTS
+            .iter()
+            .map(|(_, shader)| PassParams::for_shader(shader));
+        for params in effects.chain([custom]) {
+            let bytes = params.uniform_bytes();
+            assert!(!bytes.is_empty() && bytes.len() <= PARAMS_STRIDE);
+        }
+        assert!(matches!(
+            PassParams::for_shader("shaders/stack/mine.wgsl"),
+            PassParams::Custom(params) if params.is_empty()
+        ));
+    }
+
+    #[test]
+    fn bytes_follow_the_wgsl_layout() {
+        let params = SimpleOutlineParams {
+            thickness: 1.0,
+            threshold: 2.0,
+            intensity: 3.0,
+            color: Vec3::new(4.0, 5.0, 6.0),
+        };
+        let bytes = PassParams::SimpleOutline(params).uniform_bytes();
+        let floats: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        // vec3 allineato a 16 byte: un float di padding dopo `intensity`.
+        assert_eq!(floats[..3], [1.0, 2.0, 3.0]);
+        assert_eq!(floats[4..7], [4.0, 5.0, 6.0]);
+        assert_eq!(
+            SimpleOutlineParams::wgsl_fields()[3],
+            ("color", "vec3<f32>".to_string())
+        );
+
+        let custom = [0.5, 1.5, 2.5, 3.5, 4.5].map(|value| PassParam { value, ..default() });
+        let bytes = PassParams::Custom(custom.to_vec()).uniform_bytes();
+        assert_eq!(bytes.len(), 8 * 16);
+        assert_eq!(f32::from_le_bytes(bytes[16..20].try_into().unwrap()), 4.5);
+    }
+}
@@ -0,0 +1,417 @@
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::pp::{PostProcessPreset, PostProcessSettings};
+use crate::retrocamera::RetroCamera;
+use crate::ron_file;
+use crate::shader_params::snap;
+
+const PRESET_DIR: &str = "assets/post_process";
+
+impl From<&PostProcessSettings> for PostProcessPreset {
+    fn from(settings: &PostProcessSettings) -> Self {
+        let count = settings.color_count_and_pad.x.min(32) as usize;
+        Self {
+            palette_enabled: settings.color_count_and_pad.y != 0,
+            palette: settings.palette[..count].iter().map(|c| c.to_array()).collect(),
+            ..Self::from_params(settings)
+        }
+    }
+}
+
+impl PostProcessPreset {
+    pub fn apply(&self, settings: &mut PostProcessSettings) {
+        self.apply_params(settings);
+        settings.color_count_and_pad.y = self.palette_enabled as u32;
+        if !self.palette.is_empty() {
+            let count = self.palette.len().min(32);
+            settings.color_count_and_pad.x = count as u32;
+            for (slot, color) in settings.palette.iter_mut().zip(&self.palette) {
+                *slot = Vec4::from_array(*color);
+            }
+        }
+    }
+
+    /// I numeri si interpolano, la palette colore per colore (la più corta ripete l'ultimo);
+    /// quello che non si può sfumare (palette attiva, numero di colori, modi) scatta a metà.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let len = snap(self.palette.len(), other.palette.len(), t);
+        let color = |palette: &[[f32; 4]], i: usize| {
+            palette
+                .get(i)
+                .or(palette.last())
+                .map_or(Vec4::ZERO, |c| Vec4::from_array(*c))
+        };
+        Self {
+            palette_enabled: snap(self.palette_enabled, other.palette_enabled, t),
+            palette: (0..len)
+                .map(|i| {
+                    color(&self.palette, i)
+                        .lerp(color(&other.palette, i), t)
+                        .to_array()
+                })
+                .collect(),
+            ..self.lerp_params(other, t)
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        ron_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        ron_file::save(self, path)
+    }
+}
+
+pub fn preset_path(name: &str) -> PathBuf {
+    Path::new(PRESET_DIR).join(format!("{}.ron", name))
+}
+
+/// Passa le retro camere a un preset della libreria, sfumando in `duration` secondi
+/// (0 = subito). Con `from` si parte da quel preset invece che dai valori attuali.
+#[derive(Event, Clone)]
+pub struct BlendPostProcess {
+    pub from: Option<String>,
+    pub preset: String,
+    pub duration: f32,
+}
+
+impl BlendPostProcess {
+    pub fn preset(name: impl Into<String>, duration: f32) -> Self {
+        Self {
+            from: None,
+            preset: name.into(),
+            duration,
+        }
+    }
+
+    pub fn starting_from(mut self, name: impl Into<String>) -> Self {
+        self.from = Some(name.into());
+        self
+    }
+}
+
+/// Transizione in corso tra due preset (per i cambi di scena).
+#[derive(Component)]
+pub struct PostProcessTransition {
+    from: PostProcessPreset,
+    to: PostProcessPreset,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl PostProcessTransition {
+    pub fn new(from: PostProcessPreset, to: PostProcessPreset, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.0,
+            duration,
+        }
+    }
+}
+
+fn start_post_process_blends(
+    mut commands: Commands,
+    mut events: EventReader<BlendPostProcess>,
+    mut library: ResMut<PresetLibrary>,
+    mut cameras: Query<(Entity, &mut PostProcessSettings), With<RetroCamera>>,
+) {
+    for event in events.read() {
+        let load = |name: &str| {
+            PostProcessPreset::load(&preset_path(name)).map_err(|e| format!("{}: {}", name, e))
+        };
+        let (from, to) = match (event.from.as_deref().map(load).transpose(), load(&event.preset)) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(e), _) | (_, Err(e)) => {
+                library.status = format!("Preset not loaded: {}", e);
+                continue;
+            }
+        };
+        for (entity, mut settings) in cameras.iter_mut() {
+            if event.duration <= 0.0 {
+                to.apply(&mut settings);
+                commands.entity(entity).remove::<PostProcessTransition>();
+                continue;
+            }
+            let from = from
+                .clone()
+                .unwrap_or_else(|| PostProcessPreset::from(&*settings));
+            from.apply(&mut settings);
+            commands
+                .entity(entity)
+                .insert(PostProcessTransition::new(from, to.clone(), event.duration));
+        }
+    }
+}
+
+fn animate_post_process_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cameras: Query<(Entity, &mut PostProcessTransition, &mut PostProcessSettings)>,
+) {
+    for (entity, mut transition, mut settings) in cameras.iter_mut() {
+        transition.elapsed += time.delta_secs();
+        let t = (transition.elapsed / transition.duration).min(1.0);
+        let s = t * t * (3.0 - 2.0 * t);
+        transition.from.lerp(&transition.to, s).apply(&mut settings);
+        if t >= 1.0 {
+            commands.entity(entity).remove::<PostProcessTransition>();
+        }
+    }
+}
+
+#[derive(Resource)]
+struct PresetLibrary {
+    names: Vec<String>,
+    selected: usize,
+    blend_from: usize,
+    duration: f32,
+    new_name: String,
+    file: String,
+    status: String,
+}
+
+impl Default for PresetLibrary {
+    fn default() -> Self {
+        let mut library = Self {
+            names: Vec::new(),
+            selected: 0,
+            blend_from: 0,
+            duration: 2.0,
+            new_name: String::new(),
+            file: String::new(),
+            status: String::new(),
+        };
+        library.refresh();
+        library
+    }
+}
+
+impl PresetLibrary {
+    fn refresh(&mut self) {
+        self.names = fs::read_dir(PRESET_DIR)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+                    .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.names.sort();
+        let last = self.names.len().saturating_sub(1);
+        self.selected = self.selected.min(last);
+        self.blend_from = self.blend_from.min(last);
+    }
+
+    fn select(&mut self, name: &str) {
+        if let Some(i) = self.names.iter().position(|n| n == name) {
+            self.selected = i;
+        }
+    }
+}
+
+fn post_process_presets_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut library: ResMut<PresetLibrary>,
+    mut events: EventWriter<BlendPostProcess>,
+    cameras: Query<(&Camera, &PostProcessSettings, Has<PostProcessTransition>), With<RetroCamera>>,
+) {
+    let library = &mut *library;
+    // Si salva quello che si vede: la retro camera attiva.
+    let current = cameras
+        .iter()
+        .find(|(camera, ..)| camera.is_active)
+        .or_else(|| cameras.iter().next());
+    let ui = context.ui();
+    ui.window("Post-process Presets")
+        .size([340.0, 300.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            if library.names.is_empty() {
+                ui.text_disabled(format!("No presets in {}", PRESET_DIR));
+            } else {
+                ui.combo_simple_string("Preset", &mut library.selected, &library.names);
+                let name = library.names[library.selected].clone();
+                if ui.button("Apply") {
+                    events.write(BlendPostProcess::preset(&name, 0.0));
+                }
+                ui.same_line();
+                if ui.button("Delete") {
+                    if let Err(e) = fs::remove_file(preset_path(&name)) {
+                        library.status = format!("Delete failed: {}", e);
+                    }
+                    library.refresh();
+                }
+
+                ui.separator();
+                ui.slider("Seconds", 0.1, 10.0, &mut library.duration);
+                if ui.button("Blend from current") {
+                    events.write(BlendPostProcess::preset(&name, library.duration));
+                }
+                ui.combo_simple_string("From", &mut library.blend_from, &library.names);
+                if ui.button("Blend from -> Preset") {
+                    let from = library.names[library.blend_from].clone();
+                    events.write(BlendPostProcess::preset(&name, library.duration).starting_from(from));
+                }
+                if current.is_some_and(|(_, _, blending)| blending) {
+                    ui.same_line();
+                    ui.text_colored([1.0, 0.9, 0.3, 1.0], "blending...");
+                }
+            }
+
+            ui.separator();
+            ui.input_text("##preset name", &mut library.new_name)
+                .hint("Preset name")
+                .build();
+            ui.same_line();
+            if ui.button("Save current") {
+                library.status = match (ron_file::valid_name(&library.new_name), current) {
+                    (Some(name), Some((_, settings, _))) => {
+                        match PostProcessPreset::from(settings).save(&preset_path(name)) {
+                            Ok(()) => format!("Saved {}", preset_path(name).display()),
+                            Err(e) => format!("Save failed: {}", e),
+                        }
+                    }
+                    (None, _) => format!("Invalid preset name: {:?}", library.new_name),
+                    (_, None) => "No retro camera".to_string(),
+                };
+                let name = library.new_name.trim().to_string();
+                library.refresh();
+                library.select(&name);
+            }
+
+            ui.separator();
+            ui.input_text("File", &mut library.file)
+                .hint("path/to/preset.ron")
+                .build();
+            if ui.button("Import") {
+                // Si valida leggendolo, poi lo si copia nella libreria con il suo nome.
+                let path = Path::new(library.file.trim());
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                library.status = match (PostProcessPreset::load(path), ron_file::valid_name(&name)) {
+                    (Ok(preset), Some(name)) => match preset.save(&preset_path(name)) {
+                        Ok(()) => format!("Imported {}", name),
+                        Err(e) => format!("Import failed: {}", e),
+                    },
+                    (Err(e), _) => format!("Import failed: {}", e),
+                    (_, None) => format!("Invalid preset name: {:?}", name),
+                };
+                library.refresh();
+                library.select(&name);
+            }
+            ui.same_line();
+            if ui.button("Export selected") {
+                library.status = match library.names.get(library.selected) {
+                    Some(name) => match PostProcessPreset::load(&preset_path(name))
+                        .and_then(|preset| preset.save(Path::new(library.file.trim())))
+                    {
+                        Ok(()) => format!("Exported {} to {}", name, library.file.trim()),
+                        Err(e) => format!("Export failed: {}", e),
+                    },
+                    None => "No preset selected".to_string(),
+                };
+            }
+            if !library.status.is_empty() {
+                ui.text_wrapped(&library.status);
+            }
+        });
+}
+
+pub struct PostProcessPresetsPlugin;
+impl Plugin for PostProcessPresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BlendPostProcess>()
+            .init_resource::<PresetLibrary>()
+            .add_systems(
+                Update,
+                (
+                    post_process_presets_ui,
+                    start_post_process_blends,
+                    animate_post_process_transitions,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    fn preset(contrast: f32, dither_mode: u32, palette: &[[f32; 4]]) -> PostProcessPreset {
+        PostProcessPreset {
+            contrast,
+            dither_mode,
+            palette_enabled: !palette.is_empty(),
+            palette: palette.to_vec(),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn lerp_blends_numbers_and_snaps_modes() {
+        let a = preset(1.0, 0, &[BLACK, WHITE]);
+        let b = preset(3.0, 2, &[WHITE, BLACK]);
+        assert!(a.lerp(&b, 0.0) == a);
+        assert!(a.lerp(&b, 1.0) == b);
+
+        let quarter = a.lerp(&b, 0.25);
+        assert_eq!(quarter.contrast, 1.5);
+        assert_eq!(quarter.dither_mode, 0);
+        assert_eq!(quarter.palette, vec![[0.25, 0.25, 0.25, 1.0], [0.75, 0.75, 0.75, 1.0]]);
+        assert_eq!(a.lerp(&b, 0.5).dither_mode, 2);
+    }
+
+    #[test]
+    fn lerp_palettes_of_different_lengths() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let green = [0.0, 1.0, 0.0, 1.0];
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        let short = preset(1.0, 0, &[BLACK, WHITE]);
+        let long = preset(1.0, 0, &[red, green, blue, WHITE]);
+
+        // Il numero di colori scatta a metà; la palette corta ripete il suo ultimo colore.
+        let early = short.lerp(&long, 0.25);
+        assert_eq!(early.palette, vec![[0.25, 0.0, 0.0, 1.0], [0.75, 1.0, 0.75, 1.0]]);
+        let late = short.lerp(&long, 0.75);
+        assert_eq!(late.palette.len(), 4);
+        assert_eq!(late.palette[2], [0.25, 0.25, 1.0, 1.0]);
+        assert_eq!(late.palette[3], WHITE);
+        assert!(short.lerp(&long, 1.0) == long);
+        assert_eq!(long.lerp(&short, 0.75).palette, vec![[0.25, 0.0, 0.0, 1.0], [0.75, 1.0, 0.75, 1.0]]);
+
+        // Senza palette si parte dal nero trasparente.
+        let none = preset(1.0, 0, &[]);
+        let blend = none.lerp(&short, 0.5);
+        assert!(blend.palette_enabled);
+        assert_eq!(blend.palette, vec![[0.0, 0.0, 0.0, 0.5], [0.5, 0.5, 0.5, 0.5]]);
+    }
+
+    #[test]
+    fn shipped_presets_load() {
+        let library = PresetLibrary::default();
+        for name in ["gameboy", "nes", "grayscale", "retro", "grayscale_32", "vibrant_32", "pastel_32"] {
+            assert!(library.names.iter().any(|n| n == name), "missing preset {}", name);
+        }
+        for name in &library.names {
+            let preset = PostProcessPreset::load(&preset_path(name)).unwrap();
+            assert!(preset.palette_enabled, "{}", name);
+            assert!((1..=32).contains(&preset.palette.len()), "{}", name);
+
+            let mut settings = PostProcessSettings::default();
+            preset.apply(&mut settings);
+            assert!(PostProcessPreset::from(&settings) == preset, "{} does not round trip", name);
+        }
+    }
+}
@@ -0,0 +1,767 @@
+use bevy::{
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer, uniform_buffer_sized},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        view::ViewTarget,
+        Render, RenderApp, RenderSet,
+    },
+};
+use bevy_mod_imgui::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::camera_manager::NamedCamera;
+use crate::pp_effects::{add_effect_params, PassParam, PassParams, MAX_PARAMS, PARAMS_STRIDE};
+use crate::shader_params::{add_shader_params, shader_params};
+use crate::ron_file;
+
+const STACK_DIR: &str = "assets/post_process/stacks";
+/// Stack montato sulla camera dell'editor all'avvio.
+const DEFAULT_STACK: &str = "pastel_outline";
+/// Ogni quanto si controlla se i `.wgsl` dello stack sono cambiati su disco.
+const RELOAD_INTERVAL: f32 = 0.5;
+
+shader_params! {
+    /// Uniform comune alle passate. Negli shader: `screen_texture`, `texture_sampler`,
+    /// `view: StackView` (`#import xirai::stack::StackView`) e i parametri della passata
+    /// (vedi `pp_effects`) ai binding 0, 1, 2 e 3.
+    struct StackView in "xirai::stack" {
+        pub resolution: Vec2 = Vec2::ZERO,
+        pub time: f32 = 0.0,
+    }
+}
+
+/// Una passata: uno shader fullscreen (percorso relativo ad `assets/`) e il suo blocco
+/// di parametri.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PostProcessPass {
+    pub name: String,
+    pub shader: String,
+    pub enabled: bool,
+    pub params: PassParams,
+    /// Assente finché lo shader non si è caricato senza errori.
+    #[serde(skip)]
+    handle: Option<Handle<Shader>>,
+}
+
+impl Default for PostProcessPass {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            shader: String::new(),
+            enabled: true,
+            params: PassParams::default(),
+            handle: None,
+        }
+    }
+}
+
+impl PostProcessPass {
+    pub fn new(shader: impl Into<String>) -> Self {
+        let shader = shader.into();
+        let name = Path::new(&shader)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| shader.clone());
+        Self {
+            name,
+            params: PassParams::for_shader(&shader),
+            shader,
+            ..default()
+        }
+    }
+}
+
+/// Passate applicate in ordine all'uscita di una camera, dopo il pixel art.
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PostProcessStack {
+    pub passes: Vec<PostProcessPass>,
+}
+
+impl PostProcessStack {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        ron_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        ron_file::save(self, path)
+    }
+
+    /// Lo stack di default degli asset; vuoto se il file manca o non si legge.
+    pub fn shipped() -> Self {
+        Self::load(&stack_path(DEFAULT_STACK)).unwrap_or_else(|e| {
+            warn!("Post-process stack {}: {}", DEFAULT_STACK, e);
+            Self::default()
+        })
+    }
+}
+
+pub fn stack_path(name: &str) -> PathBuf {
+    Path::new(STACK_DIR).join(format!("{}.ron", name))
+}
+
+#[derive(Clone)]
+pub struct ExtractedPass {
+    shader: AssetId<Shader>,
+    /// Il blocco di parametri con il layout WGSL.
+    params: Vec<u8>,
+}
+
+/// Solo le passate attive e con lo shader pronto.
+#[derive(Component, Clone)]
+pub struct ExtractedStack {
+    passes: Vec<ExtractedPass>,
+}
+
+impl ExtractComponent for PostProcessStack {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = ExtractedStack;
+
+    fn extract_component(stack: QueryItem<'_, Self::QueryData>) -> Option<ExtractedStack> {
+        Some(ExtractedStack {
+            passes: stack
+                .passes
+                .iter()
+                .filter(|pass| pass.enabled)
+                .filter_map(|pass| {
+                    Some(ExtractedPass {
+                        shader: pass.handle.as_ref()?.id(),
+                        params: pass.params.uniform_bytes(),
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+#[derive(Resource, Clone, Default, ExtractResource)]
+struct StackTime(f32);
+
+/// Errori di compilazione delle pipeline, scritti dal render world e letti dalla UI.
+#[derive(Resource, Clone, Default)]
+struct StackPipelineErrors(Arc<Mutex<HashMap<AssetId<Shader>, String>>>);
+
+struct StackShader {
+    handle: Handle<Shader>,
+    modified: Option<SystemTime>,
+    error: Option<String>,
+}
+
+/// Shader dello stack letti a mano da disco, così si ricaricano anche senza `file_watcher`.
+#[derive(Resource)]
+struct StackShaders {
+    shaders: HashMap<String, StackShader>,
+    timer: Timer,
+}
+
+impl Default for StackShaders {
+    fn default() -> Self {
+        Self {
+            shaders: HashMap::default(),
+            timer: Timer::from_seconds(RELOAD_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Un binding che non c'è nel layout fa fallire la creazione della pipeline senza
+/// passare dalla cache: meglio fermarlo prima e mostrarlo come errore.
+fn check_shader_source(source: &str) -> Result<(), String> {
+    if !source.contains("fn fragment") {
+        return Err("missing `fn fragment` entry point".to_string());
+    }
+    let number_after = |line: &str, tag: &str| -> Option<u32> {
+        let rest = &line[line.find(tag)? + tag.len()..];
+        rest[..rest.find(')')?].trim().parse().ok()
+    };
+    for (i, line) in source.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default();
+        if !line.contains("@group(") {
+            continue;
+        }
+        match (number_after(line, "@group("), number_after(line, "@binding(")) {
+            (Some(0), Some(0..=3)) => {}
+            _ => {
+                return Err(format!(
+                    "line {}: only @group(0) @binding(0..=3) are available to stack passes",
+                    i + 1
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_shader(path: &str) -> Result<Shader, String> {
+    let source = fs::read_to_string(Path::new("assets").join(path)).map_err(|e| e.to_string())?;
+    check_shader_source(&source)?;
+    Ok(Shader::from_wgsl(source, path.to_string()))
+}
+
+/// Carica gli shader nuovi, ricarica quelli cambiati su disco e aggiorna gli handle
+/// delle passate (una passata con lo shader in errore resta fuori dallo stack).
+fn load_stack_shaders(
+    time: Res<Time>,
+    mut cache: ResMut<StackShaders>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut stacks: Query<&mut PostProcessStack>,
+) {
+    let cache = &mut *cache;
+    for stack in &stacks {
+        for pass in &stack.passes {
+            if cache.shaders.contains_key(&pass.shader) {
+                continue;
+            }
+            let full_path = Path::new("assets").join(&pass.shader);
+            let (handle, error) = match read_shader(&pass.shader) {
+                Ok(shader) => (shaders.add(shader), None),
+                Err(e) => (shaders.reserve_handle(), Some(e)),
+            };
+            cache.shaders.insert(
+                pass.shader.clone(),
+                StackShader {
+                    handle,
+                    modified: modified_time(&full_path),
+                    error,
+                },
+            );
+        }
+    }
+
+    if cache.timer.tick(time.delta()).just_finished() {
+        for (path, shader) in cache.shaders.iter_mut() {
+            let modified = modified_time(&Path::new("assets").join(path));
+            if modified == shader.modified {
+                continue;
+            }
+            shader.modified = modified;
+            match read_shader(path) {
+                Ok(source) => {
+                    info!("Post-process stack: ricaricato {}", path);
+                    shaders.insert(shader.handle.id(), source);
+                    shader.error = None;
+                }
+                Err(e) => shader.error = Some(e),
+            }
+        }
+    }
+
+    for mut stack in &mut stacks {
+        let stale = stack.passes.iter().any(|pass| {
+            let ready = cache
+                .shaders
+                .get(&pass.shader)
+                .filter(|shader| shader.error.is_none())
+                .map(|shader| shader.handle.id());
+            pass.handle.as_ref().map(|handle| handle.id()) != ready
+        });
+        if !stale {
+            continue;
+        }
+        for pass in &mut stack.passes {
+            pass.handle = cache
+                .shaders
+                .get(&pass.shader)
+                .filter(|shader| shader.error.is_none())
+                .map(|shader| shader.handle.clone());
+        }
+    }
+}
+
+fn update_stack_time(time: Res<Time>, mut stack_time: ResMut<StackTime>) {
+    stack_time.0 = time.elapsed_secs_wrapped();
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct PostProcessStackLabel;
+
+#[derive(Resource)]
+struct StackPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl FromWorld for StackPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "post_process_stack_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<StackView>(true),
+                    uniform_buffer_sized(true, None),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        Self { layout, sampler }
+    }
+}
+
+impl StackPipeline {
+    fn queue(
+        &self,
+        pipeline_cache: &PipelineCache,
+        shader: AssetId<Shader>,
+        format: TextureFormat,
+    ) -> CachedRenderPipelineId {
+        pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
+            label: Some("post_process_stack_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: Handle::Weak(shader),
+                shader_defs: vec![],
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        })
+    }
+}
+
+/// Una pipeline per shader e formato della view target.
+#[derive(Resource, Default)]
+struct StackPipelines(HashMap<(AssetId<Shader>, TextureFormat), CachedRenderPipelineId>);
+
+/// `StackView` di ogni view e i parametri di ogni passata, uno ogni `PARAMS_STRIDE` byte.
+#[derive(Resource)]
+struct StackUniforms {
+    views: DynamicUniformBuffer<StackView>,
+    params: RawBufferVec<u8>,
+}
+
+impl Default for StackUniforms {
+    fn default() -> Self {
+        Self {
+            views: DynamicUniformBuffer::default(),
+            params: RawBufferVec::new(BufferUsages::UNIFORM),
+        }
+    }
+}
+
+/// Offset della `StackView` e, per ogni passata in ordine, pipeline e offset dei parametri.
+#[derive(Component)]
+struct StackPassInstances {
+    view: u32,
+    passes: Vec<(CachedRenderPipelineId, u32)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_stack_passes(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    stack_pipeline: Res<StackPipeline>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<StackPipelines>,
+    mut uniforms: ResMut<StackUniforms>,
+    errors: Res<StackPipelineErrors>,
+    time: Res<StackTime>,
+    views: Query<(Entity, &ViewTarget, &ExtractedStack)>,
+) {
+    let uniforms = &mut *uniforms;
+    uniforms.views.clear();
+    uniforms.params.clear();
+    let mut errors = errors.0.lock().unwrap();
+    for (entity, view_target, stack) in &views {
+        let format = view_target.main_texture_format();
+        let size = view_target.main_texture().size();
+        let view = uniforms.views.push(&StackView {
+            resolution: Vec2::new(size.width as f32, size.height as f32),
+            time: time.0,
+        });
+        let mut instances = Vec::with_capacity(stack.passes.len());
+        for pass in &stack.passes {
+            let id = *pipelines
+                .0
+                .entry((pass.shader, format))
+                .or_insert_with(|| stack_pipeline.queue(&pipeline_cache, pass.shader, format));
+            match pipeline_cache.get_render_pipeline_state(id) {
+                CachedPipelineState::Ok(_) => {
+                    errors.remove(&pass.shader);
+                }
+                CachedPipelineState::Err(
+                    PipelineCacheError::ShaderNotLoaded(_)
+                    | PipelineCacheError::ShaderImportNotYetAvailable,
+                ) => {}
+                CachedPipelineState::Err(err) => {
+                    errors.insert(pass.shader, err.to_string());
+                }
+                _ => {}
+            }
+            let values = uniforms.params.values_mut();
+            let offset = values.len();
+            values.extend_from_slice(&pass.params);
+            values.resize(offset + PARAMS_STRIDE, 0);
+            instances.push((id, offset as u32));
+        }
+        commands.entity(entity).insert(StackPassInstances {
+            view,
+            passes: instances,
+        });
+    }
+    uniforms.views.write_buffer(&render_device, &render_queue);
+    uniforms.params.write_buffer(&render_device, &render_queue);
+}
+
+#[derive(Default)]
+struct PostProcessStackNode;
+
+impl ViewNode for PostProcessStackNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ExtractedStack,
+        &'static StackPassInstances,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _stack, instances): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let stack_pipeline = world.resource::<StackPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let uniforms = world.resource::<StackUniforms>();
+        let (Some(views), Some(params)) = (uniforms.views.binding(), uniforms.params.buffer())
+        else {
+            return Ok(());
+        };
+        let params = BufferBinding {
+            buffer: params,
+            offset: 0,
+            size: NonZeroU64::new(PARAMS_STRIDE as u64),
+        };
+
+        for (pipeline_id, offset) in &instances.passes {
+            // Shader ancora in compilazione o in errore: la passata si salta.
+            let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
+                continue;
+            };
+            let post_process = view_target.post_process_write();
+            let bind_group = render_context.render_device().create_bind_group(
+                "post_process_stack_bind_group",
+                &stack_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    post_process.source,
+                    &stack_pipeline.sampler,
+                    views.clone(),
+                    params.clone(),
+                )),
+            );
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("post_process_stack_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: post_process.destination,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[instances.view, *offset]);
+            render_pass.draw(0..3, 0..1);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Resource, Default)]
+struct StackEditor {
+    camera: Option<Entity>,
+    new_shader: String,
+    new_param: String,
+    file_name: String,
+    status: String,
+}
+
+enum StackAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+}
+
+#[allow(clippy::type_complexity)]
+fn post_process_stack_ui(
+    mut commands: Commands,
+    mut context: NonSendMut<ImguiContext>,
+    mut editor: ResMut<StackEditor>,
+    cache: Res<StackShaders>,
+    pipeline_errors: Res<StackPipelineErrors>,
+    cameras: Query<(Entity, Option<&NamedCamera>), With<Camera3d>>,
+    mut stacks: Query<&mut PostProcessStack>,
+) {
+    let editor = &mut *editor;
+    let ui = context.ui();
+    ui.window("Post-process Stack")
+        .size([360.0, 420.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let label = |entity: Entity, name: Option<&NamedCamera>| {
+                name.map_or_else(|| format!("Camera {}", entity), |n| n.name.clone())
+            };
+            if editor.camera.is_none_or(|camera| !cameras.contains(camera)) {
+                editor.camera = cameras.iter().next().map(|(entity, _)| entity);
+            }
+            let Some(camera) = editor.camera else {
+                ui.text("No cameras");
+                return;
+            };
+            let current = cameras
+                .get(camera)
+                .map(|(entity, name)| label(entity, name))
+                .unwrap_or_default();
+            if let Some(_combo) = ui.begin_combo("Camera", &current) {
+                for (entity, name) in &cameras {
+                    if ui
+                        .selectable_config(label(entity, name))
+                        .selected(entity == camera)
+                        .build()
+                    {
+                        editor.camera = Some(entity);
+                    }
+                }
+            }
+
+            let Ok(mut stack) = stacks.get_mut(camera) else {
+                if ui.button("Add stack") {
+                    commands.entity(camera).insert(PostProcessStack::default());
+                }
+                return;
+            };
+
+            ui.separator();
+            let errors = pipeline_errors.0.lock().unwrap();
+            let count = stack.passes.len();
+            let mut action = None;
+            for (i, pass) in stack.passes.iter_mut().enumerate() {
+                let _id = ui.push_id_usize(i);
+                ui.checkbox("##enabled", &mut pass.enabled);
+                ui.same_line();
+                let open = ui.tree_node(&pass.name);
+                ui.same_line();
+                if i > 0 && ui.small_button("Up") {
+                    action = Some(StackAction::MoveUp(i));
+                }
+                ui.same_line();
+                if i + 1 < count && ui.small_button("Down") {
+                    action = Some(StackAction::MoveDown(i));
+                }
+                ui.same_line();
+                if ui.small_button("Remove") {
+                    action = Some(StackAction::Remove(i));
+                }
+
+                let error = cache
+                    .shaders
+                    .get(&pass.shader)
+                    .and_then(|shader| {
+                        shader
+                            .error
+                            .clone()
+                            .or_else(|| errors.get(&shader.handle.id()).cloned())
+                    });
+                if error.is_some() {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], "Shader error");
+                }
+                if let Some(_node) = open {
+                    ui.text_disabled(&pass.shader);
+                    if let Some(error) = &error {
+                        ui.text_wrapped(error);
+                    }
+                    pass.params.edit_ui(ui);
+                    // Gli shader senza struct propria hanno parametri liberi.
+                    match &mut pass.params {
+                        PassParams::Custom(params) if params.len() < MAX_PARAMS => {
+                            ui.input_text("##param", &mut editor.new_param)
+                                .hint("param name")
+                                .build();
+                            ui.same_line();
+                            if ui.button("Add param") && !editor.new_param.trim().is_empty() {
+                                params.push(PassParam {
+                                    name: editor.new_param.trim().to_string(),
+                                    ..default()
+                                });
+                                editor.new_param.clear();
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            drop(errors);
+            match action {
+                Some(StackAction::MoveUp(i)) => stack.passes.swap(i, i - 1),
+                Some(StackAction::MoveDown(i)) => stack.passes.swap(i, i + 1),
+                Some(StackAction::Remove(i)) => {
+                    stack.passes.remove(i);
+                }
+                None => {}
+            }
+
+            ui.separator();
+            ui.input_text("Shader", &mut editor.new_shader)
+                .hint("shaders/stack/outline.wgsl")
+                .build();
+            if let Some(_combo) = ui.begin_combo("Effect", "Custom shader") {
+                for (label, shader) in PassParams::EFFECTS {
+                    if ui.selectable(label) {
+                        editor.new_shader = shader.to_string();
+                    }
+                }
+            }
+            if ui.button("Add pass") && !editor.new_shader.trim().is_empty() {
+                stack
+                    .passes
+                    .push(PostProcessPass::new(editor.new_shader.trim()));
+                editor.new_shader.clear();
+            }
+
+            ui.separator();
+            ui.input_text("Stack file", &mut editor.file_name).build();
+            let load = ui.button("Load");
+            ui.same_line();
+            let save = ui.button("Save");
+            if load || save {
+                // Il nome diventa un file in STACK_DIR: niente percorsi.
+                editor.status = match ron_file::valid_name(&editor.file_name) {
+                    None => format!("Invalid stack name: {:?}", editor.file_name),
+                    Some(name) if load => match PostProcessStack::load(&stack_path(name)) {
+                        Ok(loaded) => {
+                            *stack = loaded;
+                            format!("Loaded {}", name)
+                        }
+                        Err(e) => format!("Load failed: {}", e),
+                    },
+                    Some(name) => match stack.save(&stack_path(name)) {
+                        Ok(()) => format!("Saved {}", name),
+                        Err(e) => format!("Save failed: {}", e),
+                    },
+                };
+            }
+            if !editor.status.is_empty() {
+                ui.text_wrapped(&editor.status);
+            }
+        });
+}
+
+pub struct PostProcessStackPlugin;
+impl Plugin for PostProcessStackPlugin {
+    fn build(&self, app: &mut App) {
+        let errors = StackPipelineErrors::default();
+        add_shader_params::<StackView>(app);
+        add_effect_params(app);
+        app.add_plugins((
+            ExtractComponentPlugin::<PostProcessStack>::default(),
+            ExtractResourcePlugin::<StackTime>::default(),
+        ))
+        .init_resource::<StackShaders>()
+        .init_resource::<StackTime>()
+        .init_resource::<StackEditor>()
+        .insert_resource(errors.clone())
+        .add_systems(
+            Update,
+            (load_stack_shaders, update_stack_time, post_process_stack_ui),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .insert_resource(errors)
+            .init_resource::<StackPipelines>()
+            .init_resource::<StackUniforms>()
+            .add_systems(Render, prepare_stack_passes.in_set(RenderSet::Prepare))
+            .add_render_graph_node::<ViewNodeRunner<PostProcessStackNode>>(
+                Core3d,
+                PostProcessStackLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    crate::pp::PostProcessLabel,
+                    PostProcessStackLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<StackPipeline>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::discriminant;
+
+    #[test]
+    fn shipped_stack_is_disabled_and_typed() {
+        let stack = PostProcessStack::load(&stack_path(DEFAULT_STACK)).unwrap();
+        assert_eq!(stack.passes.len(), PassParams::EFFECTS.len());
+        for pass in &stack.passes {
+            assert!(!pass.enabled, "{} changes the default render", pass.name);
+            let expected = PassParams::for_shader(&pass.shader);
+            assert!(!matches!(expected, PassParams::Custom(_)), "{}", pass.shader);
+            assert_eq!(discriminant(&pass.params), discriminant(&expected));
+            assert_eq!(pass.params.uniform_bytes(), expected.uniform_bytes());
+        }
+    }
+
+    #[test]
+    fn shipped_shaders_use_the_stack_bindings() {
+        for (_, shader) in PassParams::EFFECTS {
+            let source = fs::read_to_string(Path::new("assets").join(shader)).unwrap();
+            check_shader_source(&source).unwrap();
+            assert!(source.contains("@binding(3) var<uniform> params:"), "{}", shader);
+        }
+        let err = check_shader_source("@group(0) @binding(4) var<uniform> x: f32;\nfn fragment() {}");
+        assert!(err.unwrap_err().starts_with("line 1:"));
+        assert!(check_shader_source("@group(0) @binding(0) var t: texture_2d<f32>;").is_err());
+    }
+}
@@ -0,0 +1,441 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowPosition};
+use bevy_mod_imgui::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::camera::{PanOrbitSettings, PanOrbitState};
+use crate::layout::ViewportCamera;
+use crate::pp::{PostProcessPreset, PostProcessSettings};
+use crate::retrocamera::RetroRenderTarget;
+use crate::save_load::SceneFileState;
+use crate::ron_file;
+
+const PREFERENCES_PATH: &str = "config/preferences.ron";
+/// Dopo l'ultima modifica si aspetta un po' prima di scrivere (gli slider cambiano a ogni frame).
+const SAVE_DELAY: f32 = 1.0;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct WindowPreferences {
+    pub position: Option<[i32; 2]>,
+    pub size: Option<[f32; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CameraPreferences {
+    pub pan_sensitivity: f32,
+    pub orbit_sensitivity: f32,
+    pub zoom_sensitivity: f32,
+    pub scroll_line_sensitivity: f32,
+    pub scroll_pixel_sensitivity: f32,
+}
+
+impl Default for CameraPreferences {
+    fn default() -> Self {
+        Self::from(&PanOrbitSettings::default())
+    }
+}
+
+impl From<&PanOrbitSettings> for CameraPreferences {
+    fn from(settings: &PanOrbitSettings) -> Self {
+        Self {
+            pan_sensitivity: settings.pan_sensitivity,
+            orbit_sensitivity: settings.orbit_sensitivity,
+            zoom_sensitivity: settings.zoom_sensitivity,
+            scroll_line_sensitivity: settings.scroll_line_sensitivity,
+            scroll_pixel_sensitivity: settings.scroll_pixel_sensitivity,
+        }
+    }
+}
+
+impl CameraPreferences {
+    fn apply(&self, settings: &mut PanOrbitSettings) {
+        settings.pan_sensitivity = self.pan_sensitivity;
+        settings.orbit_sensitivity = self.orbit_sensitivity;
+        settings.zoom_sensitivity = self.zoom_sensitivity;
+        settings.scroll_line_sensitivity = self.scroll_line_sensitivity;
+        settings.scroll_pixel_sensitivity = self.scroll_pixel_sensitivity;
+    }
+}
+
+/// Impostazioni dell'editor, salvate in `config/preferences.ron`. I campi `None` lasciano
+/// i valori con cui l'editor parte.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Preferences {
+    pub window: WindowPreferences,
+    pub retro_resolution: [u32; 2],
+    pub post_process: Option<PostProcessPreset>,
+    pub light_color: Option<[f32; 3]>,
+    pub camera: CameraPreferences,
+    pub last_scene: Option<String>,
+    pub open_last_scene: bool,
+    /// Layout caricato all'avvio (vedi il menu Layout).
+    pub layout: String,
+    /// Risalva il layout di avvio quando si spostano o agganciano i pannelli.
+    pub autosave_layout: bool,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            window: WindowPreferences::default(),
+            retro_resolution: [320, 180],
+            post_process: None,
+            light_color: None,
+            camera: CameraPreferences::default(),
+            last_scene: None,
+            open_last_scene: true,
+            layout: "default".to_string(),
+            autosave_layout: true,
+        }
+    }
+}
+
+impl Preferences {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        ron_file::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        ron_file::save(self, path)
+    }
+}
+
+#[derive(Resource, Default)]
+struct PreferencesState {
+    /// Riporta le preferenze sull'editor (all'avvio, dopo Reload o Reset).
+    apply: bool,
+    /// Lo stato con cui l'editor è partito, prima delle preferenze: è quello di "Reset to defaults".
+    defaults: Preferences,
+    /// Istante dell'ultima modifica non ancora scritta su disco.
+    dirty_since: Option<f32>,
+    status: String,
+}
+
+/// Le preferenze che descrivono lo stato vivo dell'editor; i campi non tracciati vengono da `base`.
+fn live_preferences(
+    base: &Preferences,
+    scene: &SceneFileState,
+    retro: &RetroRenderTarget,
+    window: &Window,
+    light: &DirectionalLight,
+    editor: Option<(&PanOrbitSettings, &PostProcessSettings)>,
+) -> Preferences {
+    let mut current = base.clone();
+    current.window.size = Some([window.resolution.width(), window.resolution.height()]);
+    if let WindowPosition::At(position) = window.position {
+        current.window.position = Some(position.to_array());
+    }
+    current.retro_resolution = [retro.width, retro.height];
+    let linear = light.color.to_linear();
+    current.light_color = Some([linear.red, linear.green, linear.blue]);
+    if let Some((settings, post)) = editor {
+        current.camera = CameraPreferences::from(settings);
+        current.post_process = Some(PostProcessPreset::from(post));
+    }
+    if scene.opened.is_some() {
+        current.last_scene = scene.opened.clone();
+    }
+    current
+}
+
+/// Porta le preferenze sui componenti vivi; la scena si carica una volta sola, all'avvio.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn apply_preferences(
+    mut prefs_state: ResMut<PreferencesState>,
+    prefs: Res<Preferences>,
+    mut scene: ResMut<SceneFileState>,
+    mut retro: ResMut<RetroRenderTarget>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    mut light: Single<&mut DirectionalLight>,
+    mut editor: Query<
+        (&mut PanOrbitSettings, &mut PostProcessSettings),
+        (With<PanOrbitState>, Without<ViewportCamera>),
+    >,
+    mut startup: Local<bool>,
+) {
+    if !std::mem::take(&mut prefs_state.apply) {
+        return;
+    }
+    if !*startup {
+        prefs_state.defaults = live_preferences(
+            &Preferences::default(),
+            &scene,
+            &retro,
+            &window,
+            &light,
+            editor.iter().next(),
+        );
+    }
+    if let Some([width, height]) = prefs.window.size {
+        window.resolution.set(width.max(320.0), height.max(240.0));
+    }
+    if let Some([x, y]) = prefs.window.position {
+        window.position = WindowPosition::At(IVec2::new(x, y));
+    }
+    let [retro_width, retro_height] = prefs.retro_resolution;
+    if retro.width != retro_width || retro.height != retro_height {
+        retro.width = retro_width.max(1);
+        retro.height = retro_height.max(1);
+    }
+    if let Some([r, g, b]) = prefs.light_color {
+        light.color = Color::LinearRgba(LinearRgba::new(r, g, b, 1.0));
+    }
+    for (mut settings, mut post) in editor.iter_mut() {
+        prefs.camera.apply(&mut settings);
+        if let Some(post_process) = &prefs.post_process {
+            post_process.apply(&mut post);
+        }
+    }
+    if !*startup {
+        *startup = true;
+        if let (true, Some(last)) = (prefs.open_last_scene, &prefs.last_scene) {
+            if Path::new(last).exists() {
+                scene.path = last.clone();
+                scene.load_requested = true;
+            }
+        }
+    }
+}
+
+/// Fotografa lo stato dell'editor: se è cambiato, le preferenze si risalvano poco dopo.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn track_preferences(
+    time: Res<Time>,
+    mut prefs: ResMut<Preferences>,
+    mut prefs_state: ResMut<PreferencesState>,
+    scene: Res<SceneFileState>,
+    retro: Res<RetroRenderTarget>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    light: Single<&DirectionalLight>,
+    editor: Query<(&PanOrbitSettings, &PostProcessSettings), (With<PanOrbitState>, Without<ViewportCamera>)>,
+) {
+    let current = live_preferences(&prefs, &scene, &retro, &window, &light, editor.iter().next());
+    let now = time.elapsed_secs();
+    if current != *prefs {
+        *prefs = current;
+        prefs_state.dirty_since = Some(now);
+    }
+    if prefs_state
+        .dirty_since
+        .is_some_and(|since| now - since > SAVE_DELAY)
+    {
+        prefs_state.dirty_since = None;
+        if let Err(e) = prefs.save(Path::new(PREFERENCES_PATH)) {
+            prefs_state.status = format!("Save failed: {}", e);
+        }
+    }
+}
+
+/// In chiusura si scrive subito quello che è rimasto in sospeso.
+fn flush_preferences(
+    mut exit: EventReader<AppExit>,
+    prefs: Res<Preferences>,
+    mut prefs_state: ResMut<PreferencesState>,
+) {
+    if exit.read().next().is_none() || prefs_state.dirty_since.take().is_none() {
+        return;
+    }
+    if let Err(e) = prefs.save(Path::new(PREFERENCES_PATH)) {
+        error!("Preferenze non salvate: {}", e);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn preferences_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut prefs: ResMut<Preferences>,
+    mut prefs_state: ResMut<PreferencesState>,
+    mut retro: ResMut<RetroRenderTarget>,
+    mut editor: Query<&mut PanOrbitSettings, (With<PanOrbitState>, Without<ViewportCamera>)>,
+) {
+    let prefs_state = &mut *prefs_state;
+    let ui = context.ui();
+    ui.window("Preferences")
+        .size([360.0, 420.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            // Si modificano i valori vivi: `track_preferences` li riporta nel file.
+            if ui.collapsing_header("Camera", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                if let Some(mut settings) = editor.iter_mut().next() {
+                    ui.slider_config("Pan", 0.0001, 0.01)
+                        .display_format("%.4f")
+                        .build(&mut settings.pan_sensitivity);
+                    let mut orbit = settings.orbit_sensitivity.to_degrees();
+                    if ui.slider_config("Orbit (deg/px)", 0.01, 1.0).build(&mut orbit) {
+                        settings.orbit_sensitivity = orbit.to_radians();
+                    }
+                    ui.slider_config("Zoom", 0.001, 0.1)
+                        .display_format("%.3f")
+                        .build(&mut settings.zoom_sensitivity);
+                    ui.slider("Scroll (lines)", 1.0, 64.0, &mut settings.scroll_line_sensitivity);
+                    ui.slider("Scroll (pixels)", 0.1, 4.0, &mut settings.scroll_pixel_sensitivity);
+                } else {
+                    ui.text_disabled("Editor camera not found");
+                }
+            }
+            if ui.collapsing_header("Retro resolution", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                let mut size = [retro.width as i32, retro.height as i32];
+                if ui.input_int2("Width x height", &mut size).build()
+                    && size[0] > 0
+                    && size[1] > 0
+                {
+                    retro.width = size[0] as u32;
+                    retro.height = size[1] as u32;
+                }
+            }
+            if ui.collapsing_header("Startup", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                ui.checkbox("Open last scene", &mut prefs.open_last_scene);
+                ui.text_disabled(format!(
+                    "Last scene: {}",
+                    prefs.last_scene.as_deref().unwrap_or("none")
+                ));
+                ui.input_text("Layout", &mut prefs.layout).build();
+                ui.checkbox("Save panel layout automatically", &mut prefs.autosave_layout);
+            }
+
+            ui.separator();
+            if ui.button("Reload") {
+                prefs_state.status = match Preferences::load(Path::new(PREFERENCES_PATH)) {
+                    Ok(loaded) => {
+                        *prefs = loaded;
+                        prefs_state.apply = true;
+                        format!("Loaded {}", PREFERENCES_PATH)
+                    }
+                    Err(e) => format!("Load failed: {}", e),
+                };
+            }
+            ui.same_line();
+            if ui.button("Reset to defaults") {
+                *prefs = prefs_state.defaults.clone();
+                prefs_state.apply = true;
+            }
+            ui.text_disabled(format!("Saved automatically to {}", PREFERENCES_PATH));
+            if !prefs_state.status.is_empty() {
+                ui.text_wrapped(&prefs_state.status);
+            }
+        });
+}
+
+pub struct PreferencesPlugin;
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        let prefs = match Preferences::load(Path::new(PREFERENCES_PATH)) {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                info!("Preferenze: uso i valori di default ({})", e);
+                Preferences::default()
+            }
+        };
+        app.insert_resource(prefs)
+            .insert_resource(PreferencesState {
+                apply: true,
+                ..default()
+            })
+            .add_systems(
+                Update,
+                // Reload e Reset si applicano nello stesso frame, prima che il tracking
+                // rilegga lo stato vivo e li sovrascriva.
+                (preferences_ui, apply_preferences, track_preferences).chain(),
+            )
+            .add_systems(Last, flush_preferences);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor_app(prefs: Preferences) -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<SceneFileState>()
+            .insert_resource(RetroRenderTarget {
+                width: 320,
+                height: 180,
+                handle: None,
+            })
+            .insert_resource(prefs)
+            .insert_resource(PreferencesState {
+                apply: true,
+                ..default()
+            })
+            .add_systems(Update, (apply_preferences, track_preferences).chain());
+        app.world_mut().spawn((Window::default(), PrimaryWindow));
+        app.world_mut().spawn(DirectionalLight::default());
+        app.world_mut().spawn((
+            PanOrbitState::default(),
+            PanOrbitSettings::default(),
+            PostProcessSettings::default(),
+        ));
+        app
+    }
+
+    fn custom() -> Preferences {
+        let post = PostProcessPreset {
+            contrast: 2.5,
+            dither_mode: 0,
+            ..default()
+        };
+        Preferences {
+            window: WindowPreferences {
+                position: Some([40, 60]),
+                size: Some([1024.0, 768.0]),
+            },
+            retro_resolution: [160, 144],
+            post_process: Some(post),
+            light_color: Some([0.5, 0.25, 1.0]),
+            camera: CameraPreferences {
+                pan_sensitivity: 0.005,
+                ..default()
+            },
+            ..default()
+        }
+    }
+
+    /// Quello che finisce nelle preferenze dopo un frame con le preferenze applicate.
+    fn tracked(app: &mut App) -> Preferences {
+        app.update();
+        app.world().resource::<Preferences>().clone()
+    }
+
+    fn request(app: &mut App, prefs: Preferences) {
+        *app.world_mut().resource_mut::<Preferences>() = prefs;
+        app.world_mut().resource_mut::<PreferencesState>().apply = true;
+    }
+
+    #[test]
+    fn applied_preferences_survive_tracking() {
+        let mut app = editor_app(Preferences::default());
+        let defaults = tracked(&mut app);
+        assert_eq!(defaults.retro_resolution, [320, 180]);
+
+        // Come il pulsante Reload: le preferenze lette vanno sull'editor e ci restano.
+        request(&mut app, custom());
+        assert!(tracked(&mut app) == custom());
+        assert!(tracked(&mut app) == custom());
+        let retro = app.world().resource::<RetroRenderTarget>();
+        assert_eq!([retro.width, retro.height], [160, 144]);
+
+        // Reset to defaults torna allo stato con cui l'editor è partito.
+        let reset = app.world().resource::<PreferencesState>().defaults.clone();
+        request(&mut app, reset);
+        let prefs = tracked(&mut app);
+        assert_eq!(prefs.retro_resolution, [320, 180]);
+        assert!(prefs.camera == CameraPreferences::default());
+        assert!(prefs.light_color == defaults.light_color);
+        assert!(prefs.post_process == defaults.post_process);
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let path = std::env::temp_dir().join("xirai_preferences_test.ron");
+        custom().save(&path).unwrap();
+        let loaded = Preferences::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(loaded == custom());
+    }
+}
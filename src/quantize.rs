@@ -1,213 +0,0 @@
-use bevy::{
-    prelude::*,
-    render::{
-        extract_component::{ExtractComponent, ExtractComponentPlugin},
-        render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
-        render_resource::{
-            BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
-            BindGroupLayoutEntry, BindingResource, BindingType, CachedRenderPipelineId,
-            ColorTargetState, ColorWrites, FragmentState, MultisampleState,
-            Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
-            RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
-            SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType, TextureFormat,
-            TextureSampleType, TextureViewDimension, VertexState, LoadOp, StoreOp,
-        },
-        renderer::{RenderContext, RenderDevice},
-        texture::DefaultImageSampler
-        view::{ViewTarget, ViewUniformOffset},
-        Render, RenderApp, RenderSet,
-    },
-    core_pipeline::core_3d::graph::{Core3d, Node3d},
-};
-
-pub struct PastelloPostProcessPlugin;
-
-impl Plugin for PastelloPostProcessPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_plugins(ExtractComponentPlugin::<PastelloSettings>::default());
-        
-        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
-            return;
-        };
-
-        render_app
-            .init_resource::<PastelloPipeline>()
-            .add_render_graph_node::<ViewNodeRunner<PastelloNode>>(
-                Core3d,
-                PastelloLabel,
-            )
-            .add_render_graph_edges(
-                Core3d,
-                (
-                    Node3d::Tonemapping,
-                    PastelloLabel,
-                    Node3d::EndMainPassPostProcessing,
-                ),
-            );
-    }
-
-    fn finish(&self, app: &mut App) {
-        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
-            return;
-        };
-        render_app.init_resource::<PastelloPipeline>();
-    }
-}
-
-#[derive(Component, Default, Clone, ExtractComponent, ShaderType)]
-pub struct PastelloSettings {
-    pub intensity: f32,
-    pub color_levels: f32,
-    pub outline_strength: f32,
-    pub outline_threshold: f32,
-}
-
-impl PastelloSettings {
-    pub fn new() -> Self {
-        Self {
-            intensity: 1.0,
-            color_levels: 8.0,
-            outline_strength: 2.0,
-            outline_threshold: 0.1,
-        }
-    }
-}
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
-struct PastelloLabel;
-
-#[derive(Default)]
-struct PastelloNode;
-
-impl ViewNode for PastelloNode {
-    type ViewQuery = &'static PastelloSettings;
-
-    fn run(
-        &self,
-        _graph: &mut RenderGraphContext,
-        render_context: &mut RenderContext,
-        settings: QueryItem<Self::ViewQuery>,
-        world: &World,
-    ) -> Result<(), NodeRunError> {
-        let pipeline_cache = world.resource::<PipelineCache>();
-        let pipeline = world.resource::<PastelloPipeline>();
-
-        let Some(pipeline_id) = pipeline_cache.get_render_pipeline(pipeline.pipeline_id) else {
-            return Ok(());
-        };
-
-        let view_target = ViewTarget::get(world)?;
-        let source = &view_target.main_texture_view();
-        let destination = view_target.out_texture();
-
-        let bind_group = render_context.render_device().create_bind_group(
-            "pastello_bind_group",
-            &pipeline.layout,
-            &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(source),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(&pipeline.sampler),
-                },
-            ],
-        );
-
-        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("pastello_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: destination,
-                resolve_target: None,
-                ops: Operations {
-                    load: LoadOp::Clear(Color::BLACK.into()),
-                    store: StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        render_pass.set_render_pipeline(pipeline_id);
-        render_pass.set_bind_group(0, &bind_group, &[]);
-        render_pass.draw(0..3, 0..1);
-
-        Ok(())
-    }
-}
-
-#[derive(Resource)]
-struct PastelloPipeline {
-    layout: BindGroupLayout,
-    sampler: Sampler,
-    pipeline_id: CachedRenderPipelineId,
-}
-
-impl FromWorld for PastelloPipeline {
-    fn from_world(world: &mut World) -> Self {
-        let render_device = world.resource::<RenderDevice>();
-        
-        let layout = render_device.create_bind_group_layout(
-            "pastello_bind_group_layout",
-            &[
-                BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Texture {
-                        multisampled: false,
-                        sample_type: TextureSampleType::Float { filterable: true },
-                        view_dimension: TextureViewDimension::D2,
-                    },
-                    count: None,
-                },
-                BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-        );
-
-        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
-
-        let shader = world
-            .resource::<AssetServer>()
-            .load("shaders/pastello.wgsl");
-        
-        let pipeline_id = world
-            .resource_mut::<PipelineCache>()
-            .queue_render_pipeline(RenderPipelineDescriptor {
-                label: Some("pastello_pipeline".into()),
-                layout: vec![layout.clone()],
-                vertex: VertexState {
-                    shader: shader.clone(),
-                    shader_defs: vec![],
-                    entry_point: "vs_main".into(),
-                    buffers: vec![],
-                },
-                fragment: Some(FragmentState {
-                    shader,
-                    shader_defs: vec![],
-                    entry_point: "fs_main".into(),
-                    targets: vec![Some(ColorTargetState {
-                        format: TextureFormat::bevy_default(),
-                        blend: None,
-                        write_mask: ColorWrites::ALL,
-                    })],
-                }),
-                primitive: PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                push_constant_ranges: vec![],
-            });
-
-        Self {
-            layout,
-            sampler,
-            pipeline_id,
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,627 @@
+use crate::skeleton::{HumanoidBone, SkeletonMap};
+use bevy::animation::AnimationTarget;
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::collections::HashMap;
+
+/// Ossa simulate dal ragdoll. L'ordine di `ALL` va dai genitori ai figli.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum RagdollBone {
+    Pelvis,
+    Spine,
+    Head,
+    UpperArmLeft,
+    ForearmLeft,
+    HandLeft,
+    UpperArmRight,
+    ForearmRight,
+    HandRight,
+    ThighLeft,
+    ShinLeft,
+    FootLeft,
+    ThighRight,
+    ShinRight,
+    FootRight,
+}
+
+impl RagdollBone {
+    pub const ALL: [RagdollBone; 15] = [
+        RagdollBone::Pelvis,
+        RagdollBone::Spine,
+        RagdollBone::Head,
+        RagdollBone::UpperArmLeft,
+        RagdollBone::ForearmLeft,
+        RagdollBone::HandLeft,
+        RagdollBone::UpperArmRight,
+        RagdollBone::ForearmRight,
+        RagdollBone::HandRight,
+        RagdollBone::ThighLeft,
+        RagdollBone::ShinLeft,
+        RagdollBone::FootLeft,
+        RagdollBone::ThighRight,
+        RagdollBone::ShinRight,
+        RagdollBone::FootRight,
+    ];
+
+    /// Osso a cui questo è attaccato con un giunto.
+    pub fn parent(self) -> Option<RagdollBone> {
+        use RagdollBone::*;
+        match self {
+            Pelvis => None,
+            Spine | ThighLeft | ThighRight => Some(Pelvis),
+            Head | UpperArmLeft | UpperArmRight => Some(Spine),
+            ForearmLeft => Some(UpperArmLeft),
+            HandLeft => Some(ForearmLeft),
+            ForearmRight => Some(UpperArmRight),
+            HandRight => Some(ForearmRight),
+            ShinLeft => Some(ThighLeft),
+            FootLeft => Some(ShinLeft),
+            ShinRight => Some(ThighRight),
+            FootRight => Some(ShinRight),
+        }
+    }
+
+    /// Ossa umanoidi candidate, in ordine di preferenza.
+    fn humanoid(self) -> &'static [HumanoidBone] {
+        use RagdollBone::*;
+        match self {
+            Pelvis => &[HumanoidBone::Hips],
+            Spine => &[HumanoidBone::Chest, HumanoidBone::Spine],
+            Head => &[HumanoidBone::Head],
+            UpperArmLeft => &[HumanoidBone::UpperArmLeft],
+            ForearmLeft => &[HumanoidBone::LowerArmLeft],
+            HandLeft => &[HumanoidBone::HandLeft],
+            UpperArmRight => &[HumanoidBone::UpperArmRight],
+            ForearmRight => &[HumanoidBone::LowerArmRight],
+            HandRight => &[HumanoidBone::HandRight],
+            ThighLeft => &[HumanoidBone::UpperLegLeft],
+            ShinLeft => &[HumanoidBone::LowerLegLeft],
+            FootLeft => &[HumanoidBone::FootLeft],
+            ThighRight => &[HumanoidBone::UpperLegRight],
+            ShinRight => &[HumanoidBone::LowerLegRight],
+            FootRight => &[HumanoidBone::FootRight],
+        }
+    }
+
+    /// Osso verso cui punta la capsula; le estremità usano una sfera.
+    fn tail(self) -> Option<RagdollBone> {
+        use RagdollBone::*;
+        match self {
+            Pelvis => Some(Spine),
+            Spine => Some(Head),
+            UpperArmLeft => Some(ForearmLeft),
+            ForearmLeft => Some(HandLeft),
+            UpperArmRight => Some(ForearmRight),
+            ForearmRight => Some(HandRight),
+            ThighLeft => Some(ShinLeft),
+            ShinLeft => Some(FootLeft),
+            ThighRight => Some(ShinRight),
+            ShinRight => Some(FootRight),
+            Head | HandLeft | HandRight | FootLeft | FootRight => None,
+        }
+    }
+}
+
+/// Giunto verso l'osso genitore, con limiti in gradi.
+#[derive(Debug, Clone, Copy, Reflect)]
+pub enum RagdollJoint {
+    /// Spalla, anca, collo...: stesso limite su tutti gli assi.
+    Spherical { limit: f32 },
+    /// Gomito, ginocchio: rotazione su un asse locale dell'osso.
+    Hinge { axis: Vec3, min: f32, max: f32 },
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub struct RagdollBoneSpec {
+    /// Raggio del collider in metri.
+    pub radius: f32,
+    pub joint: RagdollJoint,
+}
+
+/// Osso trasformato in corpo rigido, con ciò che serve per ricostruirne collider e giunto.
+#[derive(Debug, Clone, Reflect)]
+struct RagdollBody {
+    bone: RagdollBone,
+    entity: Entity,
+    /// Collider spawnato come figlio dell'osso.
+    collider: Entity,
+    /// Genitore nello scheletro: il corpo ne viene staccato finché la fisica lo muove.
+    skeleton_parent: Option<Entity>,
+    /// Corpo a cui è agganciato il giunto, con ancora e rotazione relativa al momento dell'attivazione.
+    joint_parent: Option<Entity>,
+    anchor: Vec3,
+    relative: Quat,
+    /// Scala dell'osso (armature a 0.01): raggi e lunghezze sono nel suo spazio.
+    scale: f32,
+    tail: Option<Vec3>,
+}
+
+impl RagdollBody {
+    fn collider(&self, spec: &RagdollBoneSpec) -> Collider {
+        let radius = spec.radius / self.scale.max(f32::EPSILON);
+        match self.tail {
+            Some(tail) if tail.length() > radius * 2.0 => {
+                let direction = tail.normalize();
+                Collider::capsule(direction * radius, tail - direction * radius, radius)
+            }
+            _ => Collider::ball(radius),
+        }
+    }
+
+    fn joint(&self, spec: &RagdollBoneSpec) -> Option<ImpulseJoint> {
+        let parent = self.joint_parent?;
+        // Le ancore dei giunti sono nel frame del corpo rigido: posizione e rotazione, senza scala.
+        let joint: TypedJoint = match spec.joint {
+            RagdollJoint::Spherical { limit } => {
+                let limit = limit.to_radians();
+                SphericalJointBuilder::new()
+                    .local_anchor1(self.anchor)
+                    .local_anchor2(Vec3::ZERO)
+                    .limits(JointAxis::AngX, [-limit, limit])
+                    .limits(JointAxis::AngY, [-limit, limit])
+                    .limits(JointAxis::AngZ, [-limit, limit])
+                    .into()
+            }
+            RagdollJoint::Hinge { axis, min, max } => RevoluteJointBuilder::new(axis)
+                .local_anchor1(self.anchor)
+                .local_anchor2(Vec3::ZERO)
+                .local_axis1(self.relative * axis)
+                .local_axis2(axis)
+                .limits([min.to_radians(), max.to_radians()])
+                .into(),
+        };
+        Some(ImpulseJoint::new(parent, joint))
+    }
+}
+
+#[derive(Debug, Clone, Reflect)]
+enum RagdollState {
+    Animated,
+    Simulated,
+    /// Blend dalla posa fisica (`from`) alla posa animata.
+    Recovering {
+        elapsed: f32,
+        from: Vec<(Entity, Transform)>,
+    },
+}
+
+/// Ragdoll attivabile per personaggio: raggi e limiti dei giunti sono configurabili,
+/// le ossa vengono dalla `SkeletonMap` del personaggio.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Ragdoll {
+    pub bones: HashMap<RagdollBone, RagdollBoneSpec>,
+    /// Durata del ritorno all'animazione in secondi.
+    pub blend_time: f32,
+    state: RagdollState,
+    bodies: Vec<RagdollBody>,
+    /// Target d'animazione tolti alle ossa mentre la fisica le muove.
+    targets: Vec<(Entity, AnimationTarget)>,
+    /// Raggi o limiti cambiati dalla UI: vanno riapplicati al ragdoll in corso.
+    specs_changed: bool,
+}
+
+impl Default for Ragdoll {
+    fn default() -> Self {
+        use RagdollBone::*;
+        let spherical = |limit| RagdollJoint::Spherical { limit };
+        let elbow = RagdollJoint::Hinge {
+            axis: Vec3::X,
+            min: 0.0,
+            max: 140.0,
+        };
+        let knee = RagdollJoint::Hinge {
+            axis: Vec3::X,
+            min: -140.0,
+            max: 0.0,
+        };
+        let bones = [
+            (Pelvis, 0.15, spherical(0.0)),
+            (Spine, 0.15, spherical(30.0)),
+            (Head, 0.1, spherical(45.0)),
+            (UpperArmLeft, 0.05, spherical(80.0)),
+            (ForearmLeft, 0.04, elbow),
+            (HandLeft, 0.04, spherical(40.0)),
+            (UpperArmRight, 0.05, spherical(80.0)),
+            (ForearmRight, 0.04, elbow),
+            (HandRight, 0.04, spherical(40.0)),
+            (ThighLeft, 0.07, spherical(60.0)),
+            (ShinLeft, 0.05, knee),
+            (FootLeft, 0.05, spherical(30.0)),
+            (ThighRight, 0.07, spherical(60.0)),
+            (ShinRight, 0.05, knee),
+            (FootRight, 0.05, spherical(30.0)),
+        ]
+        .into_iter()
+        .map(|(bone, radius, joint)| (bone, RagdollBoneSpec { radius, joint }))
+        .collect();
+
+        Self {
+            bones,
+            blend_time: 0.6,
+            state: RagdollState::Animated,
+            bodies: Vec::new(),
+            targets: Vec::new(),
+            specs_changed: false,
+        }
+    }
+}
+
+impl Ragdoll {
+    pub fn is_simulated(&self) -> bool {
+        matches!(self.state, RagdollState::Simulated)
+    }
+}
+
+/// Presente sulla radice del personaggio finché il ragdoll non è tornato all'animazione.
+#[derive(Component)]
+pub struct RagdollActive;
+
+/// Accende (`enabled = true`) o spegne il ragdoll di un personaggio, es. alla morte o a un colpo.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SetRagdoll {
+    pub entity: Entity,
+    pub enabled: bool,
+}
+
+/// Ossa del ragdoll presenti nello scheletro del personaggio.
+fn resolve_bones(ragdoll: &Ragdoll, skeleton: &SkeletonMap) -> HashMap<RagdollBone, Entity> {
+    ragdoll
+        .bones
+        .keys()
+        .filter_map(|&bone| {
+            let entity = bone.humanoid().iter().find_map(|h| skeleton.bone(*h))?;
+            Some((bone, entity))
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_ragdoll_events(
+    mut commands: Commands,
+    mut events: EventReader<SetRagdoll>,
+    mut ragdolls: Query<(&mut Ragdoll, Option<&SkeletonMap>)>,
+    children: Query<&Children>,
+    globals: Query<&GlobalTransform>,
+    transforms: Query<&Transform>,
+    targets: Query<&AnimationTarget>,
+    colliders: Query<(), With<Collider>>,
+    parents: Query<&ChildOf>,
+) {
+    for event in events.read() {
+        let Ok((mut ragdoll, skeleton)) = ragdolls.get_mut(event.entity) else {
+            continue;
+        };
+        match (event.enabled, ragdoll.is_simulated()) {
+            (true, false) => {
+                // Se stava ancora tornando all'animazione si riparte dalla posa corrente.
+                let bones = skeleton
+                    .map(|skeleton| resolve_bones(&ragdoll, skeleton))
+                    .unwrap_or_default();
+                if !bones.contains_key(&RagdollBone::Pelvis) {
+                    warn!("Ragdoll: bacino non trovato, controlla il profilo nella finestra Skeleton");
+                    continue;
+                }
+                activate(
+                    &mut commands,
+                    event.entity,
+                    &mut ragdoll,
+                    &bones,
+                    &globals,
+                    &targets,
+                    &parents,
+                );
+                // Il collider del controller non deve spingere le ossa.
+                for child in children.get(event.entity).into_iter().flatten() {
+                    if colliders.contains(*child) {
+                        commands.entity(*child).insert(ColliderDisabled);
+                    }
+                }
+                commands.entity(event.entity).insert(RagdollActive);
+            }
+            (false, true) => {
+                deactivate(&mut commands, event.entity, &mut ragdoll, &globals, &transforms);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn activate(
+    commands: &mut Commands,
+    root: Entity,
+    ragdoll: &mut Ragdoll,
+    bones: &HashMap<RagdollBone, Entity>,
+    globals: &Query<&GlobalTransform>,
+    targets: &Query<&AnimationTarget>,
+    parents: &Query<&ChildOf>,
+) {
+    ragdoll.bodies.clear();
+    ragdoll.targets.clear();
+
+    for bone in RagdollBone::ALL {
+        let (Some(&entity), Some(spec)) = (bones.get(&bone), ragdoll.bones.get(&bone)) else {
+            continue;
+        };
+        let Ok(global) = globals.get(entity) else {
+            continue;
+        };
+        let (scale, rotation, translation) = global.to_scale_rotation_translation();
+        let inverse = global.affine().inverse();
+
+        let tail = bone
+            .tail()
+            .and_then(|tail| bones.get(&tail))
+            .and_then(|&tail| globals.get(tail).ok())
+            .map(|tail| inverse.transform_point3(tail.translation()));
+        let parent = bone
+            .parent()
+            .and_then(|parent| bones.get(&parent))
+            .and_then(|&parent| Some((parent, globals.get(parent).ok()?)));
+        let (anchor, relative) = parent.map_or((Vec3::ZERO, Quat::IDENTITY), |(_, parent_global)| {
+            let (_, parent_rotation, parent_translation) =
+                parent_global.to_scale_rotation_translation();
+            (
+                parent_rotation.inverse() * (translation - parent_translation),
+                parent_rotation.inverse() * rotation,
+            )
+        });
+        let mut body = RagdollBody {
+            bone,
+            entity,
+            collider: Entity::PLACEHOLDER,
+            skeleton_parent: parents.get(entity).ok().map(|p| p.parent()),
+            joint_parent: parent.map(|(parent, _)| parent),
+            anchor,
+            relative,
+            scale: scale.max_element(),
+            tail,
+        };
+
+        // Il collider vive nello spazio locale dell'osso, che può essere scalato.
+        body.collider = commands
+            .spawn((body.collider(spec), Transform::IDENTITY, ChildOf(entity)))
+            .id();
+
+        // Staccato dallo scheletro: Rapier scrive la posa del corpo direttamente in world space,
+        // senza passare da genitori che si muovono anche loro.
+        let mut commands_body = commands.entity(entity);
+        commands_body
+            .remove::<ChildOf>()
+            .insert((global.compute_transform(), RigidBody::Dynamic, Velocity::zero()));
+        if let Some(joint) = body.joint(spec) {
+            commands_body.insert(joint);
+        }
+
+        if let Ok(target) = targets.get(entity) {
+            ragdoll.targets.push((entity, *target));
+            commands.entity(entity).remove::<AnimationTarget>();
+        }
+        ragdoll.bodies.push(body);
+    }
+
+    ragdoll.specs_changed = false;
+    ragdoll.state = RagdollState::Simulated;
+    info!("Ragdoll attivato su {:?}", root);
+}
+
+fn deactivate(
+    commands: &mut Commands,
+    root: Entity,
+    ragdoll: &mut Ragdoll,
+    globals: &Query<&GlobalTransform>,
+    transforms: &Query<&Transform>,
+) {
+    for &(entity, target) in &ragdoll.targets {
+        commands.entity(entity).insert(target);
+    }
+
+    // Il personaggio si rialza dove è caduto il bacino: la radice si sposta sotto di lui.
+    let delta = ragdoll
+        .bodies
+        .first()
+        .and_then(|pelvis| globals.get(pelvis.entity).ok())
+        .zip(globals.get(root).ok())
+        .map_or(Vec3::ZERO, |(pelvis, root)| {
+            (pelvis.translation() - root.translation()).with_y(0.0)
+        });
+    if let Ok(root_transform) = transforms.get(root) {
+        let mut moved_root = *root_transform;
+        moved_root.translation += delta;
+        commands.entity(root).insert(moved_root);
+    }
+
+    // Ogni osso torna figlio del suo genitore, con la posa fisica espressa rispetto a lui.
+    // I genitori fuori dal ragdoll si spostano con la radice; quelli simulati sono già in world.
+    let simulated: Vec<Entity> = ragdoll.bodies.iter().map(|body| body.entity).collect();
+    let mut from = Vec::with_capacity(ragdoll.bodies.len());
+    for body in &ragdoll.bodies {
+        commands.entity(body.collider).despawn();
+        let mut entity = commands.entity(body.entity);
+        entity.remove::<(RigidBody, Velocity, ImpulseJoint)>();
+        let (Some(parent), Ok(global)) = (body.skeleton_parent, globals.get(body.entity)) else {
+            continue;
+        };
+        let Ok(parent_global) = globals.get(parent) else {
+            continue;
+        };
+        let parent_affine = if simulated.contains(&parent) {
+            parent_global.affine()
+        } else {
+            Affine3A::from_translation(delta) * parent_global.affine()
+        };
+        let local = Transform::from_matrix(Mat4::from(parent_affine.inverse() * global.affine()));
+        entity.insert((ChildOf(parent), local));
+        from.push((body.entity, local));
+    }
+
+    ragdoll.bodies.clear();
+    ragdoll.targets.clear();
+    ragdoll.state = RagdollState::Recovering { elapsed: 0.0, from };
+}
+
+/// Raggi e limiti modificati dalla UI valgono subito sul ragdoll in corso.
+fn apply_live_specs(mut commands: Commands, mut ragdolls: Query<&mut Ragdoll>) {
+    for mut ragdoll in ragdolls.iter_mut() {
+        if !ragdoll.specs_changed {
+            continue;
+        }
+        ragdoll.specs_changed = false;
+        if !ragdoll.is_simulated() {
+            continue;
+        }
+        for body in &ragdoll.bodies {
+            let Some(spec) = ragdoll.bones.get(&body.bone) else {
+                continue;
+            };
+            commands.entity(body.collider).insert(body.collider(spec));
+            if let Some(joint) = body.joint(spec) {
+                commands.entity(body.entity).insert(joint);
+            }
+        }
+    }
+}
+
+/// Dopo l'animazione: mescola la posa fisica salvata con quella animata finché il blend non finisce.
+fn blend_ragdoll_recovery(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ragdolls: Query<(Entity, &mut Ragdoll, Option<&Children>)>,
+    mut transforms: Query<&mut Transform>,
+    disabled: Query<(), With<ColliderDisabled>>,
+) {
+    for (entity, mut ragdoll, children) in ragdolls.iter_mut() {
+        let blend_time = ragdoll.blend_time;
+        let RagdollState::Recovering { elapsed, from } = &mut ragdoll.state else {
+            continue;
+        };
+        *elapsed += time.delta_secs();
+        let t = if blend_time > 0.0 {
+            (*elapsed / blend_time).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let t = t * t * (3.0 - 2.0 * t);
+
+        for (bone, pose) in from.iter() {
+            let Ok(mut transform) = transforms.get_mut(*bone) else {
+                continue;
+            };
+            // `transform` contiene la posa animata di questo frame.
+            let animated = *transform;
+            *transform = Transform {
+                translation: pose.translation.lerp(animated.translation, t),
+                rotation: pose.rotation.slerp(animated.rotation, t),
+                scale: pose.scale.lerp(animated.scale, t),
+            };
+        }
+
+        if t >= 1.0 {
+            ragdoll.state = RagdollState::Animated;
+            commands.entity(entity).remove::<RagdollActive>();
+            for child in children.into_iter().flatten() {
+                if disabled.contains(*child) {
+                    commands.entity(*child).remove::<ColliderDisabled>();
+                }
+            }
+        }
+    }
+}
+
+/// In Play (di default K): cade / si rialza il personaggio giocabile.
+fn toggle_character_ragdoll(
+    actions: Res<crate::input::ActionState>,
+    characters: Query<(Entity, &Ragdoll), With<crate::character::Character>>,
+    mut events: EventWriter<SetRagdoll>,
+) {
+    if !actions.just_pressed(crate::input::Action::ToggleRagdoll) {
+        return;
+    }
+    for (entity, ragdoll) in characters.iter() {
+        events.write(SetRagdoll {
+            entity,
+            enabled: !ragdoll.is_simulated(),
+        });
+    }
+}
+
+fn ragdoll_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut ragdolls: Query<(Entity, &mut Ragdoll, Option<&Name>)>,
+    mut events: EventWriter<SetRagdoll>,
+) {
+    let ui = context.ui();
+    ui.window("Ragdoll")
+        .size([320.0, 300.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            for (entity, mut ragdoll, name) in ragdolls.iter_mut() {
+                let _id = ui.push_id(format!("{:?}", entity));
+                let label = name.map_or_else(|| format!("{:?}", entity), |n| n.to_string());
+                let status = match ragdoll.state {
+                    RagdollState::Animated => "animated",
+                    RagdollState::Simulated => "ragdoll",
+                    RagdollState::Recovering { .. } => "recovering",
+                };
+                ui.text(format!("{} ({})", label, status));
+                if ui.button(if ragdoll.is_simulated() { "Get up" } else { "Ragdoll" }) {
+                    events.write(SetRagdoll {
+                        entity,
+                        enabled: !ragdoll.is_simulated(),
+                    });
+                }
+                ui.slider("Blend back (s)", 0.0, 3.0, &mut ragdoll.blend_time);
+
+                // Le ossa vengono dalla SkeletonMap; qui solo raggi e limiti.
+                let mut changed = false;
+                if let Some(_node) = ui.tree_node("Joints") {
+                    for bone in RagdollBone::ALL {
+                        let Some(spec) = ragdoll.bones.get_mut(&bone) else {
+                            continue;
+                        };
+                        let _bone_id = ui.push_id(format!("{:?}", bone));
+                        ui.text(format!("{:?}", bone));
+                        changed |= ui.slider("Radius", 0.01, 0.3, &mut spec.radius);
+                        changed |= match &mut spec.joint {
+                            RagdollJoint::Spherical { limit } => {
+                                ui.slider("Limit", 0.0, 180.0, limit)
+                            }
+                            RagdollJoint::Hinge { min, max, .. } => {
+                                ui.slider("Min", -180.0, 180.0, min)
+                                    | ui.slider("Max", -180.0, 180.0, max)
+                            }
+                        };
+                    }
+                }
+                if changed {
+                    ragdoll.specs_changed = true;
+                }
+                ui.separator();
+            }
+        });
+}
+
+pub struct RagdollPlugin;
+impl Plugin for RagdollPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Ragdoll>()
+            .add_event::<SetRagdoll>()
+            .add_systems(
+                Update,
+                (handle_ragdoll_events, ragdoll_ui, apply_live_specs.after(ragdoll_ui)),
+            )
+            .add_systems(
+                Update,
+                toggle_character_ragdoll
+                    .before(handle_ragdoll_events)
+                    .in_set(crate::play_mode::PlaySet),
+            )
+            .add_systems(
+                PostUpdate,
+                blend_ragdoll_recovery
+                    .after(bevy::app::Animation)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
@@ -1,6 +1,254 @@
+use crate::assets::{spawn_model, SpawnedAsset};
+use crate::camera::{CameraBookmark, CameraBookmarks};
+use crate::ik::IkConstraint;
+use crate::ik_editor::PendingIkRig;
+use crate::play_mode::EditorMode;
+use crate::ron_file;
 use bevy::prelude::*;
-pub struct SavePlugin;
+use bevy_mod_imgui::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct SavedTransform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl From<&Transform> for SavedTransform {
+    fn from(transform: &Transform) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            scale: transform.scale.to_array(),
+        }
+    }
+}
+
+impl From<SavedTransform> for Transform {
+    fn from(saved: SavedTransform) -> Self {
+        Transform {
+            translation: Vec3::from_array(saved.translation),
+            rotation: Quat::from_array(saved.rotation).normalize(),
+            scale: Vec3::from_array(saved.scale),
+        }
+    }
+}
+
+/// Vincolo IK di un modello: l'osso è indicato per nome, le maniglie per posizione
+/// relativa alla radice del modello.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedIkConstraint {
+    pub bone: String,
+    pub chain_length: usize,
+    pub iterations: usize,
+    pub enabled: bool,
+    pub pole_angle: f32,
+    pub target: [f32; 3],
+    #[serde(default)]
+    pub pole: Option<[f32; 3]>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedAsset {
+    pub model_path: String,
+    pub transform: SavedTransform,
+    #[serde(default)]
+    pub ik: Vec<SavedIkConstraint>,
+}
+
+/// Rig IK di un modello che non è nella lista degli asset (es. il personaggio):
+/// al load si ritrova dal path della sua `SceneRoot`.
+#[derive(Serialize, Deserialize)]
+pub struct SavedRig {
+    pub scene: String,
+    pub ik: Vec<SavedIkConstraint>,
+}
+
+/// Contenuto di un file `.ron` della scena.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SceneFile {
+    pub assets: Vec<SavedAsset>,
+    #[serde(default)]
+    pub rigs: Vec<SavedRig>,
+    #[serde(default)]
+    pub bookmarks: Vec<CameraBookmark>,
+}
 
-fn save_items(){
-    
-}
\ No newline at end of file
+impl SceneFile {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        ron_file::load(path)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        ron_file::save(self, path)
+    }
+}
+
+#[derive(Resource)]
+pub struct SceneFileState {
+    pub path: String,
+    /// Ultima scena salvata o caricata con successo (finisce nelle preferenze).
+    pub opened: Option<String>,
+    /// Carica `path` al prossimo frame in Edit, come il pulsante Load.
+    pub load_requested: bool,
+    status: String,
+}
+
+impl Default for SceneFileState {
+    fn default() -> Self {
+        Self {
+            path: "maps/scene.ron".to_string(),
+            opened: None,
+            load_requested: false,
+            status: String::new(),
+        }
+    }
+}
+
+/// Vincoli IK sotto `root`, con le maniglie espresse nello spazio della radice.
+fn collect_ik(
+    root: Entity,
+    children: &Query<&Children>,
+    constraints: &Query<(&IkConstraint, &Name)>,
+    globals: &Query<&GlobalTransform>,
+) -> Vec<SavedIkConstraint> {
+    let Ok(root_global) = globals.get(root) else {
+        return Vec::new();
+    };
+    let to_local = root_global.affine().inverse();
+    let position = |entity: Entity| {
+        globals
+            .get(entity)
+            .ok()
+            .map(|g| to_local.transform_point3(g.translation()).to_array())
+    };
+    children
+        .iter_descendants(root)
+        .filter_map(|bone| {
+            let (constraint, name) = constraints.get(bone).ok()?;
+            Some(SavedIkConstraint {
+                bone: name.to_string(),
+                chain_length: constraint.chain_length,
+                iterations: constraint.iterations,
+                enabled: constraint.enabled,
+                pole_angle: constraint.pole_angle,
+                target: position(constraint.target)?,
+                pole: constraint.pole_target.and_then(position),
+            })
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn scene_file_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    mut state: ResMut<SceneFileState>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mode: Res<State<EditorMode>>,
+    asset_server: Res<AssetServer>,
+    assets: Query<(Entity, &SpawnedAsset, &Transform)>,
+    scenes: Query<(Entity, &SceneRoot), Without<SpawnedAsset>>,
+    children: Query<&Children>,
+    constraints: Query<(&IkConstraint, &Name)>,
+    globals: Query<&GlobalTransform>,
+) {
+    let state = &mut *state;
+    let mut save = false;
+    // Le richieste da fuori valgono anche con il pannello chiuso o nascosto.
+    let mut load = *mode.get() == EditorMode::Edit && std::mem::take(&mut state.load_requested);
+    let ui = context.ui();
+    ui.window("Scene")
+        .size([340.0, 110.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            ui.input_text("File", &mut state.path).build();
+            // Il Play ripristina la scena allo Stop: si salva solo in Edit.
+            if *mode.get() != EditorMode::Edit {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "Stop play to save or load");
+                return;
+            }
+            save = ui.button("Save");
+            ui.same_line();
+            load |= ui.button("Load");
+            if !state.status.is_empty() {
+                ui.text_wrapped(&state.status);
+            }
+        });
+
+    let path = Path::new(&state.path);
+    if save {
+        let file = SceneFile {
+            assets: assets
+                .iter()
+                .map(|(entity, asset, transform)| SavedAsset {
+                    model_path: asset.model_path.clone(),
+                    transform: transform.into(),
+                    ik: collect_ik(entity, &children, &constraints, &globals),
+                })
+                .collect(),
+            rigs: scenes
+                .iter()
+                .filter_map(|(entity, scene)| {
+                    let ik = collect_ik(entity, &children, &constraints, &globals);
+                    let scene = scene.0.path()?.to_string();
+                    (!ik.is_empty()).then_some(SavedRig { scene, ik })
+                })
+                .collect(),
+            bookmarks: bookmarks.bookmarks.clone(),
+        };
+        state.status = match file.write(path) {
+            Ok(()) => {
+                state.opened = Some(state.path.clone());
+                format!("Saved {} objects to {}", file.assets.len(), state.path)
+            }
+            Err(e) => format!("Save failed: {}", e),
+        };
+    }
+    if load {
+        state.status = match SceneFile::read(path) {
+            Ok(file) => {
+                for (entity, _, _) in assets.iter() {
+                    commands.entity(entity).despawn();
+                }
+                for saved in &file.assets {
+                    let entity = spawn_model(
+                        &mut commands,
+                        &asset_server,
+                        &saved.model_path,
+                        saved.transform.into(),
+                    );
+                    if !saved.ik.is_empty() {
+                        commands.entity(entity).insert(PendingIkRig(saved.ik.clone()));
+                    }
+                }
+                // Gli altri modelli restano: il loro rig torna quello del file, anche se vuoto.
+                for (entity, scene) in scenes.iter() {
+                    let Some(scene) = scene.0.path().map(|path| path.to_string()) else {
+                        continue;
+                    };
+                    let ik = file
+                        .rigs
+                        .iter()
+                        .find(|rig| rig.scene == scene)
+                        .map(|rig| rig.ik.clone())
+                        .unwrap_or_default();
+                    commands.entity(entity).insert(PendingIkRig(ik));
+                }
+                bookmarks.bookmarks = file.bookmarks;
+                state.opened = Some(state.path.clone());
+                format!("Loaded {} objects from {}", file.assets.len(), state.path)
+            }
+            Err(e) => format!("Load failed: {}", e),
+        };
+    }
+}
+
+pub struct SavePlugin;
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneFileState>()
+            .add_systems(Update, scene_file_ui);
+    }
+}
@@ -0,0 +1,489 @@
+use bevy::prelude::*;
+
+/// Controllo imgui di un campo dichiarato con [`shader_params!`].
+pub enum Widget {
+    /// Campo di runtime (dimensioni, palette...) che non compare nella UI.
+    Hidden,
+    Slider(&'static str, f32, f32),
+    /// Indice `u32` in una lista di nomi.
+    Combo(&'static str, &'static [&'static str]),
+    /// Colore RGB di un `Vec3`.
+    Color(&'static str),
+}
+
+impl Widget {
+    pub const fn slider(label: &'static str, min: f32, max: f32) -> Self {
+        Self::Slider(label, min, max)
+    }
+
+    pub const fn combo(label: &'static str, items: &'static [&'static str]) -> Self {
+        Self::Combo(label, items)
+    }
+
+    pub const fn color(label: &'static str) -> Self {
+        Self::Color(label)
+    }
+}
+
+/// Nome WGSL del tipo di un campo.
+pub trait WgslType {
+    fn wgsl() -> String;
+}
+
+macro_rules! wgsl_types {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(impl WgslType for $ty {
+            fn wgsl() -> String {
+                $name.to_string()
+            }
+        })*
+    };
+}
+
+wgsl_types! {
+    f32 => "f32",
+    u32 => "u32",
+    i32 => "i32",
+    Vec2 => "vec2<f32>",
+    Vec3 => "vec3<f32>",
+    Vec4 => "vec4<f32>",
+    UVec2 => "vec2<u32>",
+    UVec4 => "vec4<u32>",
+}
+
+impl<T: WgslType, const N: usize> WgslType for [T; N] {
+    fn wgsl() -> String {
+        format!("array<{}, {}>", T::wgsl(), N)
+    }
+}
+
+/// Come un campo si modifica con il suo [`Widget`]; i tipi senza controllo non fanno niente.
+pub trait ParamEdit {
+    fn edit(&mut self, _ui: &imgui::Ui, _widget: &Widget) -> bool {
+        false
+    }
+}
+
+impl ParamEdit for f32 {
+    fn edit(&mut self, ui: &imgui::Ui, widget: &Widget) -> bool {
+        match *widget {
+            Widget::Slider(label, min, max) => ui.slider(label, min, max, self),
+            _ => false,
+        }
+    }
+}
+
+impl ParamEdit for u32 {
+    fn edit(&mut self, ui: &imgui::Ui, widget: &Widget) -> bool {
+        match *widget {
+            Widget::Slider(label, min, max) => ui.slider(label, min as u32, max as u32, self),
+            Widget::Combo(label, items) => {
+                let mut index = *self as usize;
+                let changed = ui.combo_simple_string(label, &mut index, items);
+                *self = index as u32;
+                changed
+            }
+            _ => false,
+        }
+    }
+}
+
+macro_rules! float_vector_edit {
+    ($($ty:ty),* $(,)?) => {
+        $(impl ParamEdit for $ty {
+            fn edit(&mut self, ui: &imgui::Ui, widget: &Widget) -> bool {
+                match *widget {
+                    Widget::Slider(label, min, max) => {
+                        ui.slider_config(label, min, max).build_array(self.as_mut())
+                    }
+                    _ => false,
+                }
+            }
+        })*
+    };
+}
+
+float_vector_edit!(Vec2, Vec4);
+
+impl ParamEdit for Vec3 {
+    fn edit(&mut self, ui: &imgui::Ui, widget: &Widget) -> bool {
+        match *widget {
+            Widget::Slider(label, min, max) => {
+                ui.slider_config(label, min, max).build_array(self.as_mut())
+            }
+            Widget::Color(label) => {
+                let mut color = self.to_array();
+                let changed = ui.color_edit3(label, &mut color);
+                *self = Vec3::from_array(color);
+                changed
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ParamEdit for UVec2 {}
+impl ParamEdit for UVec4 {}
+impl<T, const N: usize> ParamEdit for [T; N] {}
+
+/// Campi di preset che si sfumano nelle transizioni.
+pub trait Lerp {
+    fn lerp_to(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp_to(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+/// Modo di transizione `lerp` di un campo di preset.
+pub fn lerp<T: Lerp>(from: T, to: T, t: f32) -> T {
+    from.lerp_to(to, t)
+}
+
+/// Modo di transizione `snap`: quello che non si può sfumare (modi, indici) scatta a metà.
+pub fn snap<T>(from: T, to: T, t: f32) -> T {
+    if t < 0.5 {
+        from
+    } else {
+        to
+    }
+}
+
+/// Implementato da [`shader_params!`]: la struct WGSL e la UI escono dalla stessa dichiarazione.
+pub trait ShaderParams {
+    const NAME: &'static str;
+    /// Percorso per `#import <path>::<NAME>` nello shader.
+    const IMPORT_PATH: &'static str;
+
+    fn wgsl_fields() -> Vec<(&'static str, String)>;
+
+    /// Disegna i controlli dei campi non nascosti; `true` se qualcosa è cambiato.
+    fn edit_ui(&mut self, ui: &imgui::Ui) -> bool;
+
+    /// Modulo WGSL con la sola struct. L'allineamento lo calcola WGSL e, dallo stesso
+    /// ordine dei campi, `ShaderType` lato Rust: niente più campi `_pad` a mano.
+    fn wgsl_module() -> String {
+        let fields: String = Self::wgsl_fields()
+            .iter()
+            .map(|(name, ty)| format!("    {}: {},\n", name, ty))
+            .collect();
+        format!(
+            "#define_import_path {}\n\nstruct {} {{\n{}}};\n",
+            Self::IMPORT_PATH,
+            Self::NAME,
+            fields
+        )
+    }
+}
+
+#[derive(Resource, Default)]
+struct ShaderParamModules(Vec<Handle<Shader>>);
+
+/// Registra il modulo WGSL della struct, da importare negli shader che la usano.
+pub fn add_shader_params<T: ShaderParams>(app: &mut App) {
+    let shader = Shader::from_wgsl(
+        T::wgsl_module(),
+        format!("{}.wgsl", T::IMPORT_PATH.replace("::", "/")),
+    );
+    let handle = app.world_mut().resource_mut::<Assets<Shader>>().add(shader);
+    app.world_mut()
+        .get_resource_or_insert_with(ShaderParamModules::default)
+        .0
+        .push(handle);
+}
+
+/// Dichiara una struct di uniform: tipo, default e controllo imgui di ogni campo.
+///
+/// I campi con `; lerp` o `; snap` finiscono anche nella struct `preset`, che si
+/// (de)serializza, si applica e si sfuma da sola; i campi extra del preset sono a carico
+/// di chi lo dichiara.
+///
+/// Tutto il codice generato vive in un modulo `<nome>_params` riesportato con la
+/// visibilità della struct: un campo senza `pub` lo toccano solo i default, la UI e il
+/// preset generati.
+///
+/// ```ignore
+/// shader_params! {
+///     #[derive(Component, ExtractComponent)]
+///     pub struct OutlineSettings in "xirai::outline" {
+///         pub thickness: f32 = 2.0 => slider("Thickness", 0.0, 10.0); lerp,
+///         pub mode: u32 = 0 => combo("Mode", &MODES); snap,
+///         pub texture_size: Vec2 = Vec2::ZERO,
+///     }
+///     preset OutlinePreset {
+///         pub color: [f32; 4],
+///     }
+/// }
+/// ```
+macro_rules! shader_params {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident in $import_path:literal {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:tt = $default:expr
+                    $(=> $widget:ident ( $($arg:expr),* $(,)? ))?
+                    $(; $mode:ident)?
+            ),* $(,)?
+        }
+    ) => {
+        ::paste::paste! {
+            // `ShaderType` genera funzioni di controllo che non chiama nessuno.
+            #[allow(dead_code)]
+            mod [<$name:snake _params>] {
+                use super::*;
+
+                $crate::shader_params::shader_params! {
+                    @uniform
+                    $(#[$meta])*
+                    struct $name in $import_path {
+                        $(
+                            $(#[$field_meta])*
+                            $field_vis $field: $ty = $default
+                                $(=> $widget($($arg),*))?
+                        ),*
+                    }
+                }
+            }
+            $vis use [<$name:snake _params>]::$name;
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident in $import_path:literal {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:tt = $default:expr
+                    $(=> $widget:ident ( $($arg:expr),* $(,)? ))?
+                    $(; $mode:ident)?
+            ),* $(,)?
+        }
+        $(#[$preset_meta:meta])*
+        preset $preset:ident {
+            $(
+                $(#[$extra_meta:meta])*
+                $extra_vis:vis $extra:ident : $extra_ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        ::paste::paste! {
+            #[allow(dead_code)]
+            mod [<$name:snake _params>] {
+                use super::*;
+
+                $crate::shader_params::shader_params! {
+                    @uniform
+                    $(#[$meta])*
+                    struct $name in $import_path {
+                        $(
+                            $(#[$field_meta])*
+                            $field_vis $field: $ty = $default
+                                $(=> $widget($($arg),*))?
+                        ),*
+                    }
+                }
+
+                $(#[$preset_meta])*
+                #[derive(Clone, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
+                #[serde(default)]
+                pub struct $preset {
+                    $($(
+                        #[doc = concat!("Transizione: `", stringify!($mode), "`.")]
+                        $field_vis $field: $ty,
+                    )?)*
+                    $(
+                        $(#[$extra_meta])*
+                        $extra_vis $extra: $extra_ty,
+                    )*
+                }
+
+                impl Default for $preset {
+                    fn default() -> Self {
+                        Self::from_params(&$name::default())
+                    }
+                }
+
+                impl $preset {
+                    /// Campi generati letti dai settings; gli extra restano di default.
+                    pub fn from_params(settings: &$name) -> Self {
+                        Self {
+                            $($($field: $crate::shader_params::shader_params!(@param $mode settings.$field),)?)*
+                            $($extra: Default::default(),)*
+                        }
+                    }
+
+                    /// Scrive i campi generati nei settings.
+                    pub fn apply_params(&self, settings: &mut $name) {
+                        $($(
+                            settings.$field = $crate::shader_params::shader_params!(@param $mode self.$field);
+                        )?)*
+                    }
+
+                    /// Sfuma i campi generati verso `other`; gli extra sono quelli di `self`.
+                    pub fn lerp_params(&self, other: &Self, t: f32) -> Self {
+                        Self {
+                            $($($field: $crate::shader_params::$mode(self.$field, other.$field, t),)?)*
+                            $($extra: self.$extra.clone(),)*
+                        }
+                    }
+                }
+            }
+            $vis use [<$name:snake _params>]::{$name, $preset};
+        }
+    };
+    (
+        @uniform
+        $(#[$meta:meta])*
+        struct $name:ident in $import_path:literal {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:tt = $default:expr
+                    $(=> $widget:ident ( $($arg:expr),* ))?
+            ),*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, ::bevy::render::render_resource::ShaderType)]
+        pub struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl $crate::shader_params::ShaderParams for $name {
+            const NAME: &'static str = stringify!($name);
+            const IMPORT_PATH: &'static str = $import_path;
+
+            fn wgsl_fields() -> Vec<(&'static str, String)> {
+                vec![$((
+                    stringify!($field),
+                    <$ty as $crate::shader_params::WgslType>::wgsl(),
+                ),)*]
+            }
+
+            fn edit_ui(&mut self, ui: &::imgui::Ui) -> bool {
+                let mut changed = false;
+                $(
+                    changed |= $crate::shader_params::ParamEdit::edit(
+                        &mut self.$field,
+                        ui,
+                        &$crate::shader_params::shader_params!(@widget $($widget($($arg),*))?),
+                    );
+                )*
+                changed
+            }
+        }
+    };
+    // Solo i campi con un modo di transizione entrano nel preset.
+    (@param $mode:ident $value:expr) => {
+        $value
+    };
+    (@widget) => {
+        $crate::shader_params::Widget::Hidden
+    };
+    (@widget $widget:ident($($arg:expr),*)) => {
+        $crate::shader_params::Widget::$widget($($arg),*)
+    };
+}
+pub(crate) use shader_params;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [&str; 2] = ["Off", "On"];
+
+    shader_params! {
+        pub struct TestParams in "xirai::test" {
+            pub strength: f32 = 0.5 => slider("Strength", 0.0, 1.0); lerp,
+            pub mode: u32 = 1 => combo("Mode", &MODES); snap,
+            pub size: Vec2 = Vec2::ZERO,
+            pub colors: [Vec4; 2] = [Vec4::ONE; 2],
+        }
+        preset TestPreset {
+            pub name: String,
+        }
+    }
+
+    // Una seconda struct nello stesso modulo, con un campo che resta privato.
+    shader_params! {
+        struct OtherParams in "xirai::test::other" {
+            pub value: f32 = 2.0,
+            hidden: u32 = 3,
+        }
+    }
+
+    #[test]
+    fn wgsl_module_lists_fields_in_order() {
+        assert_eq!(
+            TestParams::wgsl_module(),
+            "#define_import_path xirai::test\n\n\
+             struct TestParams {\n    \
+             strength: f32,\n    \
+             mode: u32,\n    \
+             size: vec2<f32>,\n    \
+             colors: array<vec4<f32>, 2>,\n\
+             };\n"
+        );
+        assert_eq!(OtherParams::NAME, "OtherParams");
+        assert_eq!(OtherParams::IMPORT_PATH, "xirai::test::other");
+        assert_eq!(
+            OtherParams::wgsl_fields(),
+            [("value", "f32".to_string()), ("hidden", "u32".to_string())]
+        );
+        assert_eq!(OtherParams::default().value, 2.0);
+    }
+
+    #[test]
+    fn preset_round_trip() {
+        let settings = TestParams {
+            strength: 0.25,
+            mode: 0,
+            size: Vec2::splat(4.0),
+            colors: [Vec4::ZERO; 2],
+        };
+        let preset = TestPreset::from_params(&settings);
+        assert_eq!((preset.strength, preset.mode), (0.25, 0));
+        assert!(preset.name.is_empty());
+        assert!(TestPreset::default() == TestPreset::from_params(&TestParams::default()));
+
+        let other = TestPreset {
+            strength: 0.75,
+            mode: 1,
+            name: "other".to_string(),
+        };
+        let mut applied = settings;
+        other.apply_params(&mut applied);
+        assert_eq!((applied.strength, applied.mode), (0.75, 1));
+        // I campi fuori dal preset restano quelli di prima.
+        assert_eq!(applied.size, settings.size);
+        assert_eq!(applied.colors, settings.colors);
+        let expected = TestPreset {
+            name: String::new(),
+            ..other.clone()
+        };
+        assert!(TestPreset::from_params(&applied) == expected);
+
+        let early = preset.lerp_params(&other, 0.25);
+        assert_eq!((early.strength, early.mode), (0.375, 0));
+        assert!(early.name.is_empty());
+        assert_eq!(preset.lerp_params(&other, 0.5).mode, 1);
+
+        let text = ron::to_string(&other).unwrap();
+        assert!(ron::from_str::<TestPreset>(&text).unwrap() == other);
+        // I campi mancanti prendono i default della struct.
+        let partial: TestPreset = ron::from_str("(strength: 0.1)").unwrap();
+        assert_eq!((partial.strength, partial.mode), (0.1, 1));
+    }
+}
@@ -0,0 +1,621 @@
+use crate::transform::Selected;
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use bevy::render::mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes};
+use std::collections::{HashMap, HashSet};
+
+/// Ossa umanoidi comuni a tutti i rig. L'ordine di `ALL` va dai genitori ai figli.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HumanoidBone {
+    Hips,
+    Spine,
+    Chest,
+    Neck,
+    Head,
+    EyeLeft,
+    EyeRight,
+    ShoulderLeft,
+    UpperArmLeft,
+    LowerArmLeft,
+    HandLeft,
+    ShoulderRight,
+    UpperArmRight,
+    LowerArmRight,
+    HandRight,
+    UpperLegLeft,
+    LowerLegLeft,
+    FootLeft,
+    ToesLeft,
+    UpperLegRight,
+    LowerLegRight,
+    FootRight,
+    ToesRight,
+}
+
+impl HumanoidBone {
+    pub const ALL: [HumanoidBone; 23] = [
+        HumanoidBone::Hips,
+        HumanoidBone::Spine,
+        HumanoidBone::Chest,
+        HumanoidBone::Neck,
+        HumanoidBone::Head,
+        HumanoidBone::EyeLeft,
+        HumanoidBone::EyeRight,
+        HumanoidBone::ShoulderLeft,
+        HumanoidBone::UpperArmLeft,
+        HumanoidBone::LowerArmLeft,
+        HumanoidBone::HandLeft,
+        HumanoidBone::ShoulderRight,
+        HumanoidBone::UpperArmRight,
+        HumanoidBone::LowerArmRight,
+        HumanoidBone::HandRight,
+        HumanoidBone::UpperLegLeft,
+        HumanoidBone::LowerLegLeft,
+        HumanoidBone::FootLeft,
+        HumanoidBone::ToesLeft,
+        HumanoidBone::UpperLegRight,
+        HumanoidBone::LowerLegRight,
+        HumanoidBone::FootRight,
+        HumanoidBone::ToesRight,
+    ];
+}
+
+/// Convenzione di nomi di un rig: nome del nodo per ogni osso umanoide.
+#[derive(Debug, Clone)]
+pub struct BoneProfile {
+    pub name: String,
+    pub bones: HashMap<HumanoidBone, String>,
+}
+
+impl BoneProfile {
+    fn new(name: &str, bones: &[(HumanoidBone, &str)]) -> Self {
+        Self {
+            name: name.to_string(),
+            bones: bones
+                .iter()
+                .map(|(bone, node)| (*bone, node.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn mixamo() -> Self {
+        use HumanoidBone::*;
+        Self::new(
+            "Mixamo",
+            &[
+                (Hips, "Hips"),
+                (Spine, "Spine"),
+                (Chest, "Spine2"),
+                (Neck, "Neck"),
+                (Head, "Head"),
+                (EyeLeft, "LeftEye"),
+                (EyeRight, "RightEye"),
+                (ShoulderLeft, "LeftShoulder"),
+                (UpperArmLeft, "LeftArm"),
+                (LowerArmLeft, "LeftForeArm"),
+                (HandLeft, "LeftHand"),
+                (ShoulderRight, "RightShoulder"),
+                (UpperArmRight, "RightArm"),
+                (LowerArmRight, "RightForeArm"),
+                (HandRight, "RightHand"),
+                (UpperLegLeft, "LeftUpLeg"),
+                (LowerLegLeft, "LeftLeg"),
+                (FootLeft, "LeftFoot"),
+                (ToesLeft, "LeftToeBase"),
+                (UpperLegRight, "RightUpLeg"),
+                (LowerLegRight, "RightLeg"),
+                (FootRight, "RightFoot"),
+                (ToesRight, "RightToeBase"),
+            ],
+        )
+    }
+
+    /// Rig semplice esportato da Blender (`man.glb`, il samurai).
+    pub fn blender() -> Self {
+        use HumanoidBone::*;
+        Self::new(
+            "Blender",
+            &[
+                (Hips, "pelvis"),
+                (Spine, "spine"),
+                (Chest, "chest"),
+                (Neck, "neck"),
+                (Head, "head"),
+                (EyeLeft, "eye_left"),
+                (EyeRight, "eye_right"),
+                (ShoulderLeft, "shoulder_left"),
+                (UpperArmLeft, "upper_arm_left"),
+                (LowerArmLeft, "forearm_left"),
+                (HandLeft, "hand_left"),
+                (ShoulderRight, "shoulder_right"),
+                (UpperArmRight, "upper_arm_right"),
+                (LowerArmRight, "forearm_right"),
+                (HandRight, "hand_right"),
+                (UpperLegLeft, "thigh_left"),
+                (LowerLegLeft, "shin_left"),
+                (FootLeft, "foot_left"),
+                (ToesLeft, "toe_left"),
+                (UpperLegRight, "thigh_right"),
+                (LowerLegRight, "shin_right"),
+                (FootRight, "foot_right"),
+                (ToesRight, "toe_right"),
+            ],
+        )
+    }
+
+    /// Rig di Rigify (nomi con suffisso .L/.R).
+    pub fn rigify() -> Self {
+        use HumanoidBone::*;
+        Self::new(
+            "Rigify",
+            &[
+                (Hips, "spine"),
+                (Spine, "spine.001"),
+                (Chest, "spine.003"),
+                (Neck, "spine.004"),
+                (Head, "spine.006"),
+                (EyeLeft, "eye.L"),
+                (EyeRight, "eye.R"),
+                (ShoulderLeft, "shoulder.L"),
+                (UpperArmLeft, "upper_arm.L"),
+                (LowerArmLeft, "forearm.L"),
+                (HandLeft, "hand.L"),
+                (ShoulderRight, "shoulder.R"),
+                (UpperArmRight, "upper_arm.R"),
+                (LowerArmRight, "forearm.R"),
+                (HandRight, "hand.R"),
+                (UpperLegLeft, "thigh.L"),
+                (LowerLegLeft, "shin.L"),
+                (FootLeft, "foot.L"),
+                (ToesLeft, "toe.L"),
+                (UpperLegRight, "thigh.R"),
+                (LowerLegRight, "shin.R"),
+                (FootRight, "foot.R"),
+                (ToesRight, "toe.R"),
+            ],
+        )
+    }
+
+    /// Skeleton di Unreal (Mannequin).
+    pub fn unreal() -> Self {
+        use HumanoidBone::*;
+        Self::new(
+            "Unreal",
+            &[
+                (Hips, "pelvis"),
+                (Spine, "spine_01"),
+                (Chest, "spine_03"),
+                (Neck, "neck_01"),
+                (Head, "head"),
+                (EyeLeft, "FACIAL_L_Eye"),
+                (EyeRight, "FACIAL_R_Eye"),
+                (ShoulderLeft, "clavicle_l"),
+                (UpperArmLeft, "upperarm_l"),
+                (LowerArmLeft, "lowerarm_l"),
+                (HandLeft, "hand_l"),
+                (ShoulderRight, "clavicle_r"),
+                (UpperArmRight, "upperarm_r"),
+                (LowerArmRight, "lowerarm_r"),
+                (HandRight, "hand_r"),
+                (UpperLegLeft, "thigh_l"),
+                (LowerLegLeft, "calf_l"),
+                (FootLeft, "foot_l"),
+                (ToesLeft, "ball_l"),
+                (UpperLegRight, "thigh_r"),
+                (LowerLegRight, "calf_r"),
+                (FootRight, "foot_r"),
+                (ToesRight, "ball_r"),
+            ],
+        )
+    }
+}
+
+/// Profili conosciuti, provati in ordine durante il rilevamento automatico.
+#[derive(Resource)]
+pub struct SkeletonProfiles(pub Vec<BoneProfile>);
+
+impl Default for SkeletonProfiles {
+    fn default() -> Self {
+        Self(vec![
+            BoneProfile::mixamo(),
+            BoneProfile::blender(),
+            BoneProfile::rigify(),
+            BoneProfile::unreal(),
+        ])
+    }
+}
+
+/// Nome del nodo senza namespace ("mixamorig:Hips" -> "hips").
+fn normalize(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_lowercase()
+}
+
+/// Mappa ossa umanoidi → entità dello scheletro di un personaggio.
+/// Il profilo è rilevato automaticamente, `profile` e `overrides` lo forzano dall'UI.
+#[derive(Component, Default)]
+pub struct SkeletonMap {
+    /// Profilo forzato; `None` = rilevamento automatico.
+    pub profile: Option<String>,
+    /// Nomi dei nodi che sostituiscono quelli del profilo, osso per osso.
+    pub overrides: HashMap<HumanoidBone, String>,
+    detected: Option<String>,
+    resolved: bool,
+    bones: HashMap<HumanoidBone, Entity>,
+    /// Posa di riposo in spazio modello (relativo alla radice), dalle bind pose delle skin.
+    rest: HashMap<HumanoidBone, Transform>,
+}
+
+impl SkeletonMap {
+    pub fn bone(&self, bone: HumanoidBone) -> Option<Entity> {
+        self.bones.get(&bone).copied()
+    }
+
+    /// Profilo in uso (forzato o rilevato).
+    pub fn profile_name(&self) -> Option<&str> {
+        self.profile.as_deref().or(self.detected.as_deref())
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.resolved
+    }
+
+    /// Rifà il rilevamento al prossimo frame (dopo aver cambiato profilo o override).
+    pub fn redetect(&mut self) {
+        self.resolved = false;
+    }
+}
+
+/// Tutti i joint delle skin sotto `root`: le ossa vere, senza mesh e nodi di servizio.
+pub fn skin_joints(
+    root: Entity,
+    children: &Query<&Children>,
+    skins: &Query<&SkinnedMesh>,
+) -> HashSet<Entity> {
+    children
+        .iter_descendants(root)
+        .filter_map(|e| skins.get(e).ok())
+        .flat_map(|skin| skin.joints.iter().copied())
+        .collect()
+}
+
+/// Trasformazione di `entity` relativa a `root`, componendo i `Transform` locali.
+/// Non usa `GlobalTransform`, così vale anche prima della propagazione.
+fn model_transform(
+    entity: Entity,
+    root: Entity,
+    parents: &Query<&ChildOf>,
+    transforms: &Query<&mut Transform>,
+) -> Transform {
+    let mut result = Transform::IDENTITY;
+    let mut current = entity;
+    while current != root {
+        let Ok(transform) = transforms.get(current) else {
+            break;
+        };
+        result = transform.mul_transform(result);
+        let Ok(child_of) = parents.get(current) else {
+            break;
+        };
+        current = child_of.parent();
+    }
+    result
+}
+
+/// Bind pose dei joint in spazio modello: inversa delle inverse bind pose, portata dallo
+/// spazio della mesh a quello della radice. Non dipende da quanto l'animazione ha già girato.
+/// `None` finché gli asset delle bind pose non sono caricati.
+fn bind_poses(
+    root: Entity,
+    children: &Query<&Children>,
+    skins: &Query<&SkinnedMesh>,
+    bindposes: &Assets<SkinnedMeshInverseBindposes>,
+    parents: &Query<&ChildOf>,
+    transforms: &Query<&mut Transform>,
+) -> Option<HashMap<Entity, Transform>> {
+    let mut poses = HashMap::new();
+    for mesh in children.iter_descendants(root) {
+        let Ok(skin) = skins.get(mesh) else {
+            continue;
+        };
+        let inverse = bindposes.get(&skin.inverse_bindposes)?;
+        let mesh_matrix = model_transform(mesh, root, parents, transforms).compute_matrix();
+        for (&joint, inverse) in skin.joints.iter().zip(inverse.iter()) {
+            poses
+                .entry(joint)
+                .or_insert_with(|| Transform::from_matrix(mesh_matrix * inverse.inverse()));
+        }
+    }
+    Some(poses)
+}
+
+/// Abbina i nodi della scena ai profili: vince quello con più ossa trovate.
+#[allow(clippy::too_many_arguments)]
+fn detect_skeletons(
+    profiles: Res<SkeletonProfiles>,
+    mut skeletons: Query<(Entity, &mut SkeletonMap)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    parents: Query<&ChildOf>,
+    transforms: Query<&mut Transform>,
+    skins: Query<&SkinnedMesh>,
+    bindposes: Res<Assets<SkinnedMeshInverseBindposes>>,
+) {
+    for (root, mut skeleton) in skeletons.iter_mut() {
+        if skeleton.resolved {
+            continue;
+        }
+        let nodes: HashMap<String, Entity> = children
+            .iter_descendants(root)
+            .filter_map(|e| Some((normalize(names.get(e).ok()?), e)))
+            .collect();
+        // La scena non è ancora stata spawnata.
+        if nodes.is_empty() {
+            continue;
+        }
+        let Some(bind) = bind_poses(root, &children, &skins, &bindposes, &parents, &transforms)
+        else {
+            continue;
+        };
+
+        let matches = |profile: &BoneProfile| -> HashMap<HumanoidBone, Entity> {
+            HumanoidBone::ALL
+                .iter()
+                .filter_map(|bone| {
+                    let name = skeleton.overrides.get(bone).or(profile.bones.get(bone))?;
+                    Some((*bone, *nodes.get(&normalize(name))?))
+                })
+                .collect()
+        };
+
+        let forced = skeleton
+            .profile
+            .as_ref()
+            .and_then(|name| profiles.0.iter().find(|p| &p.name == name));
+        let best = match forced {
+            Some(profile) => Some((profile, matches(profile))),
+            None => profiles
+                .0
+                .iter()
+                .map(|profile| (profile, matches(profile)))
+                .filter(|(_, bones)| bones.contains_key(&HumanoidBone::Hips))
+                // A parità di ossa vince il profilo che viene prima.
+                .rev()
+                .max_by_key(|(_, bones)| bones.len()),
+        };
+
+        let skeleton = &mut *skeleton;
+        skeleton.resolved = true;
+        skeleton.bones.clear();
+        skeleton.rest.clear();
+        skeleton.detected = None;
+        let Some((profile, bones)) = best else {
+            continue;
+        };
+        info!(
+            "Scheletro {:?}: profilo {} ({}/{} ossa)",
+            root,
+            profile.name,
+            bones.len(),
+            HumanoidBone::ALL.len()
+        );
+        // Ossa fuori dalle skin (nodi di controllo): vale la posa attuale.
+        for (&bone, &entity) in &bones {
+            let rest = bind
+                .get(&entity)
+                .copied()
+                .unwrap_or_else(|| model_transform(entity, root, &parents, &transforms));
+            skeleton.rest.insert(bone, rest);
+        }
+        skeleton.detected = Some(profile.name.clone());
+        skeleton.bones = bones;
+    }
+}
+
+/// Copia le animazioni dello scheletro `source` su questo, anche se i rig sono diversi.
+#[derive(Component)]
+pub struct RetargetSource {
+    pub source: Entity,
+}
+
+/// Dopo l'animazione: per ogni osso si prende la rotazione della sorgente rispetto alla sua
+/// posa di riposo (in spazio modello) e la si applica alla posa di riposo del bersaglio.
+/// L'anca segue anche la traslazione, scalata sull'altezza dei due rig.
+fn retarget_animations(
+    targets: Query<(Entity, &SkeletonMap, &RetargetSource)>,
+    sources: Query<&SkeletonMap>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (root, target, retarget) in targets.iter() {
+        let Ok(source) = sources.get(retarget.source) else {
+            continue;
+        };
+        if retarget.source == root || !target.resolved || !source.resolved {
+            continue;
+        }
+
+        // Prima si leggono tutte le pose della sorgente, poi si scrive il bersaglio.
+        let posed: Vec<(HumanoidBone, Transform)> = HumanoidBone::ALL
+            .iter()
+            .filter_map(|&bone| {
+                let entity = source.bones.get(&bone)?;
+                Some((
+                    bone,
+                    model_transform(*entity, retarget.source, &parents, &transforms),
+                ))
+            })
+            .collect();
+
+        let height_ratio = match (
+            source.rest.get(&HumanoidBone::Hips),
+            target.rest.get(&HumanoidBone::Hips),
+        ) {
+            (Some(s), Some(t)) if s.translation.y.abs() > f32::EPSILON => {
+                t.translation.y / s.translation.y
+            }
+            _ => 1.0,
+        };
+
+        // Ordine genitori → figli: la posa del genitore del bersaglio è già aggiornata.
+        for (bone, source_pose) in posed {
+            let (Some(&entity), Some(target_rest), Some(source_rest)) = (
+                target.bones.get(&bone),
+                target.rest.get(&bone),
+                source.rest.get(&bone),
+            ) else {
+                continue;
+            };
+            let delta = source_pose.rotation * source_rest.rotation.inverse();
+            let mut model = *target_rest;
+            model.rotation = delta * target_rest.rotation;
+            if bone == HumanoidBone::Hips {
+                model.translation += (source_pose.translation - source_rest.translation) * height_ratio;
+            }
+
+            let parent = parents
+                .get(entity)
+                .map(|p| model_transform(p.parent(), root, &parents, &transforms))
+                .unwrap_or_default();
+            let local = Transform::from_matrix(
+                parent.compute_matrix().inverse() * model.compute_matrix(),
+            );
+            if let Ok(mut transform) = transforms.get_mut(entity) {
+                transform.rotation = local.rotation;
+                if bone == HumanoidBone::Hips {
+                    transform.translation = local.translation;
+                }
+            }
+        }
+    }
+}
+
+fn skeleton_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut commands: Commands,
+    profiles: Res<SkeletonProfiles>,
+    selected: Query<Entity, With<Selected>>,
+    parents: Query<&ChildOf>,
+    mut skeletons: Query<(Entity, &mut SkeletonMap, Option<&RetargetSource>)>,
+    names: Query<&Name>,
+) {
+    // La selezione può essere una mesh dentro la scena: si risale alla radice.
+    let Some(mut root) = selected.iter().next() else {
+        return;
+    };
+    while let Ok(child_of) = parents.get(root) {
+        root = child_of.parent();
+    }
+    let others: Vec<Entity> = skeletons
+        .iter()
+        .filter(|(e, skeleton, _)| *e != root && skeleton.resolved && !skeleton.bones.is_empty())
+        .map(|(e, _, _)| e)
+        .collect();
+
+    let ui = context.ui();
+    ui.window("Skeleton")
+        .size([320.0, 400.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            let Ok((_, mut skeleton, retarget)) = skeletons.get_mut(root) else {
+                ui.text(format!("Entity: {:?}", root));
+                if ui.button("Add skeleton map") {
+                    commands.entity(root).insert(SkeletonMap::default());
+                }
+                return;
+            };
+            ui.text(format!("Entity: {:?}", root));
+            match skeleton.profile_name() {
+                Some(name) => ui.text(format!("Profile: {}", name)),
+                None if skeleton.resolved => {
+                    ui.text_colored([1.0, 0.5, 0.3, 1.0], "No matching profile")
+                }
+                None => ui.text_colored([0.7, 0.7, 0.7, 1.0], "Waiting for scene..."),
+            }
+
+            // Override del profilo: "Auto" torna al rilevamento.
+            let current = skeleton.profile.clone().unwrap_or_else(|| "Auto".to_string());
+            if let Some(_combo) = ui.begin_combo("Override", &current) {
+                let mut choice = None;
+                if ui.selectable("Auto") {
+                    choice = Some(None);
+                }
+                for profile in &profiles.0 {
+                    if ui.selectable(&profile.name) {
+                        choice = Some(Some(profile.name.clone()));
+                    }
+                }
+                if let Some(choice) = choice {
+                    skeleton.profile = choice;
+                    skeleton.redetect();
+                }
+            }
+
+            if let Some(_node) = ui.tree_node("Bones") {
+                let profile = skeleton
+                    .profile_name()
+                    .and_then(|name| profiles.0.iter().find(|p| p.name == name))
+                    .cloned();
+                let mut changed = false;
+                for bone in HumanoidBone::ALL {
+                    let found = skeleton.bones.get(&bone).and_then(|e| names.get(*e).ok());
+                    let color = if found.is_some() {
+                        [0.3, 1.0, 0.3, 1.0]
+                    } else {
+                        [1.0, 0.4, 0.4, 1.0]
+                    };
+                    ui.text_colored(color, format!("{:?}", bone));
+                    ui.same_line_with_pos(130.0);
+                    let mut name = skeleton
+                        .overrides
+                        .get(&bone)
+                        .cloned()
+                        .or_else(|| profile.as_ref().and_then(|p| p.bones.get(&bone).cloned()))
+                        .unwrap_or_default();
+                    let _width = ui.push_item_width(-1.0);
+                    if ui
+                        .input_text(format!("##{:?}", bone), &mut name)
+                        .enter_returns_true(true)
+                        .build()
+                    {
+                        skeleton.overrides.insert(bone, name);
+                        changed = true;
+                    }
+                }
+                if ui.button("Clear overrides") {
+                    skeleton.overrides.clear();
+                    changed = true;
+                }
+                if changed {
+                    skeleton.redetect();
+                }
+            }
+
+            ui.separator();
+            ui.text("Retarget animation from:");
+            let source = retarget.map(|r| r.source);
+            if ui.radio_button_bool("None", source.is_none()) {
+                commands.entity(root).remove::<RetargetSource>();
+            }
+            for other in others {
+                let label = names
+                    .get(other)
+                    .map_or_else(|_| format!("{:?}", other), |n| format!("{} ({:?})", n, other));
+                if ui.radio_button_bool(label, source == Some(other)) {
+                    commands.entity(root).insert(RetargetSource { source: other });
+                }
+            }
+        });
+}
+
+pub struct SkeletonPlugin;
+impl Plugin for SkeletonPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SkeletonProfiles>()
+            .add_systems(Update, (detect_skeletons, skeleton_ui))
+            .add_systems(
+                PostUpdate,
+                retarget_animations
+                    .after(bevy::app::Animation)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
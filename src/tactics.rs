@@ -0,0 +1,756 @@
+use crate::board::{Board, Size, Tile};
+use crate::cursor::Cursor;
+use crate::input::UiFocus;
+use bevy::picking::prelude::*;
+use bevy::prelude::*;
+use bevy_mod_imgui::prelude::*;
+use bevy_mod_outline::OutlineVolume;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+/// Board usata dalla modalità tattica, con le celle non calpestabili.
+#[derive(Component, Default)]
+pub struct TacticsMap {
+    pub blocked: HashSet<UVec2>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Team {
+    Player,
+    Enemy,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Unit {
+    pub name: String,
+    pub team: Team,
+    /// Tiles the unit can walk in one turn.
+    pub move_range: u32,
+    /// Manhattan distance the unit can hit from.
+    pub attack_range: u32,
+    pub attack: i32,
+    pub hp: i32,
+    pub max_hp: i32,
+}
+
+/// Cella occupata da un'unità sulla sua board (l'unità è figlia della board).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridPosition {
+    pub cell: UVec2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TurnPhase {
+    #[default]
+    Move,
+    Attack,
+}
+
+/// Ordine dei turni: l'unità attiva è in testa alla coda.
+#[derive(Resource, Default)]
+pub struct TurnQueue {
+    pub order: VecDeque<Entity>,
+    pub phase: TurnPhase,
+    pub round: u32,
+    /// Set when only one team is left on the map: the queue stops there.
+    pub winner: Option<Team>,
+    /// Units that already acted this round, to know when a new round starts.
+    acted: usize,
+}
+
+impl TurnQueue {
+    pub fn active(&self) -> Option<Entity> {
+        if self.winner.is_some() {
+            return None;
+        }
+        self.order.front().copied()
+    }
+
+    pub fn end_turn(&mut self) {
+        if let Some(unit) = self.order.pop_front() {
+            self.order.push_back(unit);
+        }
+        self.phase = TurnPhase::Move;
+        self.acted += 1;
+        if self.acted >= self.order.len() {
+            self.acted = 0;
+            self.round += 1;
+        }
+    }
+
+    pub fn remove(&mut self, unit: Entity) {
+        if let Some(index) = self.order.iter().position(|&e| e == unit) {
+            self.order.remove(index);
+            if index < self.acted {
+                self.acted -= 1;
+            }
+        }
+    }
+}
+
+/// Pausa prima di ogni azione nemica (movimento, poi attacco), per vederle.
+const ENEMY_STEP_SECONDS: f32 = 0.6;
+
+/// Cella della mappa tattica sotto il cursore.
+#[derive(Resource, Default)]
+pub struct HoveredCell(pub Option<UVec2>);
+
+fn manhattan(a: UVec2, b: UVec2) -> u32 {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
+/// A* su 4 direzioni con euristica di Manhattan. Il percorso include `start` e `goal`.
+pub fn find_path(
+    board: &Board,
+    blocked: &HashSet<UVec2>,
+    start: UVec2,
+    goal: UVec2,
+) -> Option<Vec<UVec2>> {
+    if blocked.contains(&goal) || !board.contains(goal.as_ivec2()) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<UVec2, UVec2> = HashMap::new();
+    let mut cost: HashMap<UVec2, u32> = HashMap::new();
+    cost.insert(start, 0);
+    open.push(Reverse((manhattan(start, goal), start.x, start.y)));
+
+    while let Some(Reverse((_, x, y))) = open.pop() {
+        let current = UVec2::new(x, y);
+        if current == goal {
+            let mut path = vec![current];
+            let mut cell = current;
+            while let Some(&previous) = came_from.get(&cell) {
+                path.push(previous);
+                cell = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let current_cost = cost[&current];
+        for next in board.neighbours(current) {
+            if blocked.contains(&next) {
+                continue;
+            }
+            let next_cost = current_cost + 1;
+            if cost.get(&next).is_none_or(|&c| next_cost < c) {
+                cost.insert(next, next_cost);
+                came_from.insert(next, current);
+                open.push(Reverse((next_cost + manhattan(next, goal), next.x, next.y)));
+            }
+        }
+    }
+    None
+}
+
+/// Celle raggiungibili entro `range` passi, con il costo per arrivarci.
+pub fn reachable_cells(
+    board: &Board,
+    blocked: &HashSet<UVec2>,
+    start: UVec2,
+    range: u32,
+) -> HashMap<UVec2, u32> {
+    let mut reached = HashMap::from([(start, 0)]);
+    let mut frontier = VecDeque::from([start]);
+    while let Some(cell) = frontier.pop_front() {
+        let steps = reached[&cell];
+        if steps == range {
+            continue;
+        }
+        for next in board.neighbours(cell) {
+            if blocked.contains(&next) || reached.contains_key(&next) {
+                continue;
+            }
+            reached.insert(next, steps + 1);
+            frontier.push_back(next);
+        }
+    }
+    reached
+}
+
+/// Celle bloccate per il movimento: ostacoli più le altre unità.
+fn movement_blockers<'a>(
+    map: &TacticsMap,
+    units: impl Iterator<Item = (Entity, &'a GridPosition)>,
+    mover: Entity,
+) -> HashSet<UVec2> {
+    let mut blocked = map.blocked.clone();
+    blocked.extend(units.filter(|(e, _)| *e != mover).map(|(_, p)| p.cell));
+    blocked
+}
+
+fn setup_tactics_map(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut queue: ResMut<TurnQueue>,
+) {
+    let tile_material = StandardMaterial {
+        base_color: Color::srgb(0.35, 0.55, 0.3),
+        unlit: true, // Flat pixel art look
+        perceptual_roughness: 1.0,
+        reflectance: 0.0,
+        ..default()
+    };
+    let tiles = vec![
+        materials.add(tile_material.clone()),
+        materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.48, 0.26),
+            ..tile_material
+        }),
+    ];
+
+    // Ostacoli: cella d'origine e ingombro.
+    let rocks = [
+        (UVec2::new(3, 3), Size { w: 1, h: 3 }),
+        (UVec2::new(6, 6), Size::default()),
+        (UVec2::new(6, 2), Size { w: 2, h: 1 }),
+    ];
+    let blocked: HashSet<UVec2> = rocks.iter().flat_map(|(cell, size)| size.cells(*cell)).collect();
+    let board = Board::new(10, 10).with_tile_size(2.0).with_materials(tiles);
+
+    let rock_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.42, 0.4),
+        unlit: true,
+        ..default()
+    });
+    let unit_mesh = meshes.add(Capsule3d::new(0.4, 0.8));
+    let player_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.2, 0.4, 0.9),
+        unlit: true,
+        ..default()
+    });
+    let enemy_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.2, 0.2),
+        unlit: true,
+        ..default()
+    });
+
+    // Un blocco per ostacolo, centrato sulle celle che copre.
+    let rocks: Vec<(Vec3, Size, Handle<Mesh>)> = rocks
+        .into_iter()
+        .map(|(cell, size)| {
+            let far = cell + UVec2::new(size.w - 1, size.h - 1);
+            let center = (board.cell_to_local(cell) + board.cell_to_local(far)) / 2.0;
+            let mesh = meshes.add(Cuboid::new(
+                size.w as f32 * board.tile_size - 0.4,
+                1.2,
+                size.h as f32 * board.tile_size - 0.4,
+            ));
+            (center + Vec3::Y * 0.6, size, mesh)
+        })
+        .collect();
+    let units = [
+        ("Knight", Team::Player, UVec2::new(1, 1), 4, 1, 5, 14),
+        ("Archer", Team::Player, UVec2::new(2, 0), 3, 3, 3, 9),
+        ("Brute", Team::Enemy, UVec2::new(8, 8), 3, 1, 6, 16),
+        ("Slinger", Team::Enemy, UVec2::new(7, 9), 3, 2, 3, 8),
+    ];
+    let unit_positions: Vec<Vec3> = units
+        .iter()
+        .map(|u| board.cell_to_local(u.2) + Vec3::Y * 0.8)
+        .collect();
+
+    let map = commands
+        .spawn((
+            Name::new("Tactics Map"),
+            board,
+            TacticsMap { blocked },
+            Transform::from_xyz(24.0, 0.1, 0.0),
+        ))
+        .id();
+
+    for (translation, size, mesh) in rocks {
+        commands.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(rock_material.clone()),
+            Transform::from_translation(translation),
+            size,
+            ChildOf(map),
+        ));
+    }
+
+    for ((name, team, cell, move_range, attack_range, attack, hp), translation) in
+        units.into_iter().zip(unit_positions)
+    {
+        let material = match team {
+            Team::Player => player_material.clone(),
+            Team::Enemy => enemy_material.clone(),
+        };
+        let unit = commands
+            .spawn((
+                Name::new(name),
+                Mesh3d(unit_mesh.clone()),
+                MeshMaterial3d(material),
+                Transform::from_translation(translation),
+                Unit {
+                    name: name.to_string(),
+                    team,
+                    move_range,
+                    attack_range,
+                    attack,
+                    hp,
+                    max_hp: hp,
+                },
+                GridPosition { cell },
+                ChildOf(map),
+            ))
+            .id();
+        queue.order.push_back(unit);
+    }
+}
+
+fn update_hovered_cell(
+    cursor: Res<Cursor>,
+    map: Option<Single<(&Board, &GlobalTransform), With<TacticsMap>>>,
+    mut hovered: ResMut<HoveredCell>,
+) {
+    let cell = map.and_then(|map| {
+        let (board, transform) = *map;
+        board.world_to_cell(transform, cursor.cursor_position)
+    });
+    if hovered.0 != cell {
+        hovered.0 = cell;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn sync_unit_transforms(
+    mut units: Query<(&GridPosition, &mut Transform), (With<Unit>, Changed<GridPosition>)>,
+    map: Option<Single<&Board, With<TacticsMap>>>,
+) {
+    let Some(board) = map else {
+        return;
+    };
+    for (position, mut transform) in units.iter_mut() {
+        transform.translation = board.cell_to_local(position.cell) + Vec3::Y * 0.8;
+    }
+}
+
+/// Colora l'outline dei tile: percorso, area di movimento, area d'attacco e hover.
+fn highlight_tiles(
+    mut commands: Commands,
+    map: Option<Single<(Entity, &Board, &TacticsMap)>>,
+    mut tiles: Query<(Entity, &Tile, &ChildOf, Option<&mut OutlineVolume>)>,
+    units: Query<(Entity, &Unit, &GridPosition)>,
+    queue: Res<TurnQueue>,
+    hovered: Res<HoveredCell>,
+) {
+    let Some(map) = map else {
+        return;
+    };
+    let (map_entity, board, tactics) = *map;
+
+    let mut reachable = HashMap::new();
+    let mut attackable = HashSet::new();
+    let mut path = Vec::new();
+    if let Some((active, unit, position)) = queue.active().and_then(|e| units.get(e).ok()) {
+        match queue.phase {
+            TurnPhase::Move => {
+                let blocked = movement_blockers(
+                    tactics,
+                    units.iter().map(|(e, _, p)| (e, p)),
+                    active,
+                );
+                reachable = reachable_cells(board, &blocked, position.cell, unit.move_range);
+                if let Some(goal) = hovered.0.filter(|c| reachable.contains_key(c)) {
+                    path = find_path(board, &blocked, position.cell, goal).unwrap_or_default();
+                }
+            }
+            TurnPhase::Attack => {
+                for y in 0..board.height {
+                    for x in 0..board.width {
+                        let cell = UVec2::new(x, y);
+                        let distance = manhattan(cell, position.cell);
+                        if distance > 0 && distance <= unit.attack_range {
+                            attackable.insert(cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for (entity, tile, child_of, outline) in tiles.iter_mut() {
+        if child_of.parent() != map_entity {
+            continue;
+        }
+        let colour = if path.contains(&tile.cell) {
+            Some(Color::srgb(1.0, 0.9, 0.2))
+        } else if hovered.0 == Some(tile.cell) {
+            Some(Color::WHITE)
+        } else if attackable.contains(&tile.cell) {
+            Some(Color::srgb(0.9, 0.2, 0.2))
+        } else if reachable.contains_key(&tile.cell) {
+            Some(Color::srgb(0.2, 0.5, 1.0))
+        } else {
+            None
+        };
+        let volume = OutlineVolume {
+            visible: colour.is_some(),
+            width: 3.0,
+            colour: colour.unwrap_or(Color::BLACK),
+        };
+        match outline {
+            Some(mut outline) => {
+                if outline.visible != volume.visible || outline.colour != volume.colour {
+                    *outline = volume;
+                }
+            }
+            None => {
+                commands.entity(entity).insert(volume);
+            }
+        }
+    }
+}
+
+/// Applica il danno e rimuove il bersaglio se muore.
+fn strike(commands: &mut Commands, queue: &mut TurnQueue, target: Entity, unit: &mut Unit, damage: i32) {
+    unit.hp -= damage;
+    info!("{} subisce {} danni ({} hp)", unit.name, damage, unit.hp.max(0));
+    if unit.hp <= 0 {
+        info!("{} è stato sconfitto", unit.name);
+        queue.remove(target);
+        commands.entity(target).despawn();
+    }
+}
+
+fn handle_tactics_clicks(
+    mut commands: Commands,
+    mut click_events: EventReader<Pointer<Click>>,
+    focus: Res<UiFocus>,
+    map: Option<Single<(Entity, &Board, &TacticsMap)>>,
+    tiles: Query<(&Tile, &ChildOf)>,
+    mut units: Query<(Entity, &mut Unit, &mut GridPosition)>,
+    mut queue: ResMut<TurnQueue>,
+) {
+    // Un click su un pannello imgui non muove le unità sotto.
+    if focus.mouse {
+        click_events.clear();
+        return;
+    }
+    let Some(map) = map else {
+        return;
+    };
+    let (map_entity, board, tactics) = *map;
+
+    for click in click_events.read() {
+        if click.button != PointerButton::Primary {
+            continue;
+        }
+        let cell = if let Ok((tile, child_of)) = tiles.get(click.target) {
+            if child_of.parent() != map_entity {
+                continue;
+            }
+            tile.cell
+        } else if let Ok((_, _, position)) = units.get(click.target) {
+            position.cell
+        } else {
+            continue;
+        };
+
+        let Some(active) = queue.active() else {
+            continue;
+        };
+        let Ok((_, unit, position)) = units.get(active) else {
+            continue;
+        };
+        if unit.team != Team::Player {
+            continue;
+        }
+        let (from, move_range, attack_range, attack) =
+            (position.cell, unit.move_range, unit.attack_range, unit.attack);
+
+        match queue.phase {
+            TurnPhase::Move => {
+                let blocked = movement_blockers(
+                    tactics,
+                    units.iter().map(|(e, _, p)| (e, p)),
+                    active,
+                );
+                let reachable = reachable_cells(board, &blocked, from, move_range);
+                if reachable.contains_key(&cell) {
+                    if let Ok((_, _, mut position)) = units.get_mut(active) {
+                        position.cell = cell;
+                    }
+                    queue.phase = TurnPhase::Attack;
+                }
+            }
+            TurnPhase::Attack => {
+                // Click sulla propria unità: attende senza attaccare.
+                if cell == from {
+                    queue.end_turn();
+                    continue;
+                }
+                let target = units
+                    .iter()
+                    .find(|(_, u, p)| p.cell == cell && u.team == Team::Enemy)
+                    .map(|(e, _, _)| e);
+                if let Some(target) = target {
+                    if manhattan(cell, from) <= attack_range {
+                        if let Ok((_, mut target_unit, _)) = units.get_mut(target) {
+                            strike(&mut commands, &mut queue, target, &mut target_unit, attack);
+                        }
+                        queue.end_turn();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Ferma la coda quando sulla mappa resta una sola squadra.
+fn check_victory(units: Query<&Unit>, mut queue: ResMut<TurnQueue>) {
+    if queue.winner.is_some() || queue.order.is_empty() {
+        return;
+    }
+    let teams_left: HashSet<Team> = units.iter().map(|u| u.team).collect();
+    if teams_left.len() == 1 {
+        queue.winner = teams_left.into_iter().next();
+        info!("{:?} vince al round {}", queue.winner, queue.round + 1);
+    }
+}
+
+/// Turno nemico: si avvicina all'unità del giocatore più vicina e attacca se può.
+/// Movimento e attacco sono separati da una pausa, così si vedono.
+fn enemy_turn(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut wait: Local<f32>,
+    map: Option<Single<(&Board, &TacticsMap)>>,
+    mut units: Query<(Entity, &mut Unit, &mut GridPosition)>,
+    mut queue: ResMut<TurnQueue>,
+) {
+    let Some(map) = map else {
+        return;
+    };
+    let (board, tactics) = *map;
+    let Some(active) = queue.active() else {
+        return;
+    };
+    let Ok((_, unit, position)) = units.get(active) else {
+        // Unità sparita (despawnata da fuori): la si toglie dalla coda.
+        queue.remove(active);
+        return;
+    };
+    if unit.team != Team::Enemy {
+        *wait = 0.0;
+        return;
+    }
+    *wait += time.delta_secs();
+    if *wait < ENEMY_STEP_SECONDS {
+        return;
+    }
+    *wait = 0.0;
+    let (from, move_range, attack_range, attack) =
+        (position.cell, unit.move_range, unit.attack_range, unit.attack);
+
+    let targets: Vec<(Entity, UVec2)> = units
+        .iter()
+        .filter(|(_, u, _)| u.team == Team::Player)
+        .map(|(e, _, p)| (e, p.cell))
+        .collect();
+
+    match queue.phase {
+        TurnPhase::Move => {
+            let blocked = movement_blockers(tactics, units.iter().map(|(e, _, p)| (e, p)), active);
+            let reachable = reachable_cells(board, &blocked, from, move_range);
+
+            // Cella raggiungibile più vicina ad un bersaglio, preferendo quelle già a tiro.
+            let destination = reachable
+                .keys()
+                .copied()
+                .min_by_key(|&cell| {
+                    let nearest = targets
+                        .iter()
+                        .map(|&(_, t)| manhattan(cell, t))
+                        .min()
+                        .unwrap_or(0);
+                    (nearest.saturating_sub(attack_range), reachable[&cell], cell.x, cell.y)
+                })
+                .unwrap_or(from);
+            if let Ok((_, _, mut position)) = units.get_mut(active) {
+                position.cell = destination;
+            }
+            queue.phase = TurnPhase::Attack;
+        }
+        TurnPhase::Attack => {
+            let target = targets
+                .iter()
+                .filter(|&&(_, t)| manhattan(from, t) <= attack_range)
+                .min_by_key(|&&(_, t)| manhattan(from, t));
+            if let Some(&(target, _)) = target {
+                if let Ok((_, mut target_unit, _)) = units.get_mut(target) {
+                    strike(&mut commands, &mut queue, target, &mut target_unit, attack);
+                }
+            }
+            queue.end_turn();
+        }
+    }
+}
+
+fn tactics_ui(
+    mut context: NonSendMut<ImguiContext>,
+    mut queue: ResMut<TurnQueue>,
+    units: Query<(Entity, &Unit, &GridPosition)>,
+    hovered: Res<HoveredCell>,
+) {
+    let ui = context.ui();
+    ui.window("Tactics")
+        .size([300.0, 320.0], imgui::Condition::FirstUseEver)
+        .build(|| {
+            ui.text(format!("Round {}", queue.round + 1));
+            let active = queue.active().and_then(|e| units.get(e).ok());
+            match (active, queue.winner) {
+                (_, Some(team)) => {
+                    ui.text_colored([1.0, 0.9, 0.2, 1.0], format!("{:?} wins", team));
+                }
+                (Some((_, unit, _)), None) => {
+                    let phase = match queue.phase {
+                        TurnPhase::Move => "move",
+                        TurnPhase::Attack => "attack",
+                    };
+                    ui.text(format!("Turn: {} ({:?}) - {}", unit.name, unit.team, phase));
+                }
+                (None, None) => ui.text("No units"),
+            }
+
+            let player_turn = active.is_some_and(|(_, u, _)| u.team == Team::Player);
+            if player_turn {
+                if queue.phase == TurnPhase::Move && ui.button("Skip move") {
+                    queue.phase = TurnPhase::Attack;
+                }
+                if ui.button("End turn") {
+                    queue.end_turn();
+                }
+            }
+
+            ui.separator();
+            for &entity in queue.order.iter() {
+                if let Ok((_, unit, position)) = units.get(entity) {
+                    ui.text(format!(
+                        "{:<8} {:?}  hp {}/{}  ({}, {})",
+                        unit.name, unit.team, unit.hp, unit.max_hp, position.cell.x, position.cell.y
+                    ));
+                }
+            }
+
+            if let Some(cell) = hovered.0 {
+                ui.separator();
+                ui.text(format!("Hover: ({}, {})", cell.x, cell.y));
+            }
+        });
+}
+
+pub struct TacticsPlugin;
+impl Plugin for TacticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TurnQueue>()
+            .init_resource::<HoveredCell>()
+            .add_systems(Startup, setup_tactics_map)
+            .add_systems(
+                Update,
+                (
+                    update_hovered_cell.after(crate::cursor::calc_cursor_pos),
+                    handle_tactics_clicks,
+                    check_victory,
+                    enemy_turn,
+                    sync_unit_transforms,
+                    highlight_tiles,
+                    tactics_ui,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(list: &[(u32, u32)]) -> HashSet<UVec2> {
+        list.iter().map(|&(x, y)| UVec2::new(x, y)).collect()
+    }
+
+    fn is_walk(path: &[UVec2], blocked: &HashSet<UVec2>) -> bool {
+        path.windows(2).all(|step| manhattan(step[0], step[1]) == 1)
+            && path.iter().all(|cell| !blocked.contains(cell))
+    }
+
+    #[test]
+    fn straight_path_when_open() {
+        let board = Board::new(5, 5);
+        let path = find_path(&board, &HashSet::new(), UVec2::new(0, 0), UVec2::new(3, 2)).unwrap();
+        assert_eq!(path.first(), Some(&UVec2::new(0, 0)));
+        assert_eq!(path.last(), Some(&UVec2::new(3, 2)));
+        assert_eq!(path.len(), 6);
+        assert!(is_walk(&path, &HashSet::new()));
+
+        let same = find_path(&board, &HashSet::new(), UVec2::new(2, 2), UVec2::new(2, 2));
+        assert_eq!(same, Some(vec![UVec2::new(2, 2)]));
+    }
+
+    #[test]
+    fn path_goes_around_blocked_tiles() {
+        // Muro in x = 2 con un solo varco in alto.
+        let board = Board::new(5, 5);
+        let blocked = cells(&[(2, 0), (2, 1), (2, 2), (2, 3)]);
+        let path = find_path(&board, &blocked, UVec2::new(0, 0), UVec2::new(4, 0)).unwrap();
+        assert!(is_walk(&path, &blocked));
+        assert!(path.contains(&UVec2::new(2, 4)));
+        assert_eq!(path.len() - 1, 12);
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_path() {
+        let board = Board::new(5, 5);
+        let wall = cells(&[(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]);
+        assert_eq!(find_path(&board, &wall, UVec2::new(0, 0), UVec2::new(4, 4)), None);
+        assert_eq!(find_path(&board, &wall, UVec2::new(0, 0), UVec2::new(5, 0)), None);
+
+        let reachable = reachable_cells(&board, &wall, UVec2::new(0, 0), 20);
+        assert_eq!(reachable.len(), 10);
+        assert!(reachable.keys().all(|cell| cell.x < 2));
+    }
+
+    #[test]
+    fn occupied_goal_has_no_path() {
+        let board = Board::new(4, 4);
+        let mover = Entity::from_raw(1);
+        let other = Entity::from_raw(2);
+        let map = TacticsMap::default();
+        let mover_cell = GridPosition { cell: UVec2::new(0, 0) };
+        let other_cell = GridPosition { cell: UVec2::new(2, 0) };
+        let units = [(mover, &mover_cell), (other, &other_cell)];
+
+        let blocked = movement_blockers(&map, units.into_iter(), mover);
+        assert!(!blocked.contains(&mover_cell.cell));
+        assert_eq!(find_path(&board, &blocked, mover_cell.cell, other_cell.cell), None);
+        assert!(!reachable_cells(&board, &blocked, mover_cell.cell, 3).contains_key(&other_cell.cell));
+
+        let path = find_path(&board, &blocked, mover_cell.cell, UVec2::new(3, 0)).unwrap();
+        assert!(is_walk(&path, &blocked));
+        assert_eq!(path.len() - 1, 5);
+    }
+
+    #[test]
+    fn range_limit_is_inclusive() {
+        let board = Board::new(7, 7);
+        let blocked = cells(&[(3, 2), (2, 3), (4, 3)]);
+        let start = UVec2::new(3, 3);
+        let reachable = reachable_cells(&board, &blocked, start, 3);
+
+        assert_eq!(reachable[&start], 0);
+        assert!(reachable.values().all(|&steps| steps <= 3));
+        // Cella a esattamente 3 passi, e la stessa distanza dal percorso di A*.
+        assert_eq!(reachable.get(&UVec2::new(3, 6)), Some(&3));
+        assert_eq!(reachable.get(&UVec2::new(3, 0)), None);
+        for (&cell, &steps) in &reachable {
+            let path = find_path(&board, &blocked, start, cell).unwrap();
+            assert_eq!(path.len() as u32 - 1, steps, "cost to {}", cell);
+        }
+        assert!(reachable.keys().all(|cell| !blocked.contains(cell)));
+    }
+}
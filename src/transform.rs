@@ -1,3 +1,4 @@
+use crate::input::{Action, ActionState, InputBindings, UiFocus};
 use bevy::picking::prelude::*;
 use bevy::prelude::*;
 use bevy_mod_imgui::prelude::*;
@@ -10,6 +11,15 @@ use bevy_rapier3d::prelude::*;
 #[derive(Component)]
 pub struct Selected;
 
+/// Radice della gerarchia di `entity`: l'oggetto di scena a cui appartiene un osso o una mesh.
+pub fn scene_root(entity: Entity, parents: &Query<&ChildOf>) -> Entity {
+    let mut root = entity;
+    while let Ok(child_of) = parents.get(root) {
+        root = child_of.parent();
+    }
+    root
+}
+
 #[derive(Resource, Default)]
 pub struct TransformGizmoState {
     pub selected_entity: Option<Entity>,
@@ -31,9 +41,15 @@ fn handle_selection(
     mut commands: Commands,
     mut gizmo_state: ResMut<TransformGizmoState>,
     mut click_events: EventReader<Pointer<Click>>,
+    focus: Res<UiFocus>,
     pickable_query: Query<Entity, With<Pickable>>,
     selected_query: Query<Entity, With<Selected>>,
 ) {
+    // Un click su un pannello imgui non seleziona quello che c'è sotto.
+    if focus.mouse {
+        click_events.clear();
+        return;
+    }
     for click in click_events.read() {
         let entity = click.target;
         if pickable_query.get(entity).is_ok() {
@@ -49,11 +65,11 @@ fn handle_selection(
 
 fn handle_deselection(
     mut commands: Commands,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     selected_query: Query<Entity, With<Selected>>,
     mut gizmo_state: ResMut<TransformGizmoState>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Deselect) {
         for entity in selected_query.iter() {
             commands.entity(entity).remove::<Selected>();
         }
@@ -64,21 +80,20 @@ fn handle_deselection(
 
 
 fn handle_transform(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mouse_button: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
     mut gizmo_state: ResMut<TransformGizmoState>,
     windows: Query<&Window>,
     mut selected_query: Query<&mut Transform, With<Selected>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyG) {
+    if actions.just_pressed(Action::GizmoTranslate) {
         gizmo_state.mode = TransformMode::Translate;
         info!("Modalità: Translate");
     }
-    if keyboard.just_pressed(KeyCode::KeyR) {
+    if actions.just_pressed(Action::GizmoRotate) {
         gizmo_state.mode = TransformMode::Rotate;
         info!("Modalità: Rotate");
     }
-    if keyboard.just_pressed(KeyCode::KeyS) {
+    if actions.just_pressed(Action::GizmoScale) {
         gizmo_state.mode = TransformMode::Scale;
         info!("Modalità: Scale");
     }
@@ -91,7 +106,7 @@ fn handle_transform(
         return;
     };
 
-    if mouse_button.just_pressed(MouseButton::Right) {
+    if actions.just_pressed(Action::GizmoDrag) {
         gizmo_state.is_dragging = true;
         gizmo_state.initial_transform = *transform;
         if let Ok(window) = windows.single() {
@@ -101,7 +116,7 @@ fn handle_transform(
         }
     }
 
-    if mouse_button.just_released(MouseButton::Right) {
+    if actions.just_released(Action::GizmoDrag) {
         gizmo_state.is_dragging = false;
     }
 
@@ -114,8 +129,14 @@ fn handle_transform(
                         let speed = 0.01;
                         transform.translation.x =
                             gizmo_state.initial_transform.translation.x + delta.x * speed;
-                        transform.translation.z =
-                            gizmo_state.initial_transform.translation.z - delta.y * speed;
+                        // Con GizmoVertical il trascinamento verticale muove su Y invece che su Z.
+                        if actions.pressed(Action::GizmoVertical) {
+                            transform.translation.y =
+                                gizmo_state.initial_transform.translation.y - delta.y * speed;
+                        } else {
+                            transform.translation.z =
+                                gizmo_state.initial_transform.translation.z - delta.y * speed;
+                        }
                     }
                     TransformMode::Rotate => {
                         let speed = 0.01;
@@ -139,7 +160,7 @@ fn handle_transform(
 
 fn handle_duplication(
     mut commands: Commands,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     selected_query: Query<
         (
             Entity,
@@ -149,7 +170,7 @@ fn handle_duplication(
         ),
         With<Selected>>
 ) {
-    if keyboard.pressed(KeyCode::ShiftLeft) && keyboard.just_pressed(KeyCode::KeyD) {
+    if actions.just_pressed(Action::Duplicate) {
         for (entity, transform, mesh, material) in selected_query.iter() {
             let mut new_transform = *transform;
             new_transform.translation.x += 2.0;
@@ -171,11 +192,11 @@ fn handle_duplication(
 
 fn handle_deletion(
     mut commands: Commands,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     selected_query: Query<Entity, With<Selected>>,
     mut gizmo_state: ResMut<TransformGizmoState>,
 ) {
-    if keyboard.just_pressed(KeyCode::Delete) || keyboard.just_pressed(KeyCode::KeyX) {
+    if actions.just_pressed(Action::Delete) {
         for entity in selected_query.iter() {
             commands.entity(entity).despawn();
             info!("Eliminato: {:?}", entity);
@@ -224,13 +245,13 @@ fn draw_selection_outline(
 
 fn gizmo_controls_ui(
     mut context: NonSendMut<ImguiContext>,
+    bindings: Res<InputBindings>,
     mut gizmo_state: ResMut<TransformGizmoState>,
     mut selected_query: Query<&mut Transform, With<Selected>>,
 ) {
     let ui = context.ui();
     let window = ui.window("Transform Gizmo");
     window
-        .position([900.0, 300.0], imgui::Condition::FirstUseEver)
         .size([320.0, 500.0], imgui::Condition::FirstUseEver)
         .build(|| {
             ui.text("Transform Controls");
@@ -246,13 +267,16 @@ fn gizmo_controls_ui(
 
             if ui.collapsing_header("Transform Mode", imgui::TreeNodeFlags::DEFAULT_OPEN) {
                 let mut current_mode = gizmo_state.mode;
-                if ui.radio_button("Translate (G)", &mut current_mode, TransformMode::Translate) {
+                let translate = format!("Translate ({})", bindings.describe(Action::GizmoTranslate));
+                let rotate = format!("Rotate ({})", bindings.describe(Action::GizmoRotate));
+                let scale = format!("Scale ({})", bindings.describe(Action::GizmoScale));
+                if ui.radio_button(&translate, &mut current_mode, TransformMode::Translate) {
                     gizmo_state.mode = TransformMode::Translate;
                 }
-                if ui.radio_button("Rotate (R)", &mut current_mode, TransformMode::Rotate) {
+                if ui.radio_button(&rotate, &mut current_mode, TransformMode::Rotate) {
                     gizmo_state.mode = TransformMode::Rotate;
                 }
-                if ui.radio_button("Scale (S)", &mut current_mode, TransformMode::Scale) {
+                if ui.radio_button(&scale, &mut current_mode, TransformMode::Scale) {
                     gizmo_state.mode = TransformMode::Scale;
                 }
             }
@@ -440,13 +464,19 @@ fn gizmo_controls_ui(
 
             if ui.collapsing_header("Keyboard Shortcuts", imgui::TreeNodeFlags::empty()) {
                 ui.bullet_text("Left Click: Select");
-                ui.bullet_text("Right Click + Drag: Transform");
-                ui.bullet_text("G: Translate mode");
-                ui.bullet_text("R: Rotate mode");
-                ui.bullet_text("S: Scale mode");
-                ui.bullet_text("Shift+D: Duplicate");
-                ui.bullet_text("X/Delete: Delete");
-                ui.bullet_text("ESC: Deselect");
+                // I binding si cambiano dal pannello "Input Bindings".
+                for (action, text) in [
+                    (Action::GizmoDrag, "Transform (drag)"),
+                    (Action::GizmoVertical, "move on Y while translating"),
+                    (Action::GizmoTranslate, "Translate mode"),
+                    (Action::GizmoRotate, "Rotate mode"),
+                    (Action::GizmoScale, "Scale mode"),
+                    (Action::Duplicate, "Duplicate"),
+                    (Action::Delete, "Delete"),
+                    (Action::Deselect, "Deselect"),
+                ] {
+                    ui.bullet_text(format!("{}: {}", bindings.describe(action), text));
+                }
             }
         });
 }
@@ -461,7 +491,6 @@ fn entity_list_ui(
     let ui = context.ui();
     let window = ui.window("Entity List");
     window
-        .position([10.0, 810.0], imgui::Condition::FirstUseEver)
         .size([320.0, 250.0], imgui::Condition::FirstUseEver)
         .build(|| {
             ui.text(format!(
@@ -516,11 +545,17 @@ pub struct TransformGizmoPlugin;
 impl Plugin for TransformGizmoPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TransformGizmoState>()
-            .add_systems(Update, handle_selection)
-            .add_systems(Update, handle_transform)
-            .add_systems(Update, handle_deselection)
-            .add_systems(Update, handle_duplication)
-            .add_systems(Update, handle_deletion)
+            .add_systems(
+                Update,
+                (
+                    handle_selection,
+                    handle_transform,
+                    handle_deselection,
+                    handle_duplication,
+                    handle_deletion,
+                )
+                    .in_set(crate::play_mode::EditSet),
+            )
             .add_systems(Update, draw_selection_outline)
             .add_systems(Update, gizmo_controls_ui)
             .add_systems(Update, entity_list_ui);
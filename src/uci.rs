@@ -0,0 +1,196 @@
+use crate::chess_ai::SearchLimits;
+use crate::chess_rules::{Move, Position};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the engine may take to answer `uci` / `isready`.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Extra time granted past `movetime` before `stop` is sent, and again after it.
+const MOVE_GRACE: Duration = Duration::from_secs(2);
+
+/// Minimal UCI client for an external engine binary (Stockfish, etc.).
+///
+/// The engine output is read on its own thread, so every wait has a deadline
+/// and a hung engine turns into an error instead of a stuck task.
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    pub path: PathBuf,
+    pub name: String,
+}
+
+impl UciEngine {
+    /// Starts the engine and waits for the `uciok` / `readyok` handshake.
+    pub fn spawn(path: &Path) -> io::Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("engine stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("engine stdout unavailable"))?;
+
+        let (sender, lines) = mpsc::channel();
+        thread::Builder::new()
+            .name("uci-reader".to_string())
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if sender.send(line.trim().to_string()).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        let mut engine = Self {
+            child,
+            stdin,
+            lines,
+            path: path.to_path_buf(),
+            name: path.display().to_string(),
+        };
+
+        engine.send("uci")?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        loop {
+            let line = engine.read_line(deadline)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.to_string();
+            }
+            if line == "uciok" {
+                break;
+            }
+        }
+        engine.send("ucinewgame")?;
+        engine.wait_ready()?;
+        Ok(engine)
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+
+    fn read_line(&mut self, deadline: Instant) -> io::Result<String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.lines.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => {
+                io::Error::new(io::ErrorKind::TimedOut, "engine did not answer in time")
+            }
+            RecvTimeoutError::Disconnected => io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "engine closed its output",
+            ),
+        })
+    }
+
+    fn wait_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        while self.read_line(deadline)? != "readyok" {}
+        Ok(())
+    }
+
+    /// Asks the engine for the best move after playing `moves` from `start`.
+    pub fn best_move(
+        &mut self,
+        start: &Position,
+        moves: &[Move],
+        limits: SearchLimits,
+    ) -> io::Result<Option<Move>> {
+        let mut command = format!("position fen {}", start.to_fen());
+        if !moves.is_empty() {
+            command.push_str(" moves");
+            for mv in moves {
+                command.push(' ');
+                command.push_str(&mv.to_uci());
+            }
+        }
+        self.send(&command)?;
+        self.wait_ready()?;
+        self.send(&format!(
+            "go depth {} movetime {}",
+            limits.depth,
+            limits.time.as_millis()
+        ))?;
+
+        // Past movetime the search is stopped; an engine that ignores `stop` is an error.
+        let mut deadline = Instant::now() + limits.time + MOVE_GRACE;
+        let mut stopped = false;
+        loop {
+            let line = match self.read_line(deadline) {
+                Err(e) if e.kind() == io::ErrorKind::TimedOut && !stopped => {
+                    self.send("stop")?;
+                    stopped = true;
+                    deadline = Instant::now() + MOVE_GRACE;
+                    continue;
+                }
+                line => line?,
+            };
+            if let Some(best) = parse_bestmove(&line) {
+                return Ok(best);
+            }
+        }
+    }
+}
+
+/// The move of a `bestmove` line (`None` for `bestmove (none)`); other lines
+/// (`info`, `id`, ...) return `None` and are skipped.
+fn parse_bestmove(line: &str) -> Option<Option<Move>> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next()? != "bestmove" {
+        return None;
+    }
+    Some(tokens.next().and_then(Move::from_uci))
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bestmove_lines() {
+        let e2e4 = Move::from_uci("e2e4");
+        assert_eq!(parse_bestmove("bestmove e2e4"), Some(e2e4));
+        assert_eq!(parse_bestmove("bestmove e2e4 ponder e7e5"), Some(e2e4));
+        assert_eq!(
+            parse_bestmove("bestmove a7a8q").flatten().map(Move::to_uci).as_deref(),
+            Some("a7a8q")
+        );
+        assert_eq!(parse_bestmove("bestmove (none)"), Some(None));
+        assert_eq!(parse_bestmove("bestmove"), Some(None));
+    }
+
+    #[test]
+    fn info_lines_are_skipped() {
+        for line in [
+            "info depth 12 seldepth 18 score cp 31 nodes 123456 pv e2e4 e7e5",
+            "info string bestmove e2e4",
+            "id name Stockfish 16",
+            "readyok",
+            "bestmovee2e4",
+            "",
+        ] {
+            assert_eq!(parse_bestmove(line), None, "parsed {:?}", line);
+        }
+    }
+}
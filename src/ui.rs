@@ -3,6 +3,7 @@ use bevy::render::camera::{OrthographicProjection, PerspectiveProjection, Projec
 use bevy_mod_imgui::prelude::*;
 
 use crate::pp::PostProcessSettings;
+use crate::shader_params::ShaderParams;
 // =======================================
 // Shader Editor
 // =======================================
@@ -16,7 +17,6 @@ fn shader_editor(
     let mut height = retro_target.height as i32;
     let window = ui.window("Shader Editor");
     window
-        .position([300., 1000.0], imgui::Condition::FirstUseEver)
         .size([300.0, 600.0], imgui::Condition::FirstUseEver)
         .build(|| {
             ui.text("Shader Settings");
@@ -31,27 +31,9 @@ fn shader_editor(
                 }
             }
             for mut settings in query.iter_mut() {
-                if ui.collapsing_header("Basic Settings", imgui::TreeNodeFlags::DEFAULT_OPEN) {
-                    ui.slider("Cel Shading Levels", 0.0, 20.0, &mut settings.cel_levels);
-                    ui.slider("Contrast", 1.0, 10.0, &mut settings.contrast);
-                    ui.slider("Saturation", 1.0, 10.0, &mut settings.saturation);
-                    ui.slider(
-                        "Scanline Intensity",
-                        0.0,
-                        10.0,
-                        &mut settings.scanline_intensity,
-                    );
-                }
-                if ui.collapsing_header("Advanced", imgui::TreeNodeFlags::empty()) {
-                    ui.slider("Dithering", 0.0, 1.0, &mut settings.dithering_strength);
-                    ui.slider("Edge Threshold", 0.0, 1.0, &mut settings.edge_threshold);
-                    ui.slider("Edge Denoise", 0.0, 1.0, &mut settings.edge_denoise);
-                    ui.slider(
-                        "Color Snap Strength",
-                        0.0,
-                        1.0,
-                        &mut settings.color_snap_strength,
-                    );
+                // Controlli generati dalla dichiarazione di PostProcessSettings (pp.rs)
+                if ui.collapsing_header("Parameters", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    settings.edit_ui(ui);
                 }
 
                 // Toggle palette
@@ -90,33 +72,7 @@ fn shader_editor(
 
                     ui.separator();
 
-                    // Preset buttons
-                    if ui.button("Grayscale Preset") {
-                        apply_grayscale_preset(&mut settings);
-                    }
-                    ui.same_line();
-                    if ui.button("Retro Preset") {
-                        apply_retro_preset(&mut settings);
-                    }
-                    if ui.button("GameBoy Preset") {
-                        apply_gameboy_preset(&mut settings);
-                    }
-                    ui.same_line();
-                    if ui.button("NES Preset") {
-                        apply_nes_preset(&mut settings);
-                    }
-                    ui.separator();
-                    if ui.button("Grayscale 32 Preset") {
-                        apply_grayscale32_preset(&mut settings);
-                    }
-                    ui.same_line();
-                    if ui.button("Vibrant 32 Preset") {
-                        apply_vibrant32_preset(&mut settings);
-                    }
-                    ui.same_line();
-                    if ui.button("Pastel 32 Preset") {
-                        apply_pastel32_preset(&mut settings);
-                    }
+                    // I preset (GameBoy, NES, ...) sono file in assets/post_process.
                     if ui.button("Randomize Palette") {
                         randomize_palette(&mut settings);
                     }
@@ -136,7 +92,6 @@ fn light_color_picker(
     let window = ui.window("Light Color");
 
     window
-        .position([900.0, 1000.0], imgui::Condition::FirstUseEver)
         .size([300.0, 150.0], imgui::Condition::FirstUseEver)
         .build(|| {
             ui.text("Light Color Picker");
@@ -199,9 +154,13 @@ fn light_color_picker(
 // =======================================
 // Camera Controls
 // =======================================
+#[allow(clippy::type_complexity)]
 fn camera_controls_ui(
     mut context: NonSendMut<ImguiContext>,
-    mut q_projection: Query<&mut Projection, With<Camera3d>>,
+    mut q_projection: Query<
+        (&Camera, &mut Projection),
+        (With<Camera3d>, Without<crate::layout::ViewportCamera>),
+    >,
     mut q_retro_sprite: Query<Entity, With<crate::retrocamera::RetroScreen>>,
     mut commands: Commands,
     target: Option<Res<crate::retrocamera::RetroRenderTarget>>,
@@ -211,7 +170,6 @@ fn camera_controls_ui(
     let window = ui.window("Camera Controls");
 
     window
-        .position([620.0, 1000.0], imgui::Condition::FirstUseEver)
         .size([320.0, 120.0], imgui::Condition::FirstUseEver)
         .build(|| {
             if ui.button("Toggle RetroCamera") {
@@ -237,7 +195,9 @@ fn camera_controls_ui(
             ui.same_line();
 
             if ui.button("Toggle Ortho/Perspective") {
-                if let Ok(mut proj) = q_projection.single_mut() {
+                if let Some((_, mut proj)) =
+                    q_projection.iter_mut().find(|(camera, _)| camera.is_active)
+                {
                     *proj = match &*proj {
                         Projection::Perspective(_) => {
                             Projection::Orthographic(OrthographicProjection::default_3d())
@@ -256,139 +216,6 @@ fn camera_controls_ui(
 // Palette Helpers
 // =======================================
 
-fn apply_grayscale_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 8;
-    for i in 0..32 {
-        let t = i as f32 / 7.0;
-        settings.palette[i] = if i < 8 {
-            Vec4::new(t, t, t, 1.0)
-        } else {
-            Vec4::ZERO
-        };
-    }
-}
-
-fn apply_retro_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 8;
-    let preset = [
-        Vec4::new(0.09, 0.05, 0.11, 1.0),
-        Vec4::new(0.8, 0.2, 0.2, 1.0),
-        Vec4::new(1.0, 0.6, 0.3, 1.0),
-        Vec4::new(1.0, 0.9, 0.3, 1.0),
-        Vec4::new(0.3, 0.7, 0.2, 1.0),
-        Vec4::new(0.3, 0.5, 0.9, 1.0),
-        Vec4::new(0.9, 0.3, 0.6, 1.0),
-        Vec4::new(1.0, 1.0, 1.0, 1.0),
-    ];
-    for i in 0..32 {
-        settings.palette[i] = if i < preset.len() {
-            preset[i]
-        } else {
-            Vec4::ZERO
-        };
-    }
-}
-
-fn apply_gameboy_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 4;
-    let preset = [
-        Vec4::new(0.06, 0.22, 0.06, 1.0),
-        Vec4::new(0.19, 0.38, 0.19, 1.0),
-        Vec4::new(0.55, 0.68, 0.06, 1.0),
-        Vec4::new(0.61, 0.74, 0.06, 1.0),
-    ];
-    for i in 0..32 {
-        settings.palette[i] = if i < preset.len() {
-            preset[i]
-        } else {
-            Vec4::ZERO
-        };
-    }
-}
-
-fn apply_nes_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 8;
-    let preset = [
-        Vec4::new(0.0, 0.0, 0.0, 1.0),
-        Vec4::new(0.9, 0.1, 0.1, 1.0),
-        Vec4::new(1.0, 0.5, 0.0, 1.0),
-        Vec4::new(1.0, 1.0, 0.0, 1.0),
-        Vec4::new(0.0, 0.8, 0.0, 1.0),
-        Vec4::new(0.0, 0.5, 1.0, 1.0),
-        Vec4::new(0.6, 0.2, 0.8, 1.0),
-        Vec4::new(1.0, 1.0, 1.0, 1.0),
-    ];
-    for i in 0..32 {
-        settings.palette[i] = if i < preset.len() {
-            preset[i]
-        } else {
-            Vec4::ZERO
-        };
-    }
-}
-
-fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Vec3 {
-    let c = v * s;
-    let h6 = (h * 6.0).fract();
-    let x = c * (1.0 - ((h6 * 2.0 - 1.0).abs()));
-    let (r1, g1, b1) = if h6 < 1.0 {
-        (c, x, 0.0)
-    } else if h6 < 2.0 {
-        (x, c, 0.0)
-    } else if h6 < 3.0 {
-        (0.0, c, x)
-    } else if h6 < 4.0 {
-        (0.0, x, c)
-    } else if h6 < 5.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-    let m = v - c;
-    Vec3::new(r1 + m, g1 + m, b1 + m)
-}
-
-fn apply_grayscale32_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 32;
-    for i in 0..32 {
-        let t = i as f32 / 31.0;
-        settings.palette[i] = Vec4::new(t, t, t, 1.0);
-    }
-}
-
-fn apply_vibrant32_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 32;
-    for h_idx in 0..8 {
-        let h = h_idx as f32 / 8.0;
-        for v_idx in 0..4 {
-            let idx = h_idx * 4 + v_idx;
-            let v = match v_idx {
-                0 => 1.0,
-                1 => 0.8,
-                2 => 0.6,
-                _ => 0.4,
-            };
-            let s = 0.95;
-            let rgb = hsv_to_rgb(h, s, v);
-            settings.palette[idx] = Vec4::new(rgb.x, rgb.y, rgb.z, 1.0);
-        }
-    }
-}
-
-fn apply_pastel32_preset(settings: &mut PostProcessSettings) {
-    settings.color_count_and_pad.x = 32;
-    for h_idx in 0..8 {
-        let h = h_idx as f32 / 8.0;
-        for v_idx in 0..4 {
-            let idx = h_idx * 4 + v_idx;
-            let s = 0.35 + (v_idx as f32) * 0.05;
-            let v = 0.85 + (v_idx as f32) * 0.05;
-            let rgb = hsv_to_rgb(h, s.min(0.6), v.min(1.0));
-            settings.palette[idx] = Vec4::new(rgb.x, rgb.y, rgb.z, 1.0);
-        }
-    }
-}
-
 fn randomize_palette(settings: &mut PostProcessSettings) {
     use rand::Rng;
     let mut rng = rand::rng();